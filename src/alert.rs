@@ -0,0 +1,153 @@
+use crate::config::SoundAlertConfig;
+use crate::log_parser::LogLevel;
+use log::{info, warn};
+use regex::Regex;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Plays a sound when a tailed line matches an alert pattern or reaches the configured
+/// level, at most once per `min_interval` so a burst of errors is a single alert
+pub struct SoundAlert {
+    pub enabled: bool,
+    sound_file: Option<PathBuf>,
+    min_level: Option<LogLevel>,
+    patterns: Vec<Regex>,
+    min_interval: Duration,
+    last_played: Option<Instant>,
+}
+
+impl SoundAlert {
+    pub fn from_config(config: &SoundAlertConfig) -> Self {
+        let patterns = config
+            .patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    warn!("Ignoring invalid alert pattern '{}': {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+
+        let min_level = config.min_level.as_deref().and_then(|name| {
+            let level = LogLevel::from_name(name);
+            if level.is_none() {
+                warn!("Ignoring unknown alert level '{}'", name);
+            }
+            level
+        });
+
+        Self {
+            enabled: config.enabled,
+            sound_file: config
+                .sound_file
+                .as_deref()
+                .map(|path| PathBuf::from(crate::VisGrepApp::expand_tilde(path))),
+            min_level,
+            patterns,
+            min_interval: Duration::from_secs(config.min_interval_secs),
+            last_played: None,
+        }
+    }
+
+    /// True if the line should raise an alert
+    pub fn matches(&self, line: &str, level: LogLevel) -> bool {
+        let level_hit = self
+            .min_level
+            .is_some_and(|min| level != LogLevel::Unknown && level.severity() >= min.severity());
+        level_hit || self.patterns.iter().any(|regex| regex.is_match(line))
+    }
+
+    /// Play the alert unless one played within the last `min_interval`. Returns true if it played.
+    pub fn trigger(&mut self, now: Instant) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.last_played.is_some_and(|last| now.duration_since(last) < self.min_interval) {
+            return false;
+        }
+        self.last_played = Some(now);
+        info!("Sound alert");
+        play(self.sound_file.clone());
+        true
+    }
+}
+
+/// Play the sound file (or a short beep) on a background thread so the UI never waits on audio
+#[cfg(feature = "sound")]
+fn play(sound_file: Option<PathBuf>) {
+    use rodio::Source;
+
+    std::thread::spawn(move || {
+        let result = (|| -> Result<(), String> {
+            let (_stream, handle) = rodio::OutputStream::try_default().map_err(|e| e.to_string())?;
+            let sink = rodio::Sink::try_new(&handle).map_err(|e| e.to_string())?;
+            match &sound_file {
+                Some(path) => {
+                    let file = std::fs::File::open(path)
+                        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+                    let source = rodio::Decoder::new(std::io::BufReader::new(file)).map_err(|e| e.to_string())?;
+                    sink.append(source);
+                }
+                None => {
+                    let beep = rodio::source::SineWave::new(880.0)
+                        .take_duration(Duration::from_millis(200))
+                        .amplify(0.2);
+                    sink.append(beep);
+                }
+            }
+            sink.sleep_until_end();
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            log::error!("Failed to play alert sound: {}", e);
+        }
+    });
+}
+
+/// Built without the `sound` feature: ring the terminal bell instead
+#[cfg(not(feature = "sound"))]
+fn play(sound_file: Option<PathBuf>) {
+    if sound_file.is_some() {
+        warn!("Alert sound file set, but this build has no audio support (enable the `sound` feature)");
+    }
+    eprint!("\x07");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(min_level: Option<&str>, patterns: &[&str]) -> SoundAlert {
+        SoundAlert::from_config(&SoundAlertConfig {
+            enabled: true,
+            sound_file: None,
+            min_level: min_level.map(str::to_string),
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            min_interval_secs: 10,
+        })
+    }
+
+    #[test]
+    fn test_matches_level_or_pattern() {
+        let alert = alert(Some("error"), &["35=3"]);
+        assert!(alert.matches("boom", LogLevel::Fatal));
+        assert!(alert.matches("boom", LogLevel::Error));
+        assert!(!alert.matches("careful", LogLevel::Warn));
+        assert!(alert.matches("8=FIX|35=3|", LogLevel::Unknown));
+    }
+
+    #[test]
+    fn test_trigger_is_rate_limited() {
+        let mut alert = alert(None, &[]);
+        // Pretend one just played, so nothing actually sounds during the test
+        let start = Instant::now();
+        alert.last_played = Some(start);
+        assert!(!alert.trigger(start + Duration::from_secs(5)));
+
+        alert.enabled = false;
+        assert!(!alert.trigger(start + Duration::from_secs(60)));
+    }
+}
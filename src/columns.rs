@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+
+/// Delimiters offered by the column view, as (label, delimiter)
+pub const DELIMITER_PRESETS: &[(&str, &str)] = &[
+    ("Comma", ","),
+    ("Tab", "\t"),
+    ("Pipe", "|"),
+    ("Semicolon", ";"),
+    ("FIX SOH", "\x01"),
+];
+
+/// Column widths beyond this are not padded further, so one long field doesn't push
+/// everything after it off screen
+const MAX_COLUMN_WIDTH: usize = 60;
+
+/// Column view settings for the tail output
+pub struct ColumnViewState {
+    pub enabled: bool,
+    pub delimiter: String,
+    /// Hidden column indices (0-based)
+    pub hidden: HashSet<usize>,
+    /// Most columns seen in the last rendered window, for the show/hide menu
+    pub column_count: usize,
+}
+
+impl ColumnViewState {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            delimiter: ",".to_string(),
+            hidden: HashSet::new(),
+            column_count: 0,
+        }
+    }
+
+    pub fn delimiter_label(&self) -> &'static str {
+        DELIMITER_PRESETS
+            .iter()
+            .find(|(_, delimiter)| *delimiter == self.delimiter)
+            .map(|(label, _)| *label)
+            .unwrap_or("Custom")
+    }
+}
+
+/// Width (in chars) of each column over a set of lines
+pub fn column_widths<'a>(lines: impl Iterator<Item = &'a str>, delimiter: &str) -> Vec<usize> {
+    let mut widths: Vec<usize> = Vec::new();
+    for line in lines {
+        for (idx, field) in line.split(delimiter).enumerate() {
+            let width = field.chars().count().min(MAX_COLUMN_WIDTH);
+            if idx >= widths.len() {
+                widths.push(width);
+            } else {
+                widths[idx] = widths[idx].max(width);
+            }
+        }
+    }
+    widths
+}
+
+/// Lay a line out as padded columns, skipping hidden ones. Meant for monospace rendering.
+pub fn format_columns(line: &str, delimiter: &str, widths: &[usize], hidden: &HashSet<usize>) -> String {
+    let fields: Vec<String> = line
+        .split(delimiter)
+        .enumerate()
+        .filter(|(idx, _)| !hidden.contains(idx))
+        .map(|(idx, field)| {
+            let width = widths.get(idx).copied().unwrap_or(0);
+            format!("{:<width$}", field, width = width)
+        })
+        .collect();
+
+    fields.join(" │ ").trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns_align() {
+        let lines = ["a,bbb,c", "dddd,e,f"];
+        let widths = column_widths(lines.iter().copied(), ",");
+        assert_eq!(widths, vec![4, 3, 1]);
+
+        let hidden = HashSet::new();
+        assert_eq!(format_columns(lines[0], ",", &widths, &hidden), "a    │ bbb │ c");
+        assert_eq!(format_columns(lines[1], ",", &widths, &hidden), "dddd │ e   │ f");
+    }
+
+    #[test]
+    fn test_hidden_columns_skipped() {
+        let widths = vec![1, 1, 1];
+        let hidden: HashSet<usize> = [1].into_iter().collect();
+        assert_eq!(format_columns("a|b|c", "|", &widths, &hidden), "a │ c");
+    }
+}
@@ -0,0 +1,363 @@
+use crate::{AppMode, VisGrepApp, config::Config, export, tail_layout::TailLayout};
+use eframe::egui;
+use log::info;
+
+/// Something the palette can run
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteAction {
+    SwitchMode(AppMode),
+    ToggleTheme,
+    ToggleRedaction,
+    ToggleHelp,
+    OpenConfig,
+    RunSearch,
+    RepeatLastSearch,
+    RunSavedPattern(usize),
+    ExportResults,
+    LoadLayout,
+    SaveLayout { group_by_directory: bool },
+    ExportStatusReport,
+    CopyStatusReport,
+    TogglePauseAll,
+    ClearTailOutput,
+}
+
+pub struct PaletteEntry {
+    pub label: String,
+    pub action: PaletteAction,
+}
+
+/// Ctrl+P command palette: a filterable list of actions
+pub struct CommandPalette {
+    pub open: bool,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// Render the palette; returns the action chosen this frame, if any
+    pub fn show(&mut self, ctx: &egui::Context, entries: &[PaletteEntry]) -> Option<PaletteAction> {
+        if !self.open {
+            return None;
+        }
+
+        let filtered: Vec<&PaletteEntry> = entries
+            .iter()
+            .filter(|entry| matches_query(&entry.label, &self.query))
+            .collect();
+        self.selected = self.selected.min(filtered.len().saturating_sub(1));
+
+        // Arrow keys move the selection, Enter runs it, Escape closes
+        let (up, down, enter, escape) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowUp),
+                i.key_pressed(egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down && self.selected + 1 < filtered.len() {
+            self.selected += 1;
+        }
+
+        let mut chosen = None;
+        if enter {
+            chosen = filtered.get(self.selected).map(|entry| entry.action.clone());
+        }
+
+        egui::Window::new("Command Palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .fixed_size([450.0, 0.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command...")
+                        .desired_width(f32::INFINITY),
+                );
+                response.request_focus();
+                if response.changed() {
+                    self.selected = 0;
+                }
+
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        if filtered.is_empty() {
+                            ui.label(egui::RichText::new("No matching commands").italics().color(egui::Color32::GRAY));
+                        }
+                        for (idx, entry) in filtered.iter().enumerate() {
+                            let response = ui.selectable_label(idx == self.selected, &entry.label);
+                            if idx == self.selected && (up || down) {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                chosen = Some(entry.action.clone());
+                            }
+                        }
+                    });
+            });
+
+        if chosen.is_some() || escape {
+            self.open = false;
+        }
+        chosen
+    }
+}
+
+/// Every whitespace-separated word of the query must appear in the label (case-insensitive)
+fn matches_query(label: &str, query: &str) -> bool {
+    let label = label.to_lowercase();
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| label.contains(word))
+}
+
+impl VisGrepApp {
+    /// All actions currently offered by the command palette
+    pub fn palette_entries(&self) -> Vec<PaletteEntry> {
+        let mut entries = vec![
+            PaletteEntry { label: "Mode: Grep".to_string(), action: PaletteAction::SwitchMode(AppMode::Grep) },
+            PaletteEntry { label: "Mode: Tail".to_string(), action: PaletteAction::SwitchMode(AppMode::Tail) },
+            PaletteEntry { label: "Search: Run search".to_string(), action: PaletteAction::RunSearch },
+            PaletteEntry { label: "Search: Repeat last search".to_string(), action: PaletteAction::RepeatLastSearch },
+            PaletteEntry { label: "Search: Export results (CSV/JSON)...".to_string(), action: PaletteAction::ExportResults },
+            PaletteEntry { label: "Tail: Load layout...".to_string(), action: PaletteAction::LoadLayout },
+            PaletteEntry { label: "Tail: Save as layout...".to_string(), action: PaletteAction::SaveLayout { group_by_directory: false } },
+            PaletteEntry { label: "Tail: Save as layout, one group per directory...".to_string(), action: PaletteAction::SaveLayout { group_by_directory: true } },
+            PaletteEntry { label: "Tail: Export status report (markdown)...".to_string(), action: PaletteAction::ExportStatusReport },
+            PaletteEntry { label: "Tail: Copy status report to clipboard".to_string(), action: PaletteAction::CopyStatusReport },
+            PaletteEntry { label: "Tail: Pause/resume all".to_string(), action: PaletteAction::TogglePauseAll },
+            PaletteEntry { label: "Tail: Clear output".to_string(), action: PaletteAction::ClearTailOutput },
+            PaletteEntry { label: "View: Toggle theme".to_string(), action: PaletteAction::ToggleTheme },
+            PaletteEntry { label: "View: Toggle redaction".to_string(), action: PaletteAction::ToggleRedaction },
+            PaletteEntry { label: "View: Keyboard shortcuts".to_string(), action: PaletteAction::ToggleHelp },
+            PaletteEntry { label: "Config: Open config file".to_string(), action: PaletteAction::OpenConfig },
+        ];
+
+        for (idx, pattern) in self.config.saved_patterns.iter().enumerate() {
+            entries.push(PaletteEntry {
+                label: format!("Pattern: {} ({})", pattern.name, pattern.pattern),
+                action: PaletteAction::RunSavedPattern(idx),
+            });
+        }
+
+        entries
+    }
+
+    pub fn execute_palette_action(&mut self, action: PaletteAction) {
+        info!("Command palette: {:?}", action);
+        match action {
+            PaletteAction::SwitchMode(mode) => self.mode = mode,
+            PaletteAction::ToggleTheme => {
+                self.theme.cycle();
+                self.config.theme = self.theme;
+                if let Err(e) = self.config.save() {
+                    log::error!("Failed to save config: {}", e);
+                }
+            }
+            PaletteAction::ToggleRedaction => self.redactor.enabled = !self.redactor.enabled,
+            PaletteAction::ToggleHelp => self.show_help = !self.show_help,
+            PaletteAction::OpenConfig => self.open_config_file(),
+            PaletteAction::RunSearch => {
+                self.mode = AppMode::Grep;
+                if !self.grep_state.search_query.is_empty() {
+                    self.perform_search();
+                }
+            }
+            PaletteAction::RepeatLastSearch => self.repeat_last_search(),
+            PaletteAction::RunSavedPattern(idx) => {
+                if let Some(pattern) = self.config.saved_patterns.get(idx) {
+                    self.grep_state.search_query = pattern.pattern.clone();
+                    self.mode = AppMode::Grep;
+                    self.perform_search();
+                }
+            }
+            PaletteAction::ExportResults => self.export_results_dialog(),
+            PaletteAction::LoadLayout => self.load_layout_dialog(),
+            PaletteAction::SaveLayout { group_by_directory } => self.save_layout_dialog(group_by_directory),
+            PaletteAction::ExportStatusReport => self.export_status_report_dialog(),
+            PaletteAction::CopyStatusReport => {
+                let report = self.tail_state.status_report().to_markdown();
+                Self::copy_to_clipboard(&report, "status report");
+            }
+            PaletteAction::TogglePauseAll => {
+                self.tail_state.paused_all = !self.tail_state.paused_all;
+            }
+            PaletteAction::ClearTailOutput => {
+                self.tail_state.output_buffer.clear();
+                self.tail_state.output_rows = None;
+                self.tail_state.buffer_level_counts.clear();
+                self.tail_state.level_histogram.clear();
+                self.tail_state.expanded_lines.clear();
+                self.tail_state.total_lines_received = 0;
+                self.tail_state.lines_dropped = 0;
+            }
+        }
+    }
+
+    /// Open the config file in the editor, writing the current settings first if it doesn't exist yet
+    fn open_config_file(&self) {
+        let Some(path) = Config::config_path() else {
+            info!("Could not determine config path");
+            return;
+        };
+
+        if !path.exists() {
+            if let Err(e) = self.config.save() {
+                log::error!("Failed to create config file: {}", e);
+                return;
+            }
+        }
+
+        self.open_file_in_editor(&path, None);
+    }
+
+    fn export_results_dialog(&self) {
+        if self.grep_state.results.is_empty() {
+            info!("No results to export");
+            return;
+        }
+
+        if let Some(path) = rfd::FileDialog::new()
+            .set_file_name("vis-grep-results.csv")
+            .add_filter("CSV", &["csv"])
+            .add_filter("JSON", &["json"])
+            .save_file()
+        {
+            if let Err(e) = export::export_results(&self.grep_state.results, &self.redactor, &path) {
+                log::error!("{}", e);
+            }
+        }
+    }
+
+    /// Write a markdown snapshot of the tailed files' state
+    fn export_status_report_dialog(&self) {
+        if self.tail_state.files.is_empty() {
+            info!("No tailed files to report on");
+            return;
+        }
+
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("tail-status.md")
+            .add_filter("Markdown", &["md"])
+            .add_filter("Text", &["txt"])
+            .save_file()
+        else {
+            return;
+        };
+
+        let report = self.tail_state.status_report().to_markdown();
+        match std::fs::write(&path, report) {
+            Ok(()) => info!("Saved status report for {} files to {:?}", self.tail_state.files.len(), path),
+            Err(e) => log::error!("Failed to write status report: {}", e),
+        }
+    }
+
+    /// Write the currently tailed files out as a layout file
+    fn save_layout_dialog(&self, group_by_directory: bool) {
+        if self.tail_state.files.is_empty() {
+            info!("No tailed files to save as a layout");
+            return;
+        }
+
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("tail-layout.yaml")
+            .add_filter("YAML", &["yaml", "yml"])
+            .add_filter("JSON", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Tail Layout".to_string());
+        let layout = self.tail_state.to_layout(&name, group_by_directory);
+
+        let serialized = if TailLayout::is_json_path(&path) {
+            layout.to_json_string()
+        } else {
+            layout.to_yaml_string()
+        };
+        match serialized.and_then(|text| {
+            std::fs::write(&path, text).map_err(|e| format!("Failed to write layout file: {}", e))
+        }) {
+            Ok(()) => info!("Saved layout with {} files to {:?}", self.tail_state.files.len(), path),
+            Err(e) => log::error!("{}", e),
+        }
+    }
+
+    /// Replace the tailed files with the ones from a layout file
+    fn load_layout_dialog(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Layout", &["yaml", "yml", "json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        // Parse first so a bad layout leaves the current files being tailed
+        let layout = match TailLayout::from_file(&path) {
+            Ok(layout) => layout,
+            Err(e) => {
+                log::error!("Failed to load layout: {}", e);
+                self.tail_state.layout_problems = vec![e];
+                return;
+            }
+        };
+
+        self.tail_state.files.clear();
+        self.tail_state.output_buffer.clear();
+        self.tail_state.output_rows = None;
+        self.tail_state.buffer_level_counts.clear();
+        self.tail_state.level_histogram.clear();
+        self.tail_state.preview_selected_file = None;
+        self.tail_state.isolated_file = None;
+        self.tail_state.remember_group_states();
+        self.tail_state.layout = None;
+        self.tail_state.layout_path = None;
+        self.tail_state.layout_problems.clear();
+
+        self.tail_state.apply_layout(layout, &path);
+        info!("Loaded layout from {:?}", path);
+        self.mode = AppMode::Tail;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_query_words() {
+        assert!(matches_query("Tail: Clear output", ""));
+        assert!(matches_query("Tail: Clear output", "clear"));
+        assert!(matches_query("Tail: Clear output", "tail out"));
+        assert!(!matches_query("Tail: Clear output", "grep"));
+    }
+}
@@ -2,6 +2,9 @@ use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use crate::relative_time::RelativeTimeFormat;
+use crate::search::CountUnit;
+use crate::session::SessionMode;
 use crate::theme::Theme;
 use crate::log_parser::{LogColorScheme, LogColorPreset};
 
@@ -21,6 +24,13 @@ pub struct SavedPattern {
     pub category: String,
 }
 
+/// A named preview/tail filter, stored with its `C:`/`R:` mode prefix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub name: String,
+    pub query: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditorConfig {
     pub command: String,
@@ -65,13 +75,111 @@ impl Default for LogFormatConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UiPreferences {
-    /// Default font size for UI elements
+    /// Default font size for UI elements, updated on exit after zooming
     #[serde(default = "default_font_size")]
     pub font_size: f32,
 
     /// Tail mode file polling interval in milliseconds
     #[serde(default = "default_poll_interval")]
     pub poll_interval_ms: u64,
+
+    /// Seconds without new lines before a tailed file (and its groups) show as idle
+    #[serde(default = "default_idle_timeout")]
+    pub idle_timeout_secs: u64,
+
+    /// Pixels the tail preview scrolls sideways per h/l press
+    #[serde(default = "default_horizontal_scroll_step")]
+    pub horizontal_scroll_step: f32,
+
+    /// Lines the preview moves per j/k press
+    #[serde(default = "default_scroll_lines")]
+    pub scroll_lines: usize,
+
+    /// Lines the preview moves per Ctrl+D/Ctrl+U
+    #[serde(default = "default_page_lines")]
+    pub page_lines: usize,
+
+    /// Ease keyboard scrolling in the preview over a few frames instead of jumping
+    #[serde(default = "default_true")]
+    pub smooth_scroll: bool,
+
+    /// Template for `yl` (yank location): `{path}` and `{line}` are filled in
+    #[serde(default = "default_location_format")]
+    pub location_format: String,
+
+    /// Compact tail output: leave out the relative time column
+    #[serde(default)]
+    pub output_hide_time: bool,
+
+    /// How the tail output's relative time column is written
+    #[serde(default)]
+    pub relative_time_format: RelativeTimeFormat,
+
+    /// Whether grep match counts are matching lines or every occurrence on them
+    #[serde(default)]
+    pub match_count_unit: CountUnit,
+
+    /// Compact tail output: leave out the [source] column (redundant with one file)
+    #[serde(default)]
+    pub output_hide_source: bool,
+
+    /// Words always highlighted in the tail output (case-insensitive)
+    #[serde(default)]
+    pub highlight_keywords: Vec<String>,
+
+    /// Also highlight this machine's hostname
+    #[serde(default = "default_true")]
+    pub highlight_hostname: bool,
+
+    /// Mark the token a line's level was detected from (`[ERROR]`, `WRN`, ...) in the
+    /// tail output and preview
+    #[serde(default = "default_true")]
+    pub highlight_level_token: bool,
+
+    /// Lines longer than this many characters are cut short in the tail output and
+    /// preview until clicked; 0 shows every line in full
+    #[serde(default = "default_max_display_line_len")]
+    pub max_display_line_len: usize,
+
+    /// Save the mode, tailed files and grep inputs on exit and reopen them on the next
+    /// launch without arguments
+    #[serde(default)]
+    pub restore_session: bool,
+
+    /// When the tail output buffer is full, up to this many ERROR/FATAL lines are kept
+    /// while older ordinary lines are dropped; 0 drops strictly oldest first
+    #[serde(default)]
+    pub keep_error_lines: usize,
+
+    /// Grep mode searches this long after typing in the query stops; 0 turns auto-search
+    /// off so only Enter or the Search button start one
+    #[serde(default = "default_search_debounce_ms")]
+    pub search_debounce_ms: u64,
+
+    /// Grep mode starts in this directory instead of the current one
+    #[serde(default)]
+    pub default_search_path: Option<String>,
+
+    /// Grep mode's file pattern at startup instead of `*.log`
+    #[serde(default)]
+    pub default_file_pattern: Option<String>,
+
+    /// Mode to open in (`grep` or `tail`) when no command-line arguments pick one and
+    /// no session is restored
+    #[serde(default)]
+    pub default_mode: SessionMode,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_display_line_len() -> usize {
+    2000
+}
+
+fn default_search_debounce_ms() -> u64 {
+    500
 }
 
 fn default_font_size() -> f32 {
@@ -82,11 +190,158 @@ fn default_poll_interval() -> u64 {
     250
 }
 
+fn default_idle_timeout() -> u64 {
+    2
+}
+
+fn default_horizontal_scroll_step() -> f32 {
+    40.0
+}
+
+fn default_scroll_lines() -> usize {
+    1
+}
+
+fn default_page_lines() -> usize {
+    20
+}
+
+fn default_location_format() -> String {
+    "{path}:{line}".to_string()
+}
+
 impl Default for UiPreferences {
     fn default() -> Self {
         Self {
             font_size: default_font_size(),
             poll_interval_ms: default_poll_interval(),
+            idle_timeout_secs: default_idle_timeout(),
+            horizontal_scroll_step: default_horizontal_scroll_step(),
+            scroll_lines: default_scroll_lines(),
+            page_lines: default_page_lines(),
+            smooth_scroll: true,
+            location_format: default_location_format(),
+            output_hide_time: false,
+            relative_time_format: RelativeTimeFormat::default(),
+            match_count_unit: CountUnit::default(),
+            output_hide_source: false,
+            highlight_keywords: Vec::new(),
+            highlight_hostname: true,
+            highlight_level_token: true,
+            max_display_line_len: default_max_display_line_len(),
+            restore_session: false,
+            keep_error_lines: 0,
+            search_debounce_ms: default_search_debounce_ms(),
+            default_search_path: None,
+            default_file_pattern: None,
+            default_mode: SessionMode::Grep,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundAlertConfig {
+    /// Start with sound alerts switched on (they can be toggled in tail mode)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Sound to play (wav); a short beep when unset
+    #[serde(default)]
+    pub sound_file: Option<String>,
+
+    /// Alert on lines at or above this level (TRACE..FATAL)
+    #[serde(default = "default_alert_level")]
+    pub min_level: Option<String>,
+
+    /// Regexes that raise an alert whatever the line's level
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    /// Play at most one alert per this many seconds
+    #[serde(default = "default_alert_interval")]
+    pub min_interval_secs: u64,
+}
+
+fn default_alert_level() -> Option<String> {
+    Some("ERROR".to_string())
+}
+
+fn default_alert_interval() -> u64 {
+    10
+}
+
+impl Default for SoundAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sound_file: None,
+            min_level: default_alert_level(),
+            patterns: Vec::new(),
+            min_interval_secs: default_alert_interval(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// Start with redaction switched on (it can be toggled from the header)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Regexes whose matches are displayed as `****`
+    #[serde(default = "default_redaction_patterns")]
+    pub patterns: Vec<String>,
+}
+
+fn default_redaction_patterns() -> Vec<String> {
+    vec![
+        // Email addresses
+        r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}".to_string(),
+        // Long digit runs: account and card numbers
+        r"\b\d{8,}\b".to_string(),
+    ]
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            patterns: default_redaction_patterns(),
+        }
+    }
+}
+
+/// A named regex whose matches are drawn in its own colors in the tail output and preview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighlightRule {
+    pub name: String,
+    pub pattern: String,
+    /// "#RRGGBB" text color; the line's level color when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<String>,
+    /// "#RRGGBB" background; none when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighlightRulesConfig {
+    /// Switch every rule on or off at once (toggled from the tail toolbar)
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Applied in order; where rules overlap, the earlier one wins
+    #[serde(default)]
+    pub rules: Vec<HighlightRule>,
+}
+
+impl Default for HighlightRulesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: Vec::new(),
         }
     }
 }
@@ -97,6 +352,8 @@ pub struct Config {
     #[serde(default)]
     pub saved_patterns: Vec<SavedPattern>,
     #[serde(default)]
+    pub saved_filters: Vec<SavedFilter>,
+    #[serde(default)]
     pub theme: Theme,
     #[serde(default)]
     pub editor: Option<EditorConfig>,
@@ -104,6 +361,12 @@ pub struct Config {
     pub log_format: LogFormatConfig,
     #[serde(default)]
     pub ui: UiPreferences,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub sound_alert: SoundAlertConfig,
+    #[serde(default)]
+    pub highlight_rules: HighlightRulesConfig,
 }
 
 impl Default for Config {
@@ -120,10 +383,14 @@ impl Default for Config {
                 },
             ],
             saved_patterns: vec![],
+            saved_filters: vec![],
             theme: Theme::default(),
             editor: None,
             log_format: LogFormatConfig::default(),
             ui: UiPreferences::default(),
+            redaction: RedactionConfig::default(),
+            sound_alert: SoundAlertConfig::default(),
+            highlight_rules: HighlightRulesConfig::default(),
         }
     }
 }
@@ -245,6 +512,12 @@ impl Config {
                     category: "Errors".to_string(),
                 },
             ],
+            saved_filters: vec![
+                SavedFilter {
+                    name: "Rejects".to_string(),
+                    query: "R:35=(3|9)\\|".to_string(),
+                },
+            ],
             theme: Theme::default(),
             editor: Some(EditorConfig {
                 command: if cfg!(windows) {
@@ -256,6 +529,18 @@ impl Config {
             }),
             log_format: LogFormatConfig::default(),
             ui: UiPreferences::default(),
+            redaction: RedactionConfig::default(),
+            sound_alert: SoundAlertConfig::default(),
+            highlight_rules: HighlightRulesConfig {
+                enabled: true,
+                rules: vec![HighlightRule {
+                    name: "Order IDs".to_string(),
+                    pattern: r"ClOrdID=\w+".to_string(),
+                    foreground: Some("#78DCFF".to_string()),
+                    background: Some("#1E3C50".to_string()),
+                    enabled: true,
+                }],
+            },
         };
 
         example.save()
@@ -0,0 +1,138 @@
+use similar::{DiffTag, TextDiff};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Give up refining the diff after this long; large files still get a (coarser) result
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    Equal,
+    Removed,
+    Added,
+    Changed,
+}
+
+/// One side-by-side row: (1-based line number, text) on each side, None where a side has no line
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRow {
+    pub kind: DiffKind,
+    pub left: Option<(usize, String)>,
+    pub right: Option<(usize, String)>,
+}
+
+/// Line diff of two files, ready to render side by side
+pub struct FileDiff {
+    pub left_path: PathBuf,
+    pub right_path: PathBuf,
+    pub rows: Vec<DiffRow>,
+}
+
+impl FileDiff {
+    pub fn load(left_path: &Path, right_path: &Path) -> Result<Self, String> {
+        let read = |path: &Path| {
+            fs::read(path)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .map_err(|e| format!("Failed to read {:?}: {}", path, e))
+        };
+        let left = read(left_path)?;
+        let right = read(right_path)?;
+
+        Ok(Self {
+            left_path: left_path.to_path_buf(),
+            right_path: right_path.to_path_buf(),
+            rows: diff_rows(&left, &right),
+        })
+    }
+
+    /// (removed, added, changed) row counts
+    pub fn stats(&self) -> (usize, usize, usize) {
+        self.rows.iter().fold((0, 0, 0), |(removed, added, changed), row| match row.kind {
+            DiffKind::Removed => (removed + 1, added, changed),
+            DiffKind::Added => (removed, added + 1, changed),
+            DiffKind::Changed => (removed, added, changed + 1),
+            DiffKind::Equal => (removed, added, changed),
+        })
+    }
+}
+
+/// Line diff of two texts. Replaced blocks are paired up line by line as Changed rows,
+/// with any leftover lines on the longer side shown as Removed/Added.
+pub fn diff_rows(left: &str, right: &str) -> Vec<DiffRow> {
+    let diff = TextDiff::configure().timeout(DIFF_TIMEOUT).diff_lines(left, right);
+    let old_lines: Vec<&str> = diff.old_slices().iter().map(|l| l.trim_end_matches(['\n', '\r'])).collect();
+    let new_lines: Vec<&str> = diff.new_slices().iter().map(|l| l.trim_end_matches(['\n', '\r'])).collect();
+
+    let left_row = |idx: usize| Some((idx + 1, old_lines[idx].to_string()));
+    let right_row = |idx: usize| Some((idx + 1, new_lines[idx].to_string()));
+
+    let mut rows = Vec::new();
+    for op in diff.ops() {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => {
+                for (old_idx, new_idx) in old_range.zip(new_range) {
+                    rows.push(DiffRow { kind: DiffKind::Equal, left: left_row(old_idx), right: right_row(new_idx) });
+                }
+            }
+            DiffTag::Delete => {
+                for old_idx in old_range {
+                    rows.push(DiffRow { kind: DiffKind::Removed, left: left_row(old_idx), right: None });
+                }
+            }
+            DiffTag::Insert => {
+                for new_idx in new_range {
+                    rows.push(DiffRow { kind: DiffKind::Added, left: None, right: right_row(new_idx) });
+                }
+            }
+            DiffTag::Replace => {
+                let paired = old_range.len().min(new_range.len());
+                for offset in 0..old_range.len().max(new_range.len()) {
+                    let old_idx = old_range.start + offset;
+                    let new_idx = new_range.start + offset;
+                    let row = if offset < paired {
+                        DiffRow { kind: DiffKind::Changed, left: left_row(old_idx), right: right_row(new_idx) }
+                    } else if old_idx < old_range.end {
+                        DiffRow { kind: DiffKind::Removed, left: left_row(old_idx), right: None }
+                    } else {
+                        DiffRow { kind: DiffKind::Added, left: None, right: right_row(new_idx) }
+                    };
+                    rows.push(row);
+                }
+            }
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_rows_kinds() {
+        let left = "a\nb\nc\nd\n";
+        let right = "a\nB\nc\nd\ne\n";
+        let rows = diff_rows(left, right);
+
+        let kinds: Vec<DiffKind> = rows.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![DiffKind::Equal, DiffKind::Changed, DiffKind::Equal, DiffKind::Equal, DiffKind::Added]
+        );
+        assert_eq!(rows[1].left, Some((2, "b".to_string())));
+        assert_eq!(rows[1].right, Some((2, "B".to_string())));
+        assert_eq!(rows[4].right, Some((5, "e".to_string())));
+    }
+
+    #[test]
+    fn test_uneven_replace_splits_leftovers() {
+        let rows = diff_rows("x\n1\n2\ny\n", "x\nz\ny\n");
+        let kinds: Vec<DiffKind> = rows.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![DiffKind::Equal, DiffKind::Changed, DiffKind::Removed, DiffKind::Equal]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::redact::Redactor;
+use crate::search::SearchResult;
+use log::info;
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+/// Write grep results to `path`, as JSON if the extension is `.json` and CSV otherwise.
+/// Each match becomes one row/object: file, line, column, text (redacted if enabled),
+/// plus the regex capture groups when the query has any.
+pub fn export_results(results: &[SearchResult], redactor: &Redactor, path: &Path) -> Result<(), String> {
+    let is_json = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
+    let content = if is_json {
+        results_to_json(results, redactor)?
+    } else {
+        results_to_csv(results, redactor)
+    };
+
+    fs::write(path, content).map_err(|e| format!("Failed to write export file: {}", e))?;
+
+    let match_count: usize = results.iter().map(|r| r.matches.len()).sum();
+    info!("Exported {} matches to {:?}", match_count, path);
+    Ok(())
+}
+
+fn results_to_csv(results: &[SearchResult], redactor: &Redactor) -> String {
+    // Capture groups from the query become extra group1..groupN columns
+    let group_count = results
+        .iter()
+        .flat_map(|r| r.matches.iter().map(|m| m.captures.len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut csv = String::from("file,line,column,text");
+    for group in 1..=group_count {
+        csv.push_str(&format!(",group{}", group));
+    }
+    csv.push('\n');
+
+    for result in results {
+        let file = result.file_path.to_string_lossy();
+        for m in &result.matches {
+            csv.push_str(&format!(
+                "{},{},{},{}",
+                csv_field(&file),
+                m.line_number,
+                m.column_start + 1,
+                csv_field(&redactor.redact(&m.line_text))
+            ));
+            for group in 0..group_count {
+                let capture = m.captures.get(group).map(String::as_str).unwrap_or("");
+                csv.push(',');
+                csv.push_str(&csv_field(&redactor.redact(capture)));
+            }
+            csv.push('\n');
+        }
+    }
+    csv
+}
+
+fn results_to_json(results: &[SearchResult], redactor: &Redactor) -> Result<String, String> {
+    let rows: Vec<serde_json::Value> = results
+        .iter()
+        .flat_map(|result| {
+            let file = result.file_path.to_string_lossy().to_string();
+            result.matches.iter().map(move |m| {
+                let mut row = serde_json::json!({
+                    "file": file,
+                    "line": m.line_number,
+                    "column": m.column_start + 1,
+                    "text": redactor.redact(&m.line_text),
+                });
+                if !m.captures.is_empty() {
+                    let captures: Vec<_> = m.captures.iter().map(|c| redactor.redact(c)).collect();
+                    row["captures"] = serde_json::json!(captures);
+                }
+                row
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&rows).map_err(|e| format!("Failed to serialize results: {}", e))
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RedactionConfig;
+    use crate::search::MatchInfo;
+    use std::path::PathBuf;
+
+    fn sample_results() -> Vec<SearchResult> {
+        vec![SearchResult {
+            file_path: PathBuf::from("/logs/app.log"),
+            matches: vec![MatchInfo {
+                line_number: 12,
+                line_text: "ERROR \"timeout\", retrying".to_string(),
+                column_start: 0,
+                column_end: 5,
+                captures: Vec::new(),
+                occurrences: 1,
+            }],
+            best_score: None,
+        }]
+    }
+
+    fn no_redaction() -> Redactor {
+        Redactor::from_config(&RedactionConfig::default())
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let csv = results_to_csv(&sample_results(), &no_redaction());
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("file,line,column,text"));
+        assert_eq!(
+            lines.next(),
+            Some("/logs/app.log,12,1,\"ERROR \"\"timeout\"\", retrying\"")
+        );
+    }
+
+    #[test]
+    fn test_json_rows() {
+        let json = results_to_json(&sample_results(), &no_redaction()).unwrap();
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["line"], 12);
+        assert_eq!(rows[0]["file"], "/logs/app.log");
+        assert!(rows[0].get("captures").is_none());
+    }
+
+    #[test]
+    fn test_captures_become_columns() {
+        let mut results = sample_results();
+        results[0].matches[0].captures = vec!["timeout".to_string(), String::new()];
+
+        let csv = results_to_csv(&results, &no_redaction());
+        assert!(csv.starts_with("file,line,column,text,group1,group2\n"));
+        assert!(csv.lines().nth(1).unwrap().ends_with(",timeout,"));
+
+        let json = results_to_json(&results, &no_redaction()).unwrap();
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows[0]["captures"][0], "timeout");
+    }
+}
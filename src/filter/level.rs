@@ -21,14 +21,13 @@ impl LogLevelFilter {
         }
     }
 
-    /// Check if a line should be shown based on current filter settings
-    pub fn should_show_line(&self, line: &str, detector: &LogLevelDetector) -> bool {
+    /// Check if a line of the given (already detected) level should be shown based on
+    /// current filter settings
+    pub fn should_show_level(&self, detected_level: LogLevel) -> bool {
         if !self.active {
             return true;  // Filter disabled, show everything
         }
 
-        let detected_level = detector.detect(line);
-
         match detected_level {
             LogLevel::Unknown => self.show_unknown,
             _ => detected_level.severity() >= self.minimum_level.severity()
@@ -150,12 +149,12 @@ mod tests {
     #[test]
     fn test_filter_inactive() {
         let filter = LogLevelFilter::new();
-        let detector = LogLevelDetector::new(vec![]);
+        let detector = LogLevelDetector::new();
 
         // When inactive, all lines should be shown
-        assert!(filter.should_show_line("[ERROR] test", &detector));
-        assert!(filter.should_show_line("[INFO] test", &detector));
-        assert!(filter.should_show_line("random text", &detector));
+        assert!(filter.should_show_level(detector.detect("[ERROR] test")));
+        assert!(filter.should_show_level(detector.detect("[INFO] test")));
+        assert!(filter.should_show_level(detector.detect("random text")));
     }
 
     #[test]
@@ -164,16 +163,16 @@ mod tests {
         filter.active = true;
         filter.minimum_level = LogLevel::Error;
 
-        let detector = LogLevelDetector::new(vec![]);
+        let detector = LogLevelDetector::new();
 
         // Should show errors and fatal
-        assert!(filter.should_show_line("[ERROR] test", &detector));
-        assert!(filter.should_show_line("[FATAL] test", &detector));
+        assert!(filter.should_show_level(detector.detect("[ERROR] test")));
+        assert!(filter.should_show_level(detector.detect("[FATAL] test")));
 
         // Should hide info, warn, debug
-        assert!(!filter.should_show_line("[INFO] test", &detector));
-        assert!(!filter.should_show_line("[WARN] test", &detector));
-        assert!(!filter.should_show_line("[DEBUG] test", &detector));
+        assert!(!filter.should_show_level(detector.detect("[INFO] test")));
+        assert!(!filter.should_show_level(detector.detect("[WARN] test")));
+        assert!(!filter.should_show_level(detector.detect("[DEBUG] test")));
     }
 
     #[test]
@@ -2,9 +2,11 @@ pub mod state;
 pub mod preview;
 pub mod tree;
 pub mod level;
+pub mod output_grep;
 
 #[cfg(test)]
 mod test;
 
 pub use state::{PreviewFilter, TreeFilter};
-pub use level::LogLevelFilter;
\ No newline at end of file
+pub use level::LogLevelFilter;
+pub use output_grep::OutputGrep;
\ No newline at end of file
@@ -0,0 +1,102 @@
+use regex::{Regex, RegexBuilder};
+
+/// Live grep over the combined tail output: only lines matching the regex are shown,
+/// applied to new lines as they arrive and to the whole buffer when the query changes
+#[derive(Debug, Clone)]
+pub struct OutputGrep {
+    pub query: String,
+    pub case_sensitive: bool,
+    regex: Option<Regex>,
+    /// Compile error for the current query, if any
+    pub error: Option<String>,
+}
+
+impl OutputGrep {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            case_sensitive: false,
+            regex: None,
+            error: None,
+        }
+    }
+
+    /// Recompile after `query` or `case_sensitive` changed
+    pub fn recompile(&mut self) {
+        self.regex = None;
+        self.error = None;
+        if self.query.is_empty() {
+            return;
+        }
+
+        match RegexBuilder::new(&self.query)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+        {
+            Ok(regex) => self.regex = Some(regex),
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    /// True when a valid query is filtering the output
+    pub fn is_active(&self) -> bool {
+        self.regex.is_some()
+    }
+
+    /// Lines pass when the grep is inactive or the regex matches
+    pub fn matches_line(&self, line: &str) -> bool {
+        self.regex.as_ref().is_none_or(|regex| regex.is_match(line))
+    }
+
+    /// Byte ranges of the matches in a line, for inline highlighting
+    pub fn find_matches(&self, line: &str) -> Vec<(usize, usize)> {
+        self.regex
+            .as_ref()
+            .map(|regex| {
+                regex
+                    .find_iter(line)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| (m.start(), m.end()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grep(query: &str) -> OutputGrep {
+        let mut grep = OutputGrep::new();
+        grep.query = query.to_string();
+        grep.recompile();
+        grep
+    }
+
+    #[test]
+    fn test_empty_query_passes_everything() {
+        let grep = grep("");
+        assert!(!grep.is_active());
+        assert!(grep.matches_line("anything"));
+    }
+
+    #[test]
+    fn test_case_insensitive_by_default() {
+        let mut grep = grep("order-\\d+");
+        assert!(grep.matches_line("Filled ORDER-42"));
+        assert_eq!(grep.find_matches("order-1 and order-22"), vec![(0, 7), (12, 20)]);
+
+        grep.case_sensitive = true;
+        grep.recompile();
+        assert!(!grep.matches_line("Filled ORDER-42"));
+    }
+
+    #[test]
+    fn test_invalid_regex_reports_error() {
+        let grep = grep("(unclosed");
+        assert!(grep.error.is_some());
+        assert!(!grep.is_active());
+        assert!(grep.matches_line("not filtered while invalid"));
+    }
+}
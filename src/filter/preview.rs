@@ -1,8 +1,10 @@
 use eframe::egui::{self, Color32, TextEdit, RichText, TextStyle};
 use super::state::PreviewFilter;
+use crate::config::SavedFilter;
+use crate::highlight::{self, RuleHighlighter};
 use crate::log_parser::{LogLevelDetector, LogColorScheme};
 
-pub fn render_filter_input(ui: &mut egui::Ui, filter: &mut PreviewFilter) -> bool {
+pub fn render_filter_input(ui: &mut egui::Ui, filter: &mut PreviewFilter, saved_filters: &[SavedFilter]) -> bool {
     let mut filter_changed = false;
 
     if filter.active {
@@ -30,8 +32,11 @@ pub fn render_filter_input(ui: &mut egui::Ui, filter: &mut PreviewFilter) -> boo
                 filter.deactivate();
             }
 
-            // Show match statistics
-            if !filter.match_lines.is_empty() {
+            // Show match statistics, or why an R: query can't match anything
+            if let Some(error) = &filter.regex_error {
+                ui.label(RichText::new(format!("⚠ {}", crate::search::error_summary(error))).color(Color32::from_rgb(255, 100, 100)))
+                    .on_hover_text(RichText::new(error).monospace());
+            } else if !filter.match_lines.is_empty() {
                 let (current, total) = filter.match_stats();
                 ui.label(format!("{} of {} matches", current, total));
             } else if !filter.query.is_empty() {
@@ -44,12 +49,58 @@ pub fn render_filter_input(ui: &mut egui::Ui, filter: &mut PreviewFilter) -> boo
             } else if filter.case_sensitive {
                 ui.label(RichText::new("case").color(Color32::from_rgb(100, 150, 255)));
             }
+
+            // Saved filters: pick one, or save the current query under a name
+            if !saved_filters.is_empty() {
+                egui::ComboBox::from_id_salt("saved_filters")
+                    .selected_text("📝")
+                    .width(40.0)
+                    .show_ui(ui, |ui| {
+                        for saved in saved_filters {
+                            if ui.selectable_label(false, &saved.name).on_hover_text(&saved.query).clicked() {
+                                filter.update_query(saved.query.clone());
+                                filter_changed = true;
+                            }
+                        }
+                    });
+            }
+
+            if !filter.query.is_empty() {
+                ui.menu_button("💾", |ui| {
+                    ui.label(format!("Save \"{}\" as:", filter.query_with_prefix()));
+                    let response = ui.add(TextEdit::singleline(&mut filter.save_name).hint_text("name"));
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let name = filter.save_name.trim().to_string();
+                    if (ui.add_enabled(!name.is_empty(), egui::Button::new("Save")).clicked() || submitted)
+                        && !name.is_empty()
+                    {
+                        filter.pending_save = Some(SavedFilter {
+                            name,
+                            query: filter.query_with_prefix(),
+                        });
+                        filter.save_name.clear();
+                        ui.close_menu();
+                    }
+                })
+                .response
+                .on_hover_text("Save this filter");
+            }
         });
     }
 
     filter_changed
 }
 
+/// How preview lines are colored by level and highlight rules
+pub struct LineStyle<'a> {
+    pub log_detector: &'a LogLevelDetector,
+    pub color_scheme: &'a LogColorScheme,
+    /// Mark the token the level was detected from
+    pub highlight_level_token: bool,
+    pub highlight_rules: &'a RuleHighlighter,
+}
+
+/// Render one preview line. Returns the line-number gutter's response, which senses clicks.
 pub fn render_filtered_line(
     ui: &mut egui::Ui,
     line: &str,
@@ -57,8 +108,7 @@ pub fn render_filtered_line(
     is_match: bool,
     is_current_match: bool,
     filter: &PreviewFilter,
-    log_detector: &LogLevelDetector,
-    color_scheme: &LogColorScheme,
+    style: &LineStyle,
 ) -> egui::Response {
     let bg_color = if is_current_match {
         Color32::from_rgb(80, 80, 0)  // Yellow highlight for current match
@@ -90,18 +140,42 @@ pub fn render_filtered_line(
         ui.painter().galley(line_num_pos, galley.clone(), Color32::from_gray(128));
 
         // Allocate space for the line number
-        ui.allocate_space(galley.size());
+        let gutter = ui.allocate_response(galley.size(), egui::Sense::click());
 
         // Line content with match highlighting and log level coloring (selectable)
-        let log_level = log_detector.detect(line);
-        let base_color = color_scheme.get_color(log_level);
+        let (log_level, token) = style.log_detector.detect_with_range(line);
+        let base_color = style.color_scheme.get_color(log_level);
 
+        let rule_spans = style.highlight_rules.find_spans(line);
         if is_match && filter.active {
             render_highlighted_text(ui, line, filter, base_color);
+        } else if !rule_spans.is_empty() {
+            let font_id = TextStyle::Monospace.resolve(ui.style());
+            ui.label(highlight::rule_spans_job(font_id, line, &rule_spans, base_color));
+        } else if let Some((start, end)) = token.filter(|_| style.highlight_level_token) {
+            ui.label(level_token_job(ui, line, start, end, base_color));
         } else {
             ui.label(RichText::new(line).monospace().color(base_color));
         }
-    }).response
+        gutter
+    }).inner
+}
+
+/// A line in its level color with the level token drawn as a badge in that color
+pub fn level_token_job(ui: &egui::Ui, line: &str, start: usize, end: usize, color: Color32) -> egui::text::LayoutJob {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let plain = egui::TextFormat::simple(font_id.clone(), color);
+    let badge = egui::TextFormat {
+        color: Color32::BLACK,
+        background: color,
+        ..egui::TextFormat::simple(font_id, color)
+    };
+
+    let mut job = egui::text::LayoutJob::default();
+    job.append(&line[..start], 0.0, plain.clone());
+    job.append(&line[start..end], 0.0, badge);
+    job.append(&line[end..], 0.0, plain);
+    job
 }
 
 fn render_highlighted_text(ui: &mut egui::Ui, text: &str, filter: &PreviewFilter, base_color: Color32) {
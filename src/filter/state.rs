@@ -1,3 +1,4 @@
+use crate::config::SavedFilter;
 use regex::Regex;
 
 #[derive(Debug, Clone)]
@@ -9,7 +10,13 @@ pub struct PreviewFilter {
     pub match_lines: Vec<usize>,
     pub current_match: Option<usize>,
     compiled_regex: Option<Regex>,
+    /// Why an `R:` query doesn't compile, shown next to the filter box
+    pub regex_error: Option<String>,
     pub request_focus: bool,
+    /// Name typed into the "save filter" field
+    pub save_name: String,
+    /// Filter the user asked to save, picked up by the app which owns the config
+    pub pending_save: Option<SavedFilter>,
 }
 
 impl PreviewFilter {
@@ -22,7 +29,10 @@ impl PreviewFilter {
             match_lines: Vec::new(),
             current_match: None,
             compiled_regex: None,
+            regex_error: None,
             request_focus: false,
+            save_name: String::new(),
+            pending_save: None,
         }
     }
 
@@ -37,6 +47,7 @@ impl PreviewFilter {
         self.match_lines.clear();
         self.current_match = None;
         self.compiled_regex = None;
+        self.regex_error = None;
         self.request_focus = false;
     }
 
@@ -47,7 +58,19 @@ impl PreviewFilter {
         self.current_match = None;
     }
 
+    /// The query as typed, with the `C:`/`R:` prefix that parse_query strips off
+    pub fn query_with_prefix(&self) -> String {
+        if self.use_regex {
+            format!("R:{}", self.query)
+        } else if self.case_sensitive {
+            format!("C:{}", self.query)
+        } else {
+            self.query.clone()
+        }
+    }
+
     fn parse_query(&mut self) {
+        self.regex_error = None;
         if self.query.starts_with("C:") {
             self.case_sensitive = true;
             self.use_regex = false;
@@ -67,7 +90,10 @@ impl PreviewFilter {
         if self.use_regex {
             match Regex::new(&self.query) {
                 Ok(regex) => self.compiled_regex = Some(regex),
-                Err(_) => self.compiled_regex = None,
+                Err(e) => {
+                    self.compiled_regex = None;
+                    self.regex_error = Some(e.to_string());
+                }
             }
         }
     }
@@ -181,6 +207,8 @@ impl PreviewFilter {
 pub struct TreeFilter {
     pub active: bool,
     pub pattern: String,
+    /// Match the pattern's case exactly, e.g. to tell `Prod` from `prod`
+    pub case_sensitive: bool,
     pub show_matching_only: bool,
     pub exclude_patterns: Vec<String>,
     pub apply_to_output: bool,
@@ -191,6 +219,7 @@ impl TreeFilter {
         Self {
             active: false,
             pattern: String::new(),
+            case_sensitive: false,
             show_matching_only: true,
             exclude_patterns: Vec::new(),
             apply_to_output: true,
@@ -202,14 +231,17 @@ impl TreeFilter {
             return true;
         }
 
-        let lower_path = path.to_lowercase();
-        let lower_pattern = self.pattern.to_lowercase();
+        let (path, pattern) = if self.case_sensitive {
+            (path.to_string(), self.pattern.clone())
+        } else {
+            (path.to_lowercase(), self.pattern.to_lowercase())
+        };
 
         // Fuzzy match: all characters in pattern must appear in order
-        let mut pattern_chars = lower_pattern.chars();
+        let mut pattern_chars = pattern.chars();
         let mut current_char = pattern_chars.next();
 
-        for path_char in lower_path.chars() {
+        for path_char in path.chars() {
             if let Some(pc) = current_char {
                 if path_char == pc {
                     current_char = pattern_chars.next();
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use super::super::state::TreeFilter;
+    use super::super::state::{PreviewFilter, TreeFilter};
     
     #[test]
     fn test_tree_filter_matches() {
@@ -30,6 +30,18 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_tree_filter_case_sensitive() {
+        let mut filter = TreeFilter::new();
+        filter.pattern = "Prod".to_string();
+        filter.active = true;
+        assert!(filter.matches("/logs/prod/app.log"));
+
+        filter.case_sensitive = true;
+        assert!(!filter.matches("/logs/prod/app.log"));
+        assert!(super::super::tree::is_file_visible(&filter, "/logs/Prod/app.log", "app"));
+    }
+
     #[test] 
     fn test_tree_filter_real_scenarios() {
         let mut filter = TreeFilter::new();
@@ -58,4 +70,24 @@ mod tests {
                       "Wrong visibility for path='{}', name='{}'", path, name);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_query_with_prefix_round_trips() {
+        let mut filter = PreviewFilter::new();
+        for query in ["R:35=(3|9)", "C:Rejected", "order"] {
+            filter.update_query(query.to_string());
+            assert_eq!(filter.query_with_prefix(), query);
+        }
+    }
+
+    #[test]
+    fn test_invalid_regex_filter_reports_error() {
+        let mut filter = PreviewFilter::new();
+        filter.update_query("R:35=(3".to_string());
+        assert!(filter.regex_error.as_deref().is_some_and(|e| e.contains("unclosed group")));
+        assert!(!filter.matches_line("35=(3"));
+
+        filter.update_query("R:35=(3)".to_string());
+        assert!(filter.regex_error.is_none());
+    }
+}
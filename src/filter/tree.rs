@@ -19,6 +19,14 @@ pub fn render_tree_filter(ui: &mut egui::Ui, filter: &mut TreeFilter) -> bool {
             changed = true;
         }
         
+        if ui.selectable_label(filter.case_sensitive, RichText::new("Aa").monospace())
+            .on_hover_text("Case sensitive")
+            .clicked()
+        {
+            filter.case_sensitive = !filter.case_sensitive;
+            changed = true;
+        }
+
         if ui.small_button("×").on_hover_text("Clear filter").clicked() {
             filter.pattern.clear();
             filter.active = false;
@@ -0,0 +1,106 @@
+/// A fuzzy (subsequence) match of a query within a line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuzzyMatch {
+    /// Higher is better: rewards consecutive runs and matches at word starts, and
+    /// penalises the gaps between matched characters
+    pub score: i64,
+    /// Byte range from the first to the last matched character
+    pub start: usize,
+    pub end: usize,
+}
+
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 12;
+const WORD_START_BONUS: i64 = 8;
+
+/// Match `query` as a subsequence of `line`. Whitespace in the query is ignored, so
+/// "conn refused" finds "Connection was refused". Returns None if not every query
+/// character appears in order.
+pub fn fuzzy_match(line: &str, query: &str, case_sensitive: bool) -> Option<FuzzyMatch> {
+    let fold = |c: char| if case_sensitive { c } else { c.to_lowercase().next().unwrap_or(c) };
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).map(fold).collect();
+    if query.is_empty() {
+        return None;
+    }
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+
+    // Greedy forward pass finds where the earliest complete match ends...
+    let mut q = 0;
+    let mut end_idx = None;
+    for (idx, &(_, c)) in chars.iter().enumerate() {
+        if fold(c) == query[q] {
+            q += 1;
+            if q == query.len() {
+                end_idx = Some(idx);
+                break;
+            }
+        }
+    }
+    let end_idx = end_idx?;
+
+    // ...then a backward pass from there finds the latest start, giving the tightest span
+    let mut q = query.len();
+    let mut start_idx = end_idx;
+    for idx in (0..=end_idx).rev() {
+        if fold(chars[idx].1) == query[q - 1] {
+            q -= 1;
+            if q == 0 {
+                start_idx = idx;
+                break;
+            }
+        }
+    }
+
+    // Score the span with a final forward pass
+    let mut score = 0;
+    let mut q = 0;
+    let mut last_matched: Option<usize> = None;
+    for idx in start_idx..=end_idx {
+        if q < query.len() && fold(chars[idx].1) == query[q] {
+            score += MATCH_SCORE;
+            if idx == 0 || !chars[idx - 1].1.is_alphanumeric() {
+                score += WORD_START_BONUS;
+            }
+            match last_matched {
+                Some(prev) if prev + 1 == idx => score += CONSECUTIVE_BONUS,
+                Some(prev) => score -= (idx - prev - 1) as i64,
+                None => {}
+            }
+            last_matched = Some(idx);
+            q += 1;
+        }
+    }
+
+    let (end_byte, end_char) = chars[end_idx];
+    Some(FuzzyMatch {
+        score,
+        start: chars[start_idx].0,
+        end: end_byte + end_char.len_utf8(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsequence_and_span() {
+        let m = fuzzy_match("ERROR: Connection was refused", "conn refused", false).unwrap();
+        assert_eq!(&"ERROR: Connection was refused"[m.start..m.end], "Connection was refused");
+
+        assert!(fuzzy_match("connection", "cnx", false).is_none());
+        assert!(fuzzy_match("Connection", "conn", true).is_none());
+        assert!(fuzzy_match("anything", "  ", false).is_none());
+    }
+
+    #[test]
+    fn test_tighter_matches_score_higher() {
+        let exact = fuzzy_match("order rejected", "rejected", false).unwrap();
+        let scattered = fuzzy_match("r-e-j-e-c-t-e-d", "rejected", false).unwrap();
+        assert!(exact.score > scattered.score);
+
+        // The backward pass moves the start up to the closest 'a'
+        let m = fuzzy_match("a a bc", "abc", false).unwrap();
+        assert_eq!(m.start, 2);
+    }
+}
@@ -1,5 +1,16 @@
-use crate::VisGrepApp;
+use crate::{VisGrepApp, diff, replace, search, widgets};
 use eframe::egui;
+use log::info;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Quiet period after the last file change before a watch re-run starts
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(1000);
+
+/// Matches shown in the replace preview; Write changes still covers every result
+const REPLACE_PREVIEW_LIMIT: usize = 200;
 
 impl VisGrepApp {
     pub fn render_grep_mode_ui(&mut self, ui: &mut egui::Ui) {
@@ -11,6 +22,7 @@ impl VisGrepApp {
         ui.separator();
 
         self.render_search_query_field(ui);
+        self.render_replace_field(ui);
         ui.separator();
 
         // File age filter
@@ -40,7 +52,7 @@ impl VisGrepApp {
             
             // Slider for fine control
             ui.add(
-                egui::Slider::new(&mut self.grep_state.font_size, 8.0..=24.0)
+                egui::Slider::new(&mut self.grep_state.font_size, crate::FONT_SIZE_RANGE)
                     .suffix(" px")
                     .show_value(true)
             );
@@ -64,12 +76,18 @@ impl VisGrepApp {
                 for i in 0..self.grep_state.results.len() {
                     self.grep_state.collapsing_state.insert(i, true);
                 }
+                self.grep_state.dir_headers_open = Some(true);
             }
             if ui.button("Collapse All").clicked() {
                 for i in 0..self.grep_state.results.len() {
                     self.grep_state.collapsing_state.insert(i, false);
                 }
+                self.grep_state.dir_headers_open = Some(false);
             }
+            ui.checkbox(&mut self.grep_state.group_by_directory, "By directory")
+                .on_hover_text("Group result files under their parent directory");
+            ui.checkbox(&mut self.grep_state.unique_lines, "Unique lines")
+                .on_hover_text("One row per distinct matched line, with how often and where it occurred");
             
             ui.separator();
             
@@ -99,11 +117,18 @@ impl VisGrepApp {
             .auto_shrink([false, false])
             .show(ui, |ui| {
                 if self.grep_state.searching {
-                    ui.label("Searching...");
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!(
+                            "Searching... {} files so far",
+                            self.grep_state.results.len()
+                        ));
+                    });
+                    self.render_results(ui);
                 } else if self.grep_state.results.is_empty()
                     && !self.grep_state.search_query.is_empty()
                 {
-                    ui.label("No results found");
+                    ui.label(self.no_results_message());
                 } else {
                     self.render_results(ui);
                 }
@@ -121,8 +146,87 @@ impl VisGrepApp {
             });
     }
     
+    /// Why the last search found nothing: no files passed the name/age filters, or the
+    /// files that did had no matching lines (or names)
+    fn no_results_message(&self) -> String {
+        let (Some(files), Some(search)) = (self.grep_state.files_scanned, &self.grep_state.last_search) else {
+            return "No results found".to_string();
+        };
+        if files > 0 && search.file_names_only {
+            return format!(
+                "None of the {} file{} matching '{}' has a matching name",
+                files,
+                if files == 1 { "" } else { "s" },
+                search.file_pattern
+            );
+        }
+        if files > 0 {
+            return format!(
+                "Searched {} file{} matching '{}', no lines matched",
+                files,
+                if files == 1 { "" } else { "s" },
+                search.file_pattern
+            );
+        }
+        let age = search
+            .file_age_hours
+            .map(|hours| format!(" modified in the last {}h", hours))
+            .unwrap_or_default();
+        format!("No files matching '{}'{} in {}", search.file_pattern, age, search.search_path)
+    }
+
     pub fn render_grep_right_panel(&mut self, ui: &mut egui::Ui) {
-        ui.label("Preview:");
+        widgets::json_view::show_json_window(ui.ctx(), "JSON (matched line)", &mut self.grep_state.json_view, &self.redactor);
+
+        if self.grep_state.diff.is_some() {
+            self.render_diff(ui);
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Preview:");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let previewed = self.previewed_file();
+                if ui.add_enabled(previewed.is_some(), egui::Button::new("⇄ Compare"))
+                    .on_hover_text("Diff the previewed file against another file")
+                    .clicked()
+                {
+                    if let Some(path) = previewed {
+                        self.compare_with_file_dialog(&path);
+                    }
+                }
+
+                if ui.add_enabled(!self.preview.lines.is_empty(), egui::Button::new("📋 Copy all"))
+                    .on_hover_text("Copy the previewed lines as plain text, without line numbers")
+                    .clicked()
+                {
+                    if let Some(text) = self.preview.plain_text() {
+                        Self::copy_to_clipboard(&self.redactor.redact(&text), "preview");
+                    }
+                }
+
+                ui.toggle_value(&mut self.grep_state.show_whitespace, "¶")
+                    .on_hover_text("Show tabs and trailing spaces");
+                ui.toggle_value(&mut self.grep_state.show_line_numbers, "#")
+                    .on_hover_text("Show line numbers");
+
+                let hex_hint = if self.preview.is_binary {
+                    "File looks binary; showing hex by default"
+                } else {
+                    "Show the bytes around the match as a hex dump"
+                };
+                let hex_toggle = ui
+                    .add_enabled(!self.redactor.enabled, egui::SelectableLabel::new(self.hex_view_shown(), "Hex"))
+                    .on_hover_text(hex_hint)
+                    .on_disabled_hover_text("Hex view is off while redaction is on, since it would show the masked bytes");
+                if hex_toggle.clicked() {
+                    self.preview.toggle_hex();
+                    self.scroll_preview_to_target();
+                }
+            });
+        });
+
+        self.render_preview_goto_line(ui);
         
         let remaining_height = ui.available_height();
 
@@ -146,14 +250,473 @@ impl VisGrepApp {
         });
     }
 
-    pub fn handle_grep_mode_background_tasks(&mut self) {
+    /// The replacement for the last search's results, if one is entered and the search
+    /// wasn't fuzzy
+    fn current_replacement(&self) -> Option<replace::Replacement> {
+        let (matcher, mode) = self.grep_state.search_matcher.as_ref()?;
+        if !self.grep_state.replace_enabled {
+            return None;
+        }
+        replace::Replacement::new(matcher, *mode, &self.grep_state.replace_text)
+    }
+
+    /// Replace row under the query: the replacement, a before/after preview per match and
+    /// Write changes, which only reports what it would do while Dry run is on
+    fn render_replace_field(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.grep_state.replace_enabled, "Replace")
+                .on_hover_text("Preview replacing the matches of the last search, then write it to the files");
+            if !self.grep_state.replace_enabled {
+                return;
+            }
+            ui.add(
+                egui::TextEdit::singleline(&mut self.grep_state.replace_text)
+                    .desired_width(300.0)
+                    .hint_text("replacement ($1, ${name} for groups)"),
+            );
+            ui.checkbox(&mut self.grep_state.replace_dry_run, "Dry run")
+                .on_hover_text("Only report what Write changes would do");
+
+            let fuzzy = matches!(self.grep_state.search_matcher, Some((_, search::SearchMode::Fuzzy)));
+            let can_write = !fuzzy && !self.grep_state.searching && !self.grep_state.results.is_empty();
+            if ui.add_enabled(can_write, egui::Button::new("✏ Write changes…"))
+                .on_disabled_hover_text(if fuzzy { "Fuzzy searches can't be replaced" } else { "Run a search first" })
+                .clicked()
+            {
+                if self.grep_state.replace_dry_run {
+                    let (lines, files) = self.replacement_scope();
+                    self.grep_state.replace_report =
+                        Some(format!("Dry run: would change {} lines in {} files", lines, files));
+                } else {
+                    self.grep_state.replace_confirm = true;
+                }
+            }
+            if let Some(report) = &self.grep_state.replace_report {
+                ui.label(egui::RichText::new(report).color(egui::Color32::GRAY));
+            }
+        });
+
+        let Some(replacement) = self.current_replacement() else {
+            return;
+        };
+        egui::CollapsingHeader::new("Replace preview")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("replace_preview")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        let matches = self.grep_state.results.iter().flat_map(|result| {
+                            result.matches.iter().map(move |m| (&result.file_path, m))
+                        });
+                        for (path, m) in matches.take(REPLACE_PREVIEW_LIMIT) {
+                            let after = replacement.apply(&m.line_text);
+                            let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+                            ui.label(egui::RichText::new(format!("{}:{}", name, m.line_number)).small().color(egui::Color32::GRAY))
+                                .on_hover_text(path.display().to_string());
+                            ui.label(
+                                egui::RichText::new(format!("- {}", self.redactor.redact(&m.line_text)))
+                                    .monospace()
+                                    .color(egui::Color32::from_rgb(255, 120, 120)),
+                            );
+                            ui.label(
+                                egui::RichText::new(format!("+ {}", self.redactor.redact(&after)))
+                                    .monospace()
+                                    .color(egui::Color32::from_rgb(120, 220, 120)),
+                            );
+                        }
+                        let total: usize = self.grep_state.results.iter().map(|r| r.matches.len()).sum();
+                        if total > REPLACE_PREVIEW_LIMIT {
+                            ui.label(format!("… and {} more matches", total - REPLACE_PREVIEW_LIMIT));
+                        }
+                    });
+            });
+
+        if self.grep_state.replace_confirm {
+            self.render_replace_confirm(ui.ctx());
+        }
+    }
+
+    /// (lines, files) the current replacement would change
+    fn replacement_scope(&self) -> (usize, usize) {
+        let Some(replacement) = self.current_replacement() else {
+            return (0, 0);
+        };
+        self.grep_state
+            .results
+            .iter()
+            .map(|result| replacement.changed_lines(&result.matches))
+            .filter(|&lines| lines > 0)
+            .fold((0, 0), |(lines, files), changed| (lines + changed, files + 1))
+    }
+
+    fn render_replace_confirm(&mut self, ctx: &egui::Context) {
+        let (lines, files) = self.replacement_scope();
+        let mut write = false;
+        let mut cancel = false;
+        egui::Window::new("Write changes?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("Replace matches in {} lines across {} files on disk?", lines, files));
+                ui.label(egui::RichText::new("This can't be undone from vis-grep.").color(egui::Color32::GRAY));
+                ui.horizontal(|ui| {
+                    write = ui.add_enabled(lines > 0, egui::Button::new("Write")).clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+        if write {
+            self.write_replacement();
+        }
+        if write || cancel {
+            self.grep_state.replace_confirm = false;
+        }
+    }
+
+    /// Apply the replacement to every result file, then search again so the results show
+    /// the files as they now are
+    fn write_replacement(&mut self) {
+        let Some(replacement) = self.current_replacement() else {
+            return;
+        };
+        let (mut lines, mut files, mut skipped, mut failed) = (0, 0, 0, 0);
+        for result in &self.grep_state.results {
+            match replacement.write_file(&result.file_path, &result.matches) {
+                Ok(edit) => {
+                    if edit.lines_changed > 0 {
+                        info!("Replaced {} lines in {:?}", edit.lines_changed, result.file_path);
+                        lines += edit.lines_changed;
+                        files += 1;
+                    }
+                    skipped += edit.lines_skipped;
+                }
+                Err(e) => {
+                    log::error!("{}", e);
+                    failed += 1;
+                }
+            }
+        }
+
+        let mut report = format!("Changed {} lines in {} files", lines, files);
+        if skipped > 0 {
+            report.push_str(&format!(", skipped {} lines changed since the search", skipped));
+        }
+        if failed > 0 {
+            report.push_str(&format!(", {} files failed (see log)", failed));
+        }
+        info!("{}", report);
+        self.grep_state.replace_report = Some(report);
+        self.perform_search();
+    }
+
+    /// ':' opens a goto-line input above the preview, next to the loaded line range
+    fn render_preview_goto_line(&mut self, ui: &mut egui::Ui) {
+        if !ui.ctx().wants_keyboard_input()
+            && !self.grep_state.goto_line_active
+            && self.preview.line_range().is_some()
+            && ui.input(|i| i.key_pressed(egui::Key::Colon))
+        {
+            self.grep_state.goto_line_active = true;
+            self.grep_state.goto_line_input.clear();
+        }
+
+        let Some((first, last)) = self.preview.line_range() else {
+            return;
+        };
+
+        let mut goto_line = None;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("Lines {}-{}", first, last)).small().color(egui::Color32::GRAY));
+
+            if !self.grep_state.goto_line_active {
+                return;
+            }
+
+            ui.label(":");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.grep_state.goto_line_input)
+                    .desired_width(100.0)
+                    .hint_text("line number"),
+            );
+            response.request_focus();
+
+            let (enter, escape) = ui.input(|i| (i.key_pressed(egui::Key::Enter), i.key_pressed(egui::Key::Escape)));
+            if enter {
+                goto_line = self.grep_state.goto_line_input.trim().parse::<usize>().ok().filter(|n| *n > 0);
+            }
+            if enter || escape {
+                self.grep_state.goto_line_active = false;
+                self.grep_state.goto_line_input.clear();
+            }
+        });
+
+        if let Some(line_number) = goto_line {
+            self.goto_preview_line(line_number);
+        }
+    }
+
+    /// Scroll the preview to an absolute file line, reloading the window around it
+    /// when it's outside the loaded range. The matched line panel keeps showing the match.
+    fn goto_preview_line(&mut self, line_number: usize) {
+        if !self.hex_view_shown() {
+            if let Some(idx) = self.preview.index_of_line(line_number) {
+                info!("Goto line {} (preview index {})", line_number, idx);
+                self.preview.target_line_in_preview = Some(idx);
+                self.scroll_preview_to_target();
+                return;
+            }
+        }
+
+        let Some(path) = self.previewed_file() else {
+            return;
+        };
+        let matched_line_text = self.preview.matched_line_text.take();
+
+        self.preview.load_file(&path, line_number);
+        if self.preview.index_of_line(line_number).is_none() {
+            info!("Line {} is past the end of {:?}", line_number, path);
+            // Go back to the window around the selected match
+            if let Some(match_line) = self.selected_match_line() {
+                self.preview.load_file(&path, match_line);
+            }
+        } else {
+            info!("Goto line {}: reloaded preview around it", line_number);
+        }
+
+        self.preview.matched_line_text = matched_line_text;
+        self.scroll_preview_to_target();
+    }
+
+    pub fn selected_match_line(&self) -> Option<usize> {
+        let id = self.grep_state.selected_result?;
+        let result = self.grep_state.results.get(id / 10000)?;
+        result.matches.get(id % 10000).map(|m| m.line_number)
+    }
+
+    /// File of the selected result, i.e. the one shown in the preview
+    pub fn previewed_file(&self) -> Option<PathBuf> {
+        let file_idx = self.grep_state.selected_result? / 10000;
+        self.grep_state.results.get(file_idx).map(|r| r.file_path.clone())
+    }
+
+    fn compare_with_file_dialog(&mut self, left: &Path) {
+        let mut dialog = rfd::FileDialog::new();
+        if let Some(dir) = left.parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(right) = dialog.pick_file() else {
+            return;
+        };
+
+        match diff::FileDiff::load(left, &right) {
+            Ok(file_diff) => {
+                let (removed, added, changed) = file_diff.stats();
+                info!(
+                    "Compared {:?} with {:?}: {} removed, {} added, {} changed",
+                    left, right, removed, added, changed
+                );
+                self.grep_state.diff = Some(file_diff);
+            }
+            Err(e) => log::error!("{}", e),
+        }
+    }
+
+    /// Side-by-side diff in place of the preview
+    fn render_diff(&mut self, ui: &mut egui::Ui) {
+        let Some(file_diff) = &self.grep_state.diff else {
+            return;
+        };
+
+        let mut close = false;
+        ui.horizontal(|ui| {
+            let (removed, added, changed) = file_diff.stats();
+            ui.label("Diff:");
+            ui.colored_label(egui::Color32::from_rgb(255, 120, 120), format!("-{}", removed));
+            ui.colored_label(egui::Color32::from_rgb(120, 220, 120), format!("+{}", added));
+            ui.colored_label(egui::Color32::from_rgb(230, 200, 100), format!("~{}", changed));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                close = ui.button("✖ Close diff").clicked();
+            });
+        });
+
+        ui.columns(2, |columns| {
+            for (ui, path) in columns.iter_mut().zip([&file_diff.left_path, &file_diff.right_path]) {
+                ui.label(egui::RichText::new(path.display().to_string()).small().color(egui::Color32::GRAY))
+                    .on_hover_text(path.display().to_string());
+            }
+        });
+        ui.separator();
+
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let row_height = ui.fonts(|f| f.row_height(&font_id));
+        let gutter_width = ui.fonts(|f| f.glyph_width(&font_id, '0')) * 6.0;
+
+        egui::ScrollArea::vertical()
+            .id_salt("diff_scroll")
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, file_diff.rows.len(), |ui, row_range| {
+                let half_width = ui.available_width() / 2.0;
+                for row in &file_diff.rows[row_range] {
+                    let (left_bg, right_bg) = match row.kind {
+                        diff::DiffKind::Equal => (egui::Color32::TRANSPARENT, egui::Color32::TRANSPARENT),
+                        diff::DiffKind::Removed => (egui::Color32::from_rgb(70, 30, 30), egui::Color32::from_gray(35)),
+                        diff::DiffKind::Added => (egui::Color32::from_gray(35), egui::Color32::from_rgb(30, 60, 30)),
+                        diff::DiffKind::Changed => (egui::Color32::from_rgb(65, 55, 25), egui::Color32::from_rgb(65, 55, 25)),
+                    };
+
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 0.0;
+                        for (side, bg) in [(&row.left, left_bg), (&row.right, right_bg)] {
+                            let (rect, response) = ui.allocate_exact_size(
+                                egui::vec2(half_width, row_height),
+                                egui::Sense::hover(),
+                            );
+                            let painter = ui.painter_at(rect);
+                            painter.rect_filled(rect, 0.0, bg);
+                            if let Some((line_number, text)) = side {
+                                let text = self.redactor.redact(text);
+                                painter.text(
+                                    rect.left_center(),
+                                    egui::Align2::LEFT_CENTER,
+                                    format!("{:>5}", line_number),
+                                    font_id.clone(),
+                                    egui::Color32::GRAY,
+                                );
+                                painter.text(
+                                    rect.left_center() + egui::vec2(gutter_width, 0.0),
+                                    egui::Align2::LEFT_CENTER,
+                                    text.as_ref(),
+                                    font_id.clone(),
+                                    ui.visuals().text_color(),
+                                );
+                                response.on_hover_text(text.as_ref());
+                            }
+                        }
+                    });
+                }
+            });
+
+        if close {
+            self.grep_state.diff = None;
+        }
+    }
+
+    pub fn handle_grep_mode_background_tasks(&mut self, ctx: &egui::Context) {
         // Debounced search handling
         if self.grep_state.pending_search
             && self.grep_state.last_search_time.elapsed()
-                > std::time::Duration::from_millis(500)
+                > std::time::Duration::from_millis(self.config.ui.search_debounce_ms)
             && !self.grep_state.search_query.is_empty()
         {
             self.perform_search();
         }
+
+        self.poll_watch_events(ctx);
+    }
+
+    /// Start watching the path the last search ran on (nothing until a search has run);
+    /// the watcher thread wakes the UI on each event
+    pub fn start_watch(&mut self, ctx: &egui::Context) {
+        self.stop_watch();
+
+        let Some(params) = &self.grep_state.last_search else {
+            return;
+        };
+        let path = Self::expand_tilde(&params.search_path);
+        let recursive = params.recursive;
+        let (sender, receiver) = mpsc::channel();
+        let repaint_ctx = ctx.clone();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+            repaint_ctx.request_repaint();
+        });
+
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+
+        match watcher.and_then(|mut w| w.watch(Path::new(&path), mode).map(|_| w)) {
+            Ok(watcher) => {
+                info!("Watching {} for changes", path);
+                self.grep_state.watcher = Some(watcher);
+                self.grep_state.watch_receiver = Some(receiver);
+                self.grep_state.watched_path = path;
+            }
+            Err(e) => {
+                log::error!("Failed to watch {}: {}", path, e);
+                self.grep_state.watch_enabled = false;
+            }
+        }
+    }
+
+    pub fn stop_watch(&mut self) {
+        if self.grep_state.watcher.take().is_some() {
+            info!("Stopped watching {}", self.grep_state.watched_path);
+        }
+        self.grep_state.watch_receiver = None;
+        self.grep_state.watch_change_time = None;
+    }
+
+    /// Drain file change events and re-run the search once changes settle
+    fn poll_watch_events(&mut self, ctx: &egui::Context) {
+        if !self.grep_state.watch_enabled {
+            return;
+        }
+
+        // Follow the search path once a search has run on a new one. Only searches move the
+        // watch; a path half-typed into the input is never watched.
+        let Some(params) = self.grep_state.last_search.clone() else {
+            return;
+        };
+        if Self::expand_tilde(&params.search_path) != self.grep_state.watched_path {
+            self.start_watch(ctx);
+        }
+
+        if let Some(receiver) = &self.grep_state.watch_receiver {
+            let file_pattern = self.grep_state.search_engine.file_pattern(&params.file_pattern).ok();
+            for event in receiver.try_iter() {
+                let Ok(event) = event else { continue };
+                let relevant = matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) && file_pattern.as_ref().is_some_and(|file_pattern| {
+                    event
+                        .paths
+                        .iter()
+                        .any(|path| file_pattern.matches(path, Path::new(&self.grep_state.watched_path)))
+                });
+                if relevant {
+                    self.grep_state.watch_change_time = Some(Instant::now());
+                }
+            }
+        }
+
+        let Some(changed_at) = self.grep_state.watch_change_time else {
+            return;
+        };
+
+        let elapsed = changed_at.elapsed();
+        if elapsed < WATCH_DEBOUNCE || self.grep_state.searching {
+            ctx.request_repaint_after(WATCH_DEBOUNCE.saturating_sub(elapsed).max(Duration::from_millis(100)));
+            return;
+        }
+
+        if !params.search_query.is_empty() {
+            info!("Watched files changed, re-running search");
+            let selection = self.grep_state.selected_result.and_then(|id| {
+                let result = self.grep_state.results.get(id / 10000)?;
+                let m = result.matches.get(id % 10000)?;
+                Some((result.file_path.clone(), m.line_number))
+            });
+            // Re-run what was searched, not whatever has been typed into the inputs since
+            self.grep_state.apply_search_params(params);
+            self.perform_search();
+            self.grep_state.restore_selection = selection;
+        }
+        self.grep_state.watch_change_time = None;
     }
 }
\ No newline at end of file
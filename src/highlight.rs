@@ -0,0 +1,258 @@
+use crate::config::HighlightRulesConfig;
+use crate::log_parser::LogColorScheme;
+use eframe::egui::text::LayoutJob;
+use eframe::egui::{Color32, FontId, TextFormat};
+use regex::{Regex, RegexBuilder};
+
+/// (text, background) per highlight pattern, cycled when there are more patterns
+pub const HIGHLIGHT_COLORS: &[(Color32, Color32)] = &[
+    (Color32::from_rgb(255, 200, 100), Color32::from_rgb(80, 60, 40)),  // orange
+    (Color32::from_rgb(120, 220, 255), Color32::from_rgb(30, 60, 80)),  // cyan
+    (Color32::from_rgb(150, 255, 150), Color32::from_rgb(30, 70, 35)),  // green
+    (Color32::from_rgb(255, 150, 220), Color32::from_rgb(80, 35, 65)),  // pink
+    (Color32::from_rgb(200, 170, 255), Color32::from_rgb(55, 40, 85)),  // purple
+];
+
+/// Colors for the pattern at `pattern_idx`
+pub fn pattern_colors(pattern_idx: usize) -> (Color32, Color32) {
+    HIGHLIGHT_COLORS[pattern_idx % HIGHLIGHT_COLORS.len()]
+}
+
+/// Split a comma-separated highlight field (`35=, 55=, ClOrdID=`) into patterns
+pub fn parse_patterns(input: &str) -> Vec<&str> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .collect()
+}
+
+/// Split a line into (text, pattern index) segments, where the index is Some for text
+/// matching one of the patterns. At each position the earliest match wins, and the
+/// longest pattern when several start at the same place.
+pub fn highlight_segments<'a>(line: &'a str, patterns: &[&str]) -> Vec<(&'a str, Option<usize>)> {
+    let mut segments = Vec::new();
+    let mut pos = 0;
+
+    while pos < line.len() {
+        let next = patterns
+            .iter()
+            .enumerate()
+            .filter(|(_, pattern)| !pattern.is_empty())
+            .filter_map(|(idx, pattern)| line[pos..].find(pattern).map(|offset| (pos + offset, pattern.len(), idx)))
+            .min_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+        let Some((start, len, idx)) = next else {
+            break;
+        };
+        if start > pos {
+            segments.push((&line[pos..start], None));
+        }
+        segments.push((&line[start..start + len], Some(idx)));
+        pos = start + len;
+    }
+
+    if pos < line.len() {
+        segments.push((&line[pos..], None));
+    }
+    segments
+}
+
+/// Always-on highlighting of watchlist keywords (e.g. this machine's hostname) in the
+/// tail output, matched literally and case-insensitively
+pub struct KeywordHighlighter {
+    regex: Option<Regex>,
+}
+
+impl KeywordHighlighter {
+    pub fn new(keywords: &[String]) -> Self {
+        let alternatives: Vec<String> = keywords
+            .iter()
+            .map(|keyword| keyword.trim())
+            .filter(|keyword| !keyword.is_empty())
+            .map(regex::escape)
+            .collect();
+        if alternatives.is_empty() {
+            return Self { regex: None };
+        }
+
+        let regex = RegexBuilder::new(&alternatives.join("|"))
+            .case_insensitive(true)
+            .build()
+            .ok();
+        Self { regex }
+    }
+
+    /// Byte ranges of keyword occurrences in a line
+    pub fn find_matches(&self, line: &str) -> Vec<(usize, usize)> {
+        self.regex
+            .as_ref()
+            .map(|regex| regex.find_iter(line).map(|m| (m.start(), m.end())).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// A highlighted range of a line in a rule's colors (None: keep the line's own color /
+/// no background)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleSpan {
+    pub start: usize,
+    pub end: usize,
+    pub foreground: Option<Color32>,
+    pub background: Option<Color32>,
+}
+
+/// The enabled highlight rules from the config, compiled
+#[derive(Default)]
+pub struct RuleHighlighter {
+    rules: Vec<(Regex, Option<Color32>, Option<Color32>)>,
+}
+
+impl RuleHighlighter {
+    /// Rules that are switched off or have an empty pattern are left out; rules that don't
+    /// compile are left out and returned as (name, error)
+    pub fn new(config: &HighlightRulesConfig) -> (Self, Vec<(String, String)>) {
+        let mut rules = Vec::new();
+        let mut errors = Vec::new();
+        if !config.enabled {
+            return (Self { rules }, errors);
+        }
+        for rule in config.rules.iter().filter(|rule| rule.enabled && !rule.pattern.is_empty()) {
+            match Regex::new(&rule.pattern) {
+                Ok(regex) => rules.push((
+                    regex,
+                    rule.foreground.as_deref().and_then(LogColorScheme::parse_hex_color),
+                    rule.background.as_deref().and_then(LogColorScheme::parse_hex_color),
+                )),
+                Err(e) => errors.push((rule.name.clone(), e.to_string())),
+            }
+        }
+        (Self { rules }, errors)
+    }
+
+    /// Non-overlapping spans in line order. Rules apply in order, so a range one rule
+    /// already took isn't highlighted again by a later one.
+    pub fn find_spans(&self, line: &str) -> Vec<RuleSpan> {
+        let mut spans: Vec<RuleSpan> = Vec::new();
+        for (regex, foreground, background) in &self.rules {
+            for m in regex.find_iter(line).filter(|m| !m.is_empty()) {
+                if spans.iter().all(|span| m.end() <= span.start || m.start() >= span.end) {
+                    spans.push(RuleSpan {
+                        start: m.start(),
+                        end: m.end(),
+                        foreground: *foreground,
+                        background: *background,
+                    });
+                }
+            }
+        }
+        spans.sort_by_key(|span| span.start);
+        spans
+    }
+}
+
+/// Line text in `color` with the rule spans drawn in their own colors
+pub fn rule_spans_job(font_id: FontId, line: &str, spans: &[RuleSpan], color: Color32) -> LayoutJob {
+    let plain = TextFormat::simple(font_id.clone(), color);
+    let mut job = LayoutJob::default();
+    let mut pos = 0;
+    for span in spans {
+        job.append(&line[pos..span.start], 0.0, plain.clone());
+        job.append(
+            &line[span.start..span.end],
+            0.0,
+            TextFormat {
+                color: span.foreground.unwrap_or(color),
+                background: span.background.unwrap_or(Color32::TRANSPARENT),
+                ..TextFormat::simple(font_id.clone(), color)
+            },
+        );
+        pos = span.end;
+    }
+    job.append(&line[pos..], 0.0, plain);
+    job
+}
+
+/// Name of this machine, from the environment or /etc/hostname
+pub fn local_hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_patterns() {
+        assert_eq!(parse_patterns(" 35=, 55= ,,ClOrdID="), vec!["35=", "55=", "ClOrdID="]);
+        assert!(parse_patterns("  ").is_empty());
+    }
+
+    #[test]
+    fn test_segments_per_pattern() {
+        let segments = highlight_segments("8=FIX|35=D|55=AAPL|", &["35=", "55="]);
+        assert_eq!(
+            segments,
+            vec![
+                ("8=FIX|", None),
+                ("35=", Some(0)),
+                ("D|", None),
+                ("55=", Some(1)),
+                ("AAPL|", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keywords_match_case_insensitively() {
+        let keywords = KeywordHighlighter::new(&["prod-db1".to_string(), " ".to_string(), "a.b".to_string()]);
+        assert_eq!(keywords.find_matches("connect PROD-DB1 via a.b"), vec![(8, 16), (21, 24)]);
+        assert!(keywords.find_matches("axb").is_empty());
+        assert!(KeywordHighlighter::new(&[]).find_matches("anything").is_empty());
+    }
+
+    #[test]
+    fn test_rules_apply_in_order() {
+        use crate::config::HighlightRule;
+        let rule = |name: &str, pattern: &str, foreground: &str, enabled: bool| HighlightRule {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            foreground: Some(foreground.to_string()),
+            background: None,
+            enabled,
+        };
+        let mut config = HighlightRulesConfig {
+            enabled: true,
+            rules: vec![
+                rule("ids", r"id=\d+", "#FF0000", true),
+                rule("digits", r"\d+", "#00FF00", true),
+                rule("off", "user", "#0000FF", false),
+                rule("broken", "(", "#0000FF", true),
+            ],
+        };
+
+        let (rules, errors) = RuleHighlighter::new(&config);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "broken");
+        let spans = rules.find_spans("user id=42 took 7ms");
+        let ranges: Vec<(usize, usize, Option<Color32>)> = spans.iter().map(|s| (s.start, s.end, s.foreground)).collect();
+        assert_eq!(
+            ranges,
+            vec![(5, 10, Some(Color32::from_rgb(255, 0, 0))), (16, 17, Some(Color32::from_rgb(0, 255, 0)))]
+        );
+
+        config.enabled = false;
+        assert!(RuleHighlighter::new(&config).0.find_spans("user id=42").is_empty());
+    }
+
+    #[test]
+    fn test_longest_pattern_wins_at_same_start() {
+        let segments = highlight_segments("ClOrdID=1", &["Cl", "ClOrdID="]);
+        assert_eq!(segments, vec![("ClOrdID=", Some(1)), ("1", None)]);
+    }
+}
@@ -20,6 +20,7 @@ pub enum NavigationCommand {
 
     // Clipboard operations
     YankMatchedLine, // yy - yank (copy) matched line to clipboard
+    YankLocation,    // yl - yank path:line of the selected match
 
     // File operations
     OpenInExplorer, // gf - open file in explorer/finder
@@ -27,8 +28,90 @@ pub enum NavigationCommand {
     // Bookmarks/Markers
     SetMark(char),  // ma, mb, etc - set a mark
     GotoMark(char), // 'a, 'b, etc - go to a mark
+
+    // Display
+    ToggleJsonView, // zj - pretty-print the current line as JSON
+    CenterView,     // zz - recenter the viewport on the current line
+    ToggleFold,     // za - fold/unfold the indented block under the current line
+}
+
+/// Where a key binding applies, used to group the help overlay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindingMode {
+    Global,
+    Grep,
+    Tail,
+    TailPreview,
+}
+
+impl BindingMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BindingMode::Global => "Global",
+            BindingMode::Grep => "Grep Mode",
+            BindingMode::Tail => "Tail Mode",
+            BindingMode::TailPreview => "Tail Preview",
+        }
+    }
 }
 
+pub struct KeyBinding {
+    pub mode: BindingMode,
+    pub keys: &'static str,
+    pub description: &'static str,
+}
+
+/// Every keyboard binding, listed once. The help overlay (?) is generated from this
+/// table, so update it alongside any change to the handlers.
+pub const KEY_BINDINGS: &[KeyBinding] = &[
+    KeyBinding { mode: BindingMode::Global, keys: "?", description: "Toggle this help" },
+    KeyBinding { mode: BindingMode::Global, keys: "Ctrl+P", description: "Command palette" },
+    KeyBinding { mode: BindingMode::Global, keys: "Ctrl+= / Ctrl+- / Ctrl+scroll", description: "Zoom font" },
+    KeyBinding { mode: BindingMode::Global, keys: "Esc", description: "Cancel pending key sequence / close help" },
+
+    KeyBinding { mode: BindingMode::Grep, keys: "n / p", description: "Next / previous match" },
+    KeyBinding { mode: BindingMode::Grep, keys: "3n / 3p", description: "Move by a count of matches" },
+    KeyBinding { mode: BindingMode::Grep, keys: "N / P", description: "First match in next / previous file" },
+    KeyBinding { mode: BindingMode::Grep, keys: "3N / 3P", description: "Move by a count of files" },
+    KeyBinding { mode: BindingMode::Grep, keys: "Tab / Shift+Tab", description: "First match in next / previous file" },
+    KeyBinding { mode: BindingMode::Grep, keys: "^ / $", description: "First / last match in current file" },
+    KeyBinding { mode: BindingMode::Grep, keys: "gg / G", description: "First / last match" },
+    KeyBinding { mode: BindingMode::Grep, keys: "yy", description: "Copy matched line to clipboard" },
+    KeyBinding { mode: BindingMode::Grep, keys: "yl", description: "Copy path:line of the match to clipboard" },
+    KeyBinding { mode: BindingMode::Grep, keys: "gf", description: "Open file in explorer" },
+    KeyBinding { mode: BindingMode::Grep, keys: "ma / 'a", description: "Set / go to mark" },
+    KeyBinding { mode: BindingMode::Grep, keys: "zj", description: "Pretty-print matched line as JSON" },
+    KeyBinding { mode: BindingMode::Grep, keys: ":", description: "Go to line in preview" },
+    KeyBinding { mode: BindingMode::Grep, keys: "zz", description: "Scroll preview back to the current line" },
+    KeyBinding { mode: BindingMode::Grep, keys: "Enter", description: "Run search" },
+    KeyBinding { mode: BindingMode::Grep, keys: "F5", description: "Repeat last search (with its original parameters)" },
+
+    KeyBinding { mode: BindingMode::Tail, keys: "+ / -", description: "Faster / slower polling" },
+    KeyBinding { mode: BindingMode::Tail, keys: "L / Shift+L", description: "Cycle log level filter (L only with no preview open)" },
+    KeyBinding { mode: BindingMode::Tail, keys: "Ctrl+B", description: "Bookmark the bottom output line" },
+    KeyBinding { mode: BindingMode::Tail, keys: "F2 / Shift+F2", description: "Next / previous output bookmark" },
+    KeyBinding { mode: BindingMode::Tail, keys: "]e / [e", description: "Next / previous ERROR line in the output" },
+    KeyBinding { mode: BindingMode::Tail, keys: "F6", description: "Move the keys between the file tree and the preview" },
+    KeyBinding { mode: BindingMode::Tail, keys: "j / k, ↑ / ↓", description: "Move through the file tree (while it has the keys)" },
+    KeyBinding { mode: BindingMode::Tail, keys: "Enter", description: "Preview the file / expand or collapse the group under the tree cursor" },
+    KeyBinding { mode: BindingMode::Tail, keys: "← / →", description: "Collapse / expand the group under the tree cursor" },
+    KeyBinding { mode: BindingMode::Tail, keys: "i", description: "Show only the tree cursor's file in the output (again to show all)" },
+
+    KeyBinding { mode: BindingMode::TailPreview, keys: "j / k", description: "Scroll down / up" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "h / l", description: "Scroll left / right" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "0 / $", description: "Scroll to line start / end" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "Ctrl+D / Ctrl+U", description: "Page down / up" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "gg / G", description: "Top / bottom" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "/", description: "Filter lines" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "n / N", description: "Next / previous filter match" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: ":", description: "Go to line" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "ma / 'a", description: "Set / go to mark" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "zj", description: "Pretty-print current line as JSON or XML" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "zz", description: "Center view on current line" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "za", description: "Fold / unfold the indented block under the current line" },
+    KeyBinding { mode: BindingMode::TailPreview, keys: "Esc", description: "Close filter / goto line" },
+];
+
 pub struct InputHandler {
     // State for building up multi-key commands (like "gg" or "3n")
     pending_keys: String,
@@ -50,8 +133,21 @@ impl InputHandler {
     /// Process keyboard input and return a command if one is complete
     pub fn process_input(&mut self, ctx: &egui::Context) -> Option<NavigationCommand> {
         let mut command = None;
+        // Tab must keep moving focus between widgets while a text field has it
+        let text_field_focused = ctx.wants_keyboard_input();
 
         ctx.input(|i| {
+            // Tab / Shift+Tab - first match in next / previous file, same as N / P
+            if i.key_pressed(egui::Key::Tab) && !text_field_focused && !i.modifiers.ctrl && !i.modifiers.alt {
+                command = Some(if i.modifiers.shift {
+                    NavigationCommand::PreviousFile
+                } else {
+                    NavigationCommand::NextFile
+                });
+                self.reset();
+                return;
+            }
+
             // Check for special shift+number combos FIRST (before digit processing)
             // '^' - first match in current file (Shift+6)
             if i.key_pressed(egui::Key::Num6)
@@ -96,6 +192,19 @@ impl InputHandler {
                 }
             }
 
+            // 'l' after 'y' - yank location
+            if i.key_pressed(egui::Key::L)
+                && !i.modifiers.shift
+                && !i.modifiers.ctrl
+                && !i.modifiers.alt
+                && self.pending_keys == "y"
+            {
+                info!("Command: yl (yank location)");
+                command = Some(NavigationCommand::YankLocation);
+                self.reset();
+                return;
+            }
+
             // Check for digit keys to build up count (e.g., "3n" -> move 3 times)
             // Only process if shift is NOT pressed (to avoid conflicts with ^ and $)
             for key in &[
@@ -238,6 +347,45 @@ impl InputHandler {
                 self.pending_keys = "'".to_string();
                 info!("Pending: ' (waiting for mark letter)");
             }
+            // 'z' - start of view sequence (zj = toggle JSON view, zz = center view)
+            else if i.key_pressed(egui::Key::Z)
+                && !i.modifiers.ctrl
+                && !i.modifiers.alt
+                && !i.modifiers.shift
+                && !self.waiting_for_mark_char
+                && !self.waiting_for_goto_mark_char
+            {
+                if self.pending_keys == "z" {
+                    info!("Command: zz (center view)");
+                    command = Some(NavigationCommand::CenterView);
+                    self.reset();
+                } else {
+                    self.pending_keys = "z".to_string();
+                    info!("Pending: z (waiting for second key)");
+                }
+            }
+            // 'j' - could be part of 'zj' sequence
+            else if i.key_pressed(egui::Key::J)
+                && !i.modifiers.ctrl
+                && !i.modifiers.alt
+                && !i.modifiers.shift
+                && self.pending_keys == "z"
+            {
+                info!("Command: zj (toggle JSON view)");
+                command = Some(NavigationCommand::ToggleJsonView);
+                self.reset();
+            }
+            // 'a' - could be part of 'za' sequence
+            else if i.key_pressed(egui::Key::A)
+                && !i.modifiers.ctrl
+                && !i.modifiers.alt
+                && !i.modifiers.shift
+                && self.pending_keys == "z"
+            {
+                info!("Command: za (toggle fold)");
+                command = Some(NavigationCommand::ToggleFold);
+                self.reset();
+            }
             // Letter keys - could be mark character
             else if self.waiting_for_mark_char || self.waiting_for_goto_mark_char {
                 // Check for any letter a-z
@@ -272,6 +420,11 @@ impl InputHandler {
         self.waiting_for_goto_mark_char = false;
     }
 
+    /// True while a multi-key sequence (e.g. "g", "z", "m") is waiting for its next key
+    pub fn has_pending_keys(&self) -> bool {
+        !self.pending_keys.is_empty()
+    }
+
     /// Get the current pending input state for display (e.g., "3" or "g")
     pub fn get_status(&self) -> String {
         if !self.count_buffer.is_empty() || !self.pending_keys.is_empty() {
@@ -322,3 +475,69 @@ impl InputHandler {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The keys that produce each command. The match is exhaustive, so a new
+    /// command can't be added without deciding which table row documents it.
+    fn keys_for(command: &NavigationCommand) -> &'static [&'static str] {
+        match command {
+            NavigationCommand::NextMatch => &["n"],
+            NavigationCommand::PreviousMatch => &["p"],
+            NavigationCommand::FirstMatch => &["gg"],
+            NavigationCommand::LastMatch => &["G"],
+            NavigationCommand::NextMatchWithCount(_) => &["3n"],
+            NavigationCommand::PreviousMatchWithCount(_) => &["3p"],
+            NavigationCommand::FirstMatchInCurrentFile => &["^"],
+            NavigationCommand::LastMatchInCurrentFile => &["$"],
+            NavigationCommand::NextFile => &["N", "Tab"],
+            NavigationCommand::PreviousFile => &["P", "Shift+Tab"],
+            NavigationCommand::NextFileWithCount(_) => &["3N"],
+            NavigationCommand::PreviousFileWithCount(_) => &["3P"],
+            NavigationCommand::YankMatchedLine => &["yy"],
+            NavigationCommand::YankLocation => &["yl"],
+            NavigationCommand::OpenInExplorer => &["gf"],
+            NavigationCommand::SetMark(_) => &["ma"],
+            NavigationCommand::GotoMark(_) => &["'a"],
+            NavigationCommand::ToggleJsonView => &["zj"],
+            NavigationCommand::CenterView => &["zz"],
+            NavigationCommand::ToggleFold => &["za"],
+        }
+    }
+
+    #[test]
+    fn every_command_key_is_in_the_binding_table() {
+        let commands = [
+            NavigationCommand::NextMatch,
+            NavigationCommand::PreviousMatch,
+            NavigationCommand::FirstMatch,
+            NavigationCommand::LastMatch,
+            NavigationCommand::NextMatchWithCount(3),
+            NavigationCommand::PreviousMatchWithCount(3),
+            NavigationCommand::FirstMatchInCurrentFile,
+            NavigationCommand::LastMatchInCurrentFile,
+            NavigationCommand::NextFile,
+            NavigationCommand::PreviousFile,
+            NavigationCommand::NextFileWithCount(3),
+            NavigationCommand::PreviousFileWithCount(3),
+            NavigationCommand::YankMatchedLine,
+            NavigationCommand::YankLocation,
+            NavigationCommand::OpenInExplorer,
+            NavigationCommand::SetMark('a'),
+            NavigationCommand::GotoMark('a'),
+            NavigationCommand::ToggleJsonView,
+            NavigationCommand::CenterView,
+            NavigationCommand::ToggleFold,
+        ];
+        for command in &commands {
+            for key in keys_for(command) {
+                let listed = KEY_BINDINGS
+                    .iter()
+                    .any(|b| b.keys.split(" / ").any(|k| k == *key));
+                assert!(listed, "{:?} key {:?} is missing from KEY_BINDINGS", command, key);
+            }
+        }
+    }
+}
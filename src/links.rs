@@ -0,0 +1,172 @@
+use eframe::egui;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// `path/to/file.ext:42` as found in stack traces and compiler output. The extension
+/// must start with a letter so timestamps (12:34:56) and IPs (10.0.0.1:80) don't match.
+static FILE_LINE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:[A-Za-z]:)?[\w.\-/\\]*[\w\-]\.[A-Za-z]\w*:(\d+)").unwrap()
+});
+
+/// A file:line reference found in a line of text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLink {
+    /// Byte range of the whole reference within the text
+    pub start: usize,
+    pub end: usize,
+    pub path: String,
+    pub line: usize,
+}
+
+/// Fill a location template such as `{path}:{line}` or `{path} +{line}`
+pub fn format_location(template: &str, path: &Path, line: usize) -> String {
+    template
+        .replace("{path}", &path.display().to_string())
+        .replace("{line}", &line.to_string())
+}
+
+/// Find all file:line references in a line of text
+pub fn find_file_links(text: &str) -> Vec<FileLink> {
+    FILE_LINE_REGEX
+        .captures_iter(text)
+        .filter_map(|caps| {
+            let whole = caps.get(0)?;
+            let line_match = caps.get(1)?;
+            let line = line_match.as_str().parse().ok()?;
+            Some(FileLink {
+                start: whole.start(),
+                end: whole.end(),
+                // Path is everything before the ":<line>" suffix
+                path: text[whole.start()..line_match.start() - 1].to_string(),
+                line,
+            })
+        })
+        .collect()
+}
+
+/// Resolve a link path; relative paths are taken relative to the directory of the
+/// file the reference was found in
+pub fn resolve_link_path(link_path: &str, source_file: &Path) -> PathBuf {
+    let path = Path::new(link_path);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+
+    match source_file.parent() {
+        Some(dir) => dir.join(path),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Render text in the given color with any file:line references shown as clickable
+/// links. Returns the link clicked this frame, if any.
+pub fn render_linkified_text(ui: &mut egui::Ui, text: &str, color: egui::Color32) -> Option<FileLink> {
+    let links = find_file_links(text);
+    if links.is_empty() {
+        ui.colored_label(color, text);
+        return None;
+    }
+
+    let mut clicked = None;
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+
+        let mut pos = 0;
+        for link in links {
+            if link.start > pos {
+                ui.colored_label(color, &text[pos..link.start]);
+            }
+            if ui
+                .link(&text[link.start..link.end])
+                .on_hover_text("Open in editor")
+                .clicked()
+            {
+                clicked = Some(link.clone());
+            }
+            pos = link.end;
+        }
+        if pos < text.len() {
+            ui.colored_label(color, &text[pos..]);
+        }
+    });
+
+    clicked
+}
+
+/// Like `render_linkified_text`, but the text between the links is laid out by
+/// `layout_gap` from its byte range, so other highlighting (e.g. watch keywords) stays
+/// alongside the links. Returns the link clicked this frame, if any.
+pub fn render_linkified_job(
+    ui: &mut egui::Ui,
+    text: &str,
+    mut layout_gap: impl FnMut(usize, usize) -> egui::text::LayoutJob,
+) -> Option<FileLink> {
+    let links = find_file_links(text);
+    let mut clicked = None;
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+
+        let mut pos = 0;
+        for link in links {
+            if link.start > pos {
+                ui.label(layout_gap(pos, link.start));
+            }
+            if ui
+                .link(&text[link.start..link.end])
+                .on_hover_text("Open in editor")
+                .clicked()
+            {
+                clicked = Some(link.clone());
+            }
+            pos = link.end;
+        }
+        if pos < text.len() {
+            ui.label(layout_gap(pos, text.len()));
+        }
+    });
+
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_location() {
+        let path = Path::new("/logs/app.log");
+        assert_eq!(format_location("{path}:{line}", path, 42), "/logs/app.log:42");
+        assert_eq!(format_location("{path} +{line}", path, 7), "/logs/app.log +7");
+    }
+
+    #[test]
+    fn test_find_rust_panic_location() {
+        let links = find_file_links("thread 'main' panicked at src/foo.rs:42:5:");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].path, "src/foo.rs");
+        assert_eq!(links[0].line, 42);
+    }
+
+    #[test]
+    fn test_find_java_frame() {
+        let links = find_file_links("    at com.example.Foo.bar(Foo.java:123)");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].path, "Foo.java");
+        assert_eq!(links[0].line, 123);
+    }
+
+    #[test]
+    fn test_ignores_timestamps_and_addresses() {
+        assert!(find_file_links("2024-01-15 12:34:56.789 connected to 10.0.0.1:8080").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_relative_to_source_dir() {
+        let resolved = resolve_link_path("src/foo.rs", Path::new("/var/log/build.log"));
+        assert_eq!(resolved, PathBuf::from("/var/log/src/foo.rs"));
+
+        let resolved = resolve_link_path("/abs/foo.rs", Path::new("/var/log/build.log"));
+        assert_eq!(resolved, PathBuf::from("/abs/foo.rs"));
+    }
+}
@@ -99,7 +99,7 @@ impl LogColorScheme {
     }
 
     /// Parse hex color string (#RRGGBB or #RRGGBBAA)
-    fn parse_hex_color(hex: &str) -> Option<Color32> {
+    pub fn parse_hex_color(hex: &str) -> Option<Color32> {
         let hex = hex.trim_start_matches('#');
 
         if hex.len() == 6 {
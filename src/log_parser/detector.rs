@@ -25,6 +25,19 @@ impl LogLevel {
             LogLevel::Unknown => 0,
         }
     }
+
+    /// Parse a level name as written in config ("error", "WARN", "warning", ...)
+    pub fn from_name(name: &str) -> Option<LogLevel> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "TRACE" => Some(LogLevel::Trace),
+            "DEBUG" => Some(LogLevel::Debug),
+            "INFO" => Some(LogLevel::Info),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "ERROR" => Some(LogLevel::Error),
+            "FATAL" | "CRITICAL" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
 }
 
 pub struct LogLevelDetector {
@@ -2,27 +2,49 @@ use arboard::Clipboard;
 use clap::{Parser, Subcommand};
 use eframe::egui;
 use log::{info, warn};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
-use std::time::Instant;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
 
+mod alert;
+mod columns;
+mod command_palette;
 mod config;
+mod diff;
+mod export;
 mod input_handler;
+mod links;
+mod marks;
 mod preview;
+mod redact;
+mod relative_time;
+mod replace;
 mod search;
+mod session;
+mod status_report;
+mod sparkline;
+mod stream_source;
 mod grep_mode;
+mod fuzzy;
+mod highlight;
 mod tail_mode;
 mod splitter;
 mod tail_layout;
+mod tail_start;
 mod theme;
+mod truncate;
+mod util;
 mod filter;
 mod log_parser;
 mod widgets;
 
+use command_palette::CommandPalette;
 use config::Config;
 use input_handler::{InputHandler, NavigationCommand};
 use preview::FilePreview;
-use search::{SearchEngine, SearchResult};
+use redact::Redactor;
+use search::{SearchEngine, SearchEvent, SearchMode, SearchResult};
 use splitter::{Splitter, SplitterAxis};
 use tail_layout::TailLayout;
 use theme::Theme;
@@ -43,10 +65,54 @@ struct Cli {
     #[arg(short = 'f', long = "follow")]
     follow: bool,
 
-    /// Load a tail layout file
+    /// Load a tail layout file (YAML, or JSON with a .json extension)
     #[arg(long = "tail-layout", short = 'l', value_name = "FILE")]
     tail_layout: Option<PathBuf>,
 
+    /// Tail every file in this directory matching --pattern (repeatable)
+    #[arg(long = "dir", value_name = "DIR")]
+    dirs: Vec<PathBuf>,
+
+    /// File name glob for --dir
+    #[arg(long = "pattern", value_name = "GLOB", default_value = "*.log")]
+    pattern: String,
+
+    /// Put each --dir's files in a tree group named after the directory
+    #[arg(long = "group")]
+    group: bool,
+
+    /// Grep mode: pre-fill the search query and run it on startup
+    #[arg(long = "query", short = 'q', value_name = "QUERY")]
+    query: Option<String>,
+
+    /// Grep mode: directory (or file) to search
+    #[arg(long = "path", value_name = "PATH")]
+    search_path: Option<String>,
+
+    /// Grep mode: file name glob to search
+    #[arg(long = "file-pattern", value_name = "GLOB")]
+    file_pattern: Option<String>,
+
+    /// Match --query as a regular expression (the default)
+    #[arg(long = "regex", conflicts_with_all = ["literal", "fuzzy"])]
+    regex: bool,
+
+    /// Match --query as literal text
+    #[arg(long = "literal", conflicts_with = "fuzzy")]
+    literal: bool,
+
+    /// Match --query fuzzily
+    #[arg(long = "fuzzy")]
+    fuzzy: bool,
+
+    /// Match --query case-sensitively
+    #[arg(long = "case-sensitive")]
+    case_sensitive: bool,
+
+    /// Tail mode: start each file with its last N lines instead of only new ones
+    #[arg(long = "lines", short = 'n', value_name = "N", default_value_t = 0, global = true)]
+    lines: usize,
+
     /// Files to tail/follow (when using -f flag)
     #[arg(value_name = "FILES")]
     files: Vec<PathBuf>,
@@ -67,6 +133,76 @@ struct StartupConfig {
     mode: AppMode,
     tail_files: Vec<PathBuf>,
     tail_layout: Option<PathBuf>,
+    tail_dirs: Vec<TailDirectory>,
+    /// Lines of history each tailed file starts with (--lines)
+    tail_lines: usize,
+    /// Grep inputs and font size from the last session, when it's being restored
+    session: Option<session::Session>,
+    grep_args: GrepArgs,
+}
+
+/// Grep inputs given on the command line (--query, --path, ...)
+#[derive(Default)]
+struct GrepArgs {
+    query: Option<String>,
+    search_path: Option<String>,
+    file_pattern: Option<String>,
+    search_mode: Option<SearchMode>,
+    case_sensitive: bool,
+}
+
+impl GrepArgs {
+    fn from_cli(cli: &Cli) -> Self {
+        let search_mode = if cli.literal {
+            Some(SearchMode::Literal)
+        } else if cli.fuzzy {
+            Some(SearchMode::Fuzzy)
+        } else if cli.regex {
+            Some(SearchMode::Regex)
+        } else {
+            None
+        };
+        Self {
+            query: cli.query.clone(),
+            search_path: cli.search_path.clone(),
+            file_pattern: cli.file_pattern.clone(),
+            search_mode,
+            case_sensitive: cli.case_sensitive,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.query.is_none()
+            && self.search_path.is_none()
+            && self.file_pattern.is_none()
+            && self.search_mode.is_none()
+            && !self.case_sensitive
+    }
+
+    fn apply(self, grep_state: &mut GrepState) {
+        if let Some(query) = self.query {
+            grep_state.search_query = query;
+        }
+        if let Some(path) = self.search_path {
+            grep_state.search_path = VisGrepApp::expand_tilde(&path);
+        }
+        if let Some(pattern) = self.file_pattern {
+            grep_state.file_pattern = pattern;
+        }
+        if let Some(mode) = self.search_mode {
+            grep_state.search_mode = mode;
+        }
+        if self.case_sensitive {
+            grep_state.case_sensitive = true;
+        }
+    }
+}
+
+/// A directory to tail from the command line (--dir)
+struct TailDirectory {
+    path: PathBuf,
+    pattern: String,
+    group: bool,
 }
 
 impl Default for StartupConfig {
@@ -75,6 +211,10 @@ impl Default for StartupConfig {
             mode: AppMode::Grep,
             tail_files: Vec::new(),
             tail_layout: None,
+            tail_dirs: Vec::new(),
+            tail_lines: 0,
+            session: None,
+            grep_args: GrepArgs::default(),
         }
     }
 }
@@ -83,6 +223,9 @@ impl Default for StartupConfig {
 // Application Mode Types
 // ============================================================================
 
+/// Font sizes the size controls and zooming allow
+const FONT_SIZE_RANGE: std::ops::RangeInclusive<f32> = 8.0..=24.0;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AppMode {
     Grep,
@@ -90,6 +233,15 @@ enum AppMode {
     Test, // Minimal test mode to debug splitter
 }
 
+impl From<session::SessionMode> for AppMode {
+    fn from(mode: session::SessionMode) -> Self {
+        match mode {
+            session::SessionMode::Grep => AppMode::Grep,
+            session::SessionMode::Tail => AppMode::Tail,
+        }
+    }
+}
+
 // ============================================================================
 // Grep Mode State
 // ============================================================================
@@ -97,24 +249,90 @@ enum AppMode {
 struct GrepState {
     search_path: String,
     file_pattern: String,
+    file_pattern_ignore_case: bool,
     search_query: String,
     case_sensitive: bool,
-    use_regex: bool,
+    search_mode: SearchMode,
     recursive: bool,
+    follow_symlinks: bool,
     file_age_hours: Option<u64>,
+    // Match the query against file names only, without reading the files
+    file_names_only: bool,
 
     search_engine: SearchEngine,
     results: Vec<SearchResult>,
     selected_result: Option<usize>,
 
     searching: bool,
+    search_receiver: Option<Receiver<SearchEvent>>,
+    search_started: Instant,
+    // Files the last search looked at, and how long it took once finished
+    files_scanned: Option<usize>,
+    search_duration: Option<Duration>,
     results_filter: String,
     collapsing_state: HashMap<usize, bool>,
+    // Nest file headers under one header per parent directory
+    group_by_directory: bool,
+    // Collapse matches with identical text into one row per distinct line
+    unique_lines: bool,
+    // Expand/Collapse All for the directory headers, applied on the next frame
+    dir_headers_open: Option<bool>,
     last_search_time: Instant,
     pending_search: bool,
+    // Parameters of the last search that ran, for Repeat last search
+    last_search: Option<session::GrepSession>,
+    // The pattern the last search ran after escaping and flags, shown on the status bar
+    executed_pattern: Option<String>,
+    // Why the file name glob didn't compile, shown on the status bar instead of results
+    file_pattern_error: Option<String>,
+    // Why the query doesn't compile as a regex, and the query and mode that was checked,
+    // so the regex is only compiled again once one of them changes
+    query_error: Option<String>,
+    query_error_checked: Option<(String, SearchMode)>,
+    // Matcher and mode of the last search, which the replacement substitutes with
+    search_matcher: Option<(search::LineMatcher, SearchMode)>,
+
+    // Search and replace over the results: the replacement (with $1 references), whether
+    // Write changes only reports (dry run), the open confirmation and the last outcome
+    replace_enabled: bool,
+    replace_text: String,
+    replace_dry_run: bool,
+    replace_confirm: bool,
+    replace_report: Option<String>,
+
+    // Quick match counts shown when hovering a saved pattern: the search settings they
+    // were counted under, the counts by pattern (None = doesn't compile) and the count
+    // still running
+    count_patterns_on_hover: bool,
+    pattern_counts_scope: String,
+    pattern_counts: HashMap<String, Option<search::MatchCount>>,
+    pattern_count_receiver: Option<(String, Receiver<Option<search::MatchCount>>)>,
 
     // FIX message highlighting pattern
     fix_highlight_pattern: String,
+
+    // Pretty-printed JSON of the matched line (zj), shown in a popup while Some
+    json_view: Option<String>,
+
+    // ':' goto-line input in the preview
+    goto_line_active: bool,
+    goto_line_input: String,
+
+    // Preview gutter and whitespace glyph toggles
+    show_line_numbers: bool,
+    show_whitespace: bool,
+
+    // Side-by-side diff shown in place of the preview while Some
+    diff: Option<diff::FileDiff>,
+
+    // Watch mode: re-run the search when matching files under search_path change
+    watch_enabled: bool,
+    watcher: Option<notify::RecommendedWatcher>,
+    watch_receiver: Option<Receiver<notify::Result<notify::Event>>>,
+    watched_path: String,
+    watch_change_time: Option<Instant>,
+    // Selection (path, line) to restore as re-run results stream in
+    restore_selection: Option<(PathBuf, usize)>,
     
     // Font settings
     font_size: f32,
@@ -123,33 +341,119 @@ struct GrepState {
 impl GrepState {
     fn new(config: &Config) -> Self {
         Self {
-            search_path: VisGrepApp::expand_tilde(
-                std::env::current_dir()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .as_ref(),
-            ),
-            file_pattern: String::from("*.log"),
+            search_path: match &config.ui.default_search_path {
+                Some(path) => VisGrepApp::expand_tilde(path),
+                None => VisGrepApp::expand_tilde(
+                    std::env::current_dir()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .as_ref(),
+                ),
+            },
+            file_pattern: config
+                .ui
+                .default_file_pattern
+                .clone()
+                .unwrap_or_else(|| String::from("*.log")),
+            file_pattern_ignore_case: false,
             search_query: String::new(),
             case_sensitive: false,
-            use_regex: true,
+            search_mode: SearchMode::Regex,
             recursive: true,
+            follow_symlinks: true,
             file_age_hours: None,
+            file_names_only: false,
 
             search_engine: SearchEngine::new(),
             results: Vec::new(),
             selected_result: None,
 
             searching: false,
+            search_receiver: None,
+            search_started: Instant::now(),
+            files_scanned: None,
+            search_duration: None,
             results_filter: String::new(),
             collapsing_state: HashMap::new(),
+            group_by_directory: false,
+            unique_lines: false,
+            dir_headers_open: None,
             last_search_time: Instant::now(),
             pending_search: false,
+            last_search: None,
+            executed_pattern: None,
+            file_pattern_error: None,
+            query_error: None,
+            query_error_checked: None,
+            search_matcher: None,
+            replace_enabled: false,
+            replace_text: String::new(),
+            replace_dry_run: true,
+            replace_confirm: false,
+            replace_report: None,
+            count_patterns_on_hover: true,
+            pattern_counts_scope: String::new(),
+            pattern_counts: HashMap::new(),
+            pattern_count_receiver: None,
 
             fix_highlight_pattern: String::new(),
+            json_view: None,
+            goto_line_active: false,
+            goto_line_input: String::new(),
+            show_line_numbers: true,
+            show_whitespace: false,
+            diff: None,
+
+            watch_enabled: false,
+            watcher: None,
+            watch_receiver: None,
+            watched_path: String::new(),
+            watch_change_time: None,
+            restore_selection: None,
             font_size: config.ui.font_size,
         }
     }
+
+    /// The search inputs as they stand
+    fn search_params(&self) -> session::GrepSession {
+        session::GrepSession {
+            search_path: self.search_path.clone(),
+            file_pattern: self.file_pattern.clone(),
+            file_pattern_ignore_case: self.file_pattern_ignore_case,
+            search_query: self.search_query.clone(),
+            case_sensitive: self.case_sensitive,
+            search_mode: self.search_mode,
+            recursive: self.recursive,
+            follow_symlinks: self.follow_symlinks,
+            file_age_hours: self.file_age_hours,
+            file_names_only: self.file_names_only,
+        }
+    }
+
+    /// Check the query for regex errors if it or the search mode changed since last time
+    fn refresh_query_error(&mut self) {
+        let checked = self
+            .query_error_checked
+            .as_ref()
+            .is_some_and(|(query, mode)| *query == self.search_query && *mode == self.search_mode);
+        if !checked {
+            self.query_error = search::query_error(&self.search_query, self.search_mode);
+            self.query_error_checked = Some((self.search_query.clone(), self.search_mode));
+        }
+    }
+
+    fn apply_search_params(&mut self, params: session::GrepSession) {
+        self.search_path = params.search_path;
+        self.file_pattern = params.file_pattern;
+        self.file_pattern_ignore_case = params.file_pattern_ignore_case;
+        self.search_query = params.search_query;
+        self.case_sensitive = params.case_sensitive;
+        self.search_mode = params.search_mode;
+        self.recursive = params.recursive;
+        self.follow_symlinks = params.follow_symlinks;
+        self.file_age_hours = params.file_age_hours;
+        self.file_names_only = params.file_names_only;
+    }
 }
 
 // ============================================================================
@@ -158,7 +462,7 @@ impl GrepState {
 
 use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum ThrottleState {
@@ -174,6 +478,18 @@ enum ThrottleReason {
     BufferFull,
 }
 
+/// How far the saved-pattern hover count looks before giving an approximate answer
+const PATTERN_COUNT_LIMITS: search::CountLimits = search::CountLimits {
+    max_files: 200,
+    max_matches: 1000,
+};
+
+/// Lines the tail preview keeps while following, for files without their own setting
+const DEFAULT_PREVIEW_FOLLOW_LINES: usize = 1000;
+
+/// How long an unterminated last line is held back before it's shown as-is
+const PARTIAL_LINE_FLUSH: Duration = Duration::from_secs(1);
+
 struct TailedFile {
     // Identity
     path: PathBuf,
@@ -182,6 +498,12 @@ struct TailedFile {
     // File monitoring
     last_size: u64,
     last_position: u64,
+    // Kept open between polls; dropped to force a reopen after truncation/rotation
+    handle: Option<File>,
+    // Bytes after the last newline of the previous read, held back until the writer
+    // finishes the line
+    partial_line: Vec<u8>,
+    partial_since: Option<Instant>,
 
     // Activity tracking
     is_active: bool,
@@ -190,6 +512,8 @@ struct TailedFile {
 
     // Throttling
     paused: bool,
+    // Still read and counted, but left out of the combined output
+    muted: bool,
     throttle_state: ThrottleState,
 
     // Statistics
@@ -198,13 +522,38 @@ struct TailedFile {
 
     // Log level tracking for recent activity
     level_counts_since_last_read: HashMap<log_parser::LogLevel, usize>,
+    // Lines at each level since tailing started, for the status report
+    level_totals: HashMap<log_parser::LogLevel, usize>,
 
     // Group membership
     group_id: Option<String>,
+
+    // Last-modified time, refreshed whenever the file is polled
+    modified: Option<std::time::SystemTime>,
+
+    // Color chosen by the user, overriding get_color_for_file
+    color: Option<egui::Color32>,
+
+    // The file was deleted; polling carries on and picks it up from the start if it returns
+    missing: bool,
+
+    // Identity of the file at `path` when last opened, to tell a rotation (a new file
+    // under the name) from a truncation (the same file cut short)
+    inode: Option<u64>,
+
+    // Set for ssh:// and journal:// entries and FIFOs, which are read on a background
+    // thread instead of polling the path
+    stream: Option<stream_source::StreamSource>,
 }
 
 impl TailedFile {
     fn new(path: PathBuf) -> std::io::Result<Self> {
+        if let Some(kind) = stream_source::StreamKind::parse(&path.to_string_lossy()) {
+            let mut file = Self::at_position(path, kind.display_name(), 0, None);
+            file.stream = Some(stream_source::StreamSource::new(kind));
+            return Ok(file);
+        }
+
         // Resolve to absolute path
         let absolute_path = if path.is_absolute() {
             path
@@ -220,68 +569,273 @@ impl TailedFile {
 
         // Get initial file size without keeping handle open
         let metadata = std::fs::metadata(&absolute_path)?;
+        if stream_source::is_fifo(&metadata) {
+            let kind = stream_source::StreamKind::Fifo { path: absolute_path.clone() };
+            let mut file = Self::at_position(absolute_path, display_name, 0, None);
+            file.stream = Some(stream_source::StreamSource::new(kind));
+            return Ok(file);
+        }
         let size = metadata.len();
+        let mut file = Self::at_position(absolute_path, display_name, size, metadata.modified().ok());
+        file.inode = file_id(&metadata);
+        Ok(file)
+    }
 
-        Ok(Self {
-            path: absolute_path,
+    fn at_position(path: PathBuf, display_name: String, size: u64, modified: Option<std::time::SystemTime>) -> Self {
+        Self {
+            path,
             display_name,
             last_size: size,
             last_position: size, // Start at end (like tail -f)
+            handle: None,
+            partial_line: Vec::new(),
+            partial_since: None,
             is_active: false,
             last_activity: Instant::now(),
             lines_since_last_read: 0,
             paused: false,
+            muted: false,
             throttle_state: ThrottleState::Normal,
             total_lines_read: 0,
             total_bytes_read: 0,
             level_counts_since_last_read: HashMap::new(),
+            level_totals: HashMap::new(),
             group_id: None,
-        })
+            modified,
+            color: None,
+            missing: false,
+            inode: None,
+            stream: None,
+        }
+    }
+
+    /// Move the read position back so the first poll delivers the last `lines` lines
+    /// already in the file, instead of only what is written from now on
+    fn start_with_last_lines(&mut self, lines: usize) {
+        if lines == 0 || self.stream.is_some() {
+            return;
+        }
+        let offset = File::open(&self.path)
+            .and_then(|mut file| tail_start::last_lines_offset(&mut file, self.last_position, lines));
+        match offset {
+            Ok(offset) => self.last_position = offset,
+            Err(e) => warn!("Failed to read back {} lines of {}: {}", lines, self.display_name, e),
+        }
+    }
+
+    /// Read the whole file on the first poll, then follow it
+    fn start_from_beginning(&mut self) {
+        if self.stream.is_none() {
+            self.last_position = 0;
+        }
     }
 
     fn check_for_updates(&mut self) -> std::io::Result<Vec<String>> {
-        // Re-open file to get fresh metadata
-        let metadata = std::fs::metadata(&self.path)?;
-        let current_size = metadata.len();
-        
-        // Debug output for file rotation detection
-        if current_size < self.last_size {
-            info!("File rotation detected for {}: size decreased from {} to {}", 
-                self.display_name, self.last_size, current_size);
+        if let Some(stream) = self.stream.as_mut() {
+            let lines = stream.poll();
+            if !lines.is_empty() {
+                self.modified = Some(std::time::SystemTime::now());
+                self.total_bytes_read += lines.iter().map(|line| line.len() as u64 + 1).sum::<u64>();
+                self.total_lines_read += lines.len();
+            }
+            return Ok(lines);
+        }
+
+        // Open lazily on first poll (and again after a rotation or deletion dropped the handle)
+        if self.handle.is_none() {
+            match File::open(&self.path) {
+                Ok(file) => {
+                    if self.missing {
+                        // A new file under the old name: read it from the start
+                        info!("{} reappeared", self.display_name);
+                        self.missing = false;
+                        self.last_position = 0;
+                        self.last_size = 0;
+                    }
+                    self.inode = file.metadata().ok().as_ref().and_then(file_id);
+                    self.handle = Some(file);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(self.mark_missing()),
+                Err(e) => return Err(e),
+            }
         }
 
-        if current_size > self.last_size {
-            // File grew - read new content
-            let mut file = File::open(&self.path)?;
+        // Read whatever was appended since the last poll through the open handle
+        let mut buffer = Vec::new();
+        if let Some(file) = self.handle.as_mut() {
             file.seek(SeekFrom::Start(self.last_position))?;
+            file.read_to_end(&mut buffer)?;
+        }
 
-            let reader = BufReader::new(file);
-            let new_lines: Vec<String> = reader.lines().filter_map(|l| l.ok()).collect();
+        if !buffer.is_empty() {
+            self.modified = self
+                .handle
+                .as_ref()
+                .and_then(|file| file.metadata().ok())
+                .and_then(|metadata| metadata.modified().ok());
 
-            let bytes_read = current_size - self.last_position;
+            let bytes_read = buffer.len() as u64;
             self.total_bytes_read += bytes_read;
+            self.last_position += bytes_read;
+            self.last_size = self.last_position;
+
+            // Only hand out complete lines; a trailing fragment waits for the rest
+            self.partial_line.extend_from_slice(&buffer);
+            let new_lines = match self.partial_line.iter().rposition(|&b| b == b'\n') {
+                Some(last_newline) => {
+                    let rest = self.partial_line.split_off(last_newline + 1);
+                    let complete = std::mem::replace(&mut self.partial_line, rest);
+                    Self::decode_lines(&complete)
+                }
+                None => Vec::new(),
+            };
+            self.partial_since = if self.partial_line.is_empty() {
+                None
+            } else {
+                self.partial_since.or(Some(Instant::now()))
+            };
+
             self.total_lines_read += new_lines.len();
-            self.last_size = current_size;
-            self.last_position = current_size;
+            return Ok(new_lines);
+        }
+
+        // A writer that never terminates its last line shouldn't hide it forever
+        if self.partial_since.is_some_and(|since| since.elapsed() >= PARTIAL_LINE_FLUSH) {
+            let fragment = std::mem::take(&mut self.partial_line);
+            self.partial_since = None;
+            let new_lines = Self::decode_lines(&fragment);
+            self.total_lines_read += new_lines.len();
+            return Ok(new_lines);
+        }
 
-            Ok(new_lines)
-        } else if current_size < self.last_size {
-            // File was truncated/rotated
+        // Nothing new through the handle - stat the path to catch truncation/rotation,
+        // which a handle to the old file can't see
+        let metadata = match std::fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(self.mark_missing()),
+            Err(e) => return Err(e),
+        };
+        let current_size = metadata.len();
+        self.modified = metadata.modified().ok();
+
+        let inode = file_id(&metadata);
+        let rotated = self.inode.is_some() && inode.is_some() && inode != self.inode;
+        if rotated || current_size < self.last_position {
+            // A new file under the name (rename and recreate), or the same file cut
+            // short (copytruncate); either way read from its start
+            let kind = if rotated { "ROTATED" } else { "TRUNCATED" };
+            let marker = format!(
+                "[FILE {}: {} -> {}]",
+                kind,
+                format_bytes(self.last_position),
+                format_bytes(current_size)
+            );
+            info!("{} for {}: {}", kind, self.display_name, marker);
+            self.handle = None;
             self.last_position = 0;
             self.last_size = current_size;
-            Ok(vec!["[FILE TRUNCATED/ROTATED]".to_string()])
+            self.partial_line.clear();
+            self.partial_since = None;
+            Ok(vec![marker])
         } else {
             // No change
             Ok(vec![])
         }
     }
+
+    /// The path no longer exists: drop the handle so the next poll looks for a new file.
+    /// Returns a marker line the first time, nothing while it stays missing.
+    fn mark_missing(&mut self) -> Vec<String> {
+        self.handle = None;
+        self.partial_line.clear();
+        self.partial_since = None;
+        if self.missing {
+            return Vec::new();
+        }
+        warn!("{} was deleted: {}", self.display_name, self.path.display());
+        self.missing = true;
+        vec!["[FILE DELETED]".to_string()]
+    }
+
+    fn decode_lines(bytes: &[u8]) -> Vec<String> {
+        String::from_utf8_lossy(bytes)
+            .lines()
+            .map(|l| l.to_string())
+            .collect()
+    }
+}
+
+/// Inode number on Unix; elsewhere there is nothing cheap to compare, so rotation is
+/// only noticed when the file shrinks
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Byte count for file event markers, e.g. "12.5 MB"
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
 }
 
 struct LogLine {
+    /// Monotonic id across the whole session; survives the buffer dropping old lines
+    seq: u64,
     timestamp: Instant,
     source_file: String,
+    source_path: PathBuf,
     line_number: usize,
     content: String,
+    /// Detected once when the line arrives
+    level: log_parser::LogLevel,
+    is_json: bool,
+}
+
+/// What decides which buffered lines show as output rows; the rows are rebuilt when
+/// any of it changes
+#[derive(PartialEq)]
+struct OutputRowFilters {
+    isolated_file: Option<PathBuf>,
+    muted_sources: HashSet<PathBuf>,
+    // Paths of the files passing the tree filter, when it applies to the output
+    tree_sources: Option<HashSet<PathBuf>>,
+    // Level filter: active, minimum level, show unknown
+    level: (bool, log_parser::LogLevel, bool),
+    // Output grep query and case sensitivity
+    grep: (String, bool),
+    collapse_repeats: bool,
+}
+
+/// The output rows as (seq of the row's line, repeat count). With collapse_repeats, a
+/// run of identical consecutive lines from the same source is one row showing the most
+/// recent of them.
+struct OutputRows {
+    filters: OutputRowFilters,
+    rows: VecDeque<(u64, usize)>,
+}
+
+/// Snapshot of a bookmarked output line, kept so it can still be listed after the
+/// line itself has been dropped from the ring buffer
+struct OutputBookmark {
+    timestamp: Instant,
+    source_file: String,
+    content: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -290,6 +844,13 @@ enum PreviewMode {
     Paused,    // Manual navigation
 }
 
+/// A row of the tail file tree
+#[derive(Debug, Clone, PartialEq)]
+enum TreeItem {
+    Group(String),
+    File(usize),
+}
+
 struct TailState {
     // Files being monitored
     files: Vec<TailedFile>,
@@ -297,21 +858,66 @@ struct TailState {
 
     // Output buffer (circular)
     output_buffer: VecDeque<LogLine>,
+    // Rows of the buffer passing the output filters, kept in step with pushes and drops;
+    // None until rebuilt after the buffer was cleared
+    output_rows: Option<OutputRows>,
+    // Lines in the buffer at each level, kept as lines are pushed and dropped
+    buffer_level_counts: HashMap<log_parser::LogLevel, usize>,
     max_buffer_lines: usize,
+    // Up to this many of the oldest ERROR/FATAL lines outlive ordinary lines when trimming
+    keep_error_lines: usize,
+    next_line_seq: u64,
+
+    // Output bookmarks keyed by LogLine::seq
+    output_bookmarks: BTreeMap<u64, OutputBookmark>,
+    output_scroll_to_seq: Option<u64>,
+    last_bookmark_jump: Option<u64>,
+    output_bottom_seq: Option<u64>,
+    // ]e / [e: the bracket typed so far (true for forward) and the last error jumped to
+    pending_error_jump: Option<bool>,
+    last_error_jump: Option<u64>,
 
     // Global controls
     paused_all: bool,
     auto_scroll: bool,
+    collapse_repeats: bool,
+    // Strip above the output decoding each [source] color
+    show_source_legend: bool,
+    output_json_view: Option<String>,
+    column_view: columns::ColumnViewState,
+    // Seqs of over-long lines the user expanded to full length
+    expanded_lines: HashSet<u64>,
 
     // Filtering
     filter_pattern: String,
     preview_filter: filter::PreviewFilter,
     tree_filter: filter::TreeFilter,
+    // Keyboard navigation of the file tree: the highlighted row, whether keys go to the
+    // tree rather than the preview (F6), and a pending scroll to bring the row into view
+    tree_cursor: Option<TreeItem>,
+    tree_focused: bool,
+    tree_scroll_to_cursor: bool,
+    // File whose lines alone are shown in the output, leaving the tree filter as it is
+    isolated_file: Option<PathBuf>,
     log_level_filter: filter::LogLevelFilter,
+    // Watchlist keywords highlighted in the output regardless of filters
+    keyword_highlighter: highlight::KeywordHighlighter,
+    // Config highlight rules, compiled; rebuilt when they're edited in the rules window.
+    // Rules that don't compile are listed there as (name, error)
+    rule_highlighter: highlight::RuleHighlighter,
+    highlight_rule_errors: Vec<(String, String)>,
+    show_highlight_rules: bool,
+
+    // Live grep over the combined output
+    output_grep: filter::OutputGrep,
+    // Warning/error counts per time bucket, drawn as a sparkline over the output
+    level_histogram: sparkline::LevelHistogram,
 
     // Polling
     last_poll_time: Instant,
     poll_interval_ms: u64,
+    // No new lines for this long marks a file idle
+    idle_timeout: Duration,
 
     // Statistics
     total_lines_received: usize,
@@ -319,14 +925,25 @@ struct TailState {
 
     // Performance tuning
     max_lines_per_poll: usize,
+    // History lines each newly added file starts with (--lines); 0 starts at the end
+    initial_lines: usize,
 
     // Preview pane
     preview_selected_file: Option<usize>,
     preview_mode: PreviewMode,
     preview_scroll_offset: f32,
+    // Scroll offset of files last left in Paused mode, restored when they're reselected
+    preview_positions: HashMap<PathBuf, f32>,
+    // Lines the preview keeps while following, for the selected file; each file's own
+    // choice is remembered by path
     preview_follow_lines: usize,
+    preview_follow_lines_by_file: HashMap<PathBuf, usize>,
     preview_content: Vec<String>,
+    // 0-indexed file line of preview_content[0]; nonzero when following the tail
+    preview_first_line: usize,
     preview_needs_reload: bool,
+    // Marks read from the selected file's .marks file, placed once its content loads
+    pending_marks: Option<marks::MarksFile>,
 
     // Text viewer state (encapsulates preview display, navigation, filtering, goto)
     text_viewer_state: widgets::TextViewerState,
@@ -336,6 +953,17 @@ struct TailState {
 
     // Tree layout
     layout: Option<TailLayout>,
+    // Group expand/collapse choices per layout file, by group name path, carried across
+    // layout reloads without leaking into another layout with a same-named group
+    group_collapse_states: HashMap<PathBuf, HashMap<String, bool>>,
+    // File the layout was loaded from, remembered for restore_session
+    layout_path: Option<PathBuf>,
+    // Why the last layout (or some of its files) failed to load, shown above the file tree
+    layout_problems: Vec<String>,
+
+    // "Add directory" menu: file name glob, and whether to group the files
+    dir_pattern: String,
+    dir_group: bool,
 
     // UI state
     control_panel_height: f32,
@@ -348,32 +976,93 @@ impl TailState {
             files: Vec::new(),
             selected_file_index: None,
             output_buffer: VecDeque::new(),
+            output_rows: None,
+            buffer_level_counts: HashMap::new(),
             max_buffer_lines: 10000,
+            keep_error_lines: config.ui.keep_error_lines,
+            next_line_seq: 0,
+            output_bookmarks: BTreeMap::new(),
+            output_scroll_to_seq: None,
+            last_bookmark_jump: None,
+            output_bottom_seq: None,
+            pending_error_jump: None,
+            last_error_jump: None,
             paused_all: false,
             auto_scroll: true,
+            collapse_repeats: false,
+            show_source_legend: true,
+            output_json_view: None,
+            expanded_lines: HashSet::new(),
+            column_view: columns::ColumnViewState::new(),
             filter_pattern: String::new(),
             preview_filter: filter::PreviewFilter::new(),
             tree_filter: filter::TreeFilter::new(),
+            tree_cursor: None,
+            tree_focused: false,
+            tree_scroll_to_cursor: false,
+            isolated_file: None,
             log_level_filter: filter::LogLevelFilter::new(),
+            keyword_highlighter: {
+                let mut keywords = config.ui.highlight_keywords.clone();
+                if config.ui.highlight_hostname {
+                    keywords.extend(highlight::local_hostname());
+                }
+                highlight::KeywordHighlighter::new(&keywords)
+            },
+            rule_highlighter: highlight::RuleHighlighter::default(),
+            highlight_rule_errors: Vec::new(),
+            show_highlight_rules: false,
+            output_grep: filter::OutputGrep::new(),
+            level_histogram: sparkline::LevelHistogram::new(Instant::now()),
             last_poll_time: Instant::now(),
             poll_interval_ms: config.ui.poll_interval_ms,
+            idle_timeout: Duration::from_secs(config.ui.idle_timeout_secs),
             total_lines_received: 0,
             lines_dropped: 0,
             max_lines_per_poll: 100,
+            initial_lines: 0,
             preview_selected_file: None,
             preview_mode: PreviewMode::Following,
             preview_scroll_offset: 0.0,
-            preview_follow_lines: 1000,
+            preview_positions: HashMap::new(),
+            preview_follow_lines: DEFAULT_PREVIEW_FOLLOW_LINES,
+            preview_follow_lines_by_file: HashMap::new(),
             preview_content: Vec::new(),
+            preview_first_line: 0,
             preview_needs_reload: false,
-            text_viewer_state: widgets::TextViewerState::new(config.ui.font_size),
+            pending_marks: None,
+            text_viewer_state: widgets::TextViewerState::new(config.ui.font_size, config.ui.horizontal_scroll_step),
             font_size: config.ui.font_size,
             layout: None,
+            group_collapse_states: HashMap::new(),
+            layout_path: None,
+            layout_problems: Vec::new(),
+            dir_pattern: "*.log".to_string(),
+            dir_group: true,
             control_panel_height: 250.0,
             max_filename_width: 200.0,  // Initial default, will be recalculated
         }
     }
 
+    /// Compile the highlight rules, keeping the errors of any that don't compile to show
+    fn rebuild_highlight_rules(&mut self, rules: &config::HighlightRulesConfig) {
+        let (highlighter, errors) = highlight::RuleHighlighter::new(rules);
+        for (name, error) in &errors {
+            warn!("Highlight rule '{}' doesn't compile: {}", name, error);
+        }
+        self.rule_highlighter = highlighter;
+        self.highlight_rule_errors = errors;
+    }
+
+    /// Change how many lines the preview keeps for the selected file
+    fn set_preview_follow_lines(&mut self, lines: usize) {
+        self.preview_follow_lines = lines;
+        if let Some(file) = self.preview_selected_file.and_then(|idx| self.files.get(idx)) {
+            self.preview_follow_lines_by_file.insert(file.path.clone(), lines);
+        }
+        self.preview_needs_reload = true;
+    }
+
     fn add_file(&mut self, path: PathBuf) -> Result<(), String> {
         self.add_file_with_group(path, None)
     }
@@ -383,6 +1072,7 @@ impl TailState {
             Ok(mut file) => {
                 info!("Started tailing: {}", file.display_name);
                 file.group_id = group_id;
+                file.start_with_last_lines(self.initial_lines);
                 self.files.push(file);
                 Ok(())
             }
@@ -394,36 +1084,448 @@ impl TailState {
         }
     }
     
-    fn load_layout(&mut self, layout_path: &PathBuf) -> Result<(), String> {
+    /// Tail every file in `dir` whose name matches the glob, optionally under a tree group
+    /// named after the directory. Returns how many files were added.
+    fn add_directory(&mut self, dir: &std::path::Path, pattern: &str, group: bool) -> Result<usize, String> {
+        if !dir.is_dir() {
+            return Err(format!("Not a directory: {}", dir.display()));
+        }
+        let file_pattern = SearchEngine::new().file_pattern(pattern)?;
+        let mut paths = SearchEngine::new().collect_files(dir, &file_pattern, false, None);
+        if paths.is_empty() {
+            return Err(format!("No files matching '{}' in {}", pattern, dir.display()));
+        }
+        paths.sort();
+
+        let group_id = group.then(|| {
+            let name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| dir.display().to_string());
+            self.layout
+                .get_or_insert_with(|| TailLayout::from_files("Tail", &[], false))
+                .add_group(&name, &paths)
+        });
+
+        let mut added = 0;
+        for path in paths {
+            let file_idx = self.files.len();
+            if self.add_file_with_group(path.clone(), group_id.clone()).is_err() {
+                continue;
+            }
+            if let (Some(group_id), Some(layout)) = (&group_id, &mut self.layout) {
+                layout.link_file_to_index(&path, group_id, file_idx);
+            }
+            added += 1;
+        }
+        info!("Tailing {} files from {}", added, dir.display());
+        Ok(added)
+    }
+
+    fn load_layout(&mut self, layout_path: &std::path::Path) -> Result<(), String> {
+        self.layout_problems.clear();
+        self.remember_group_states();
+
         // Load the layout file
-        let mut layout = TailLayout::from_yaml_file(layout_path)?;
-        
+        let layout = TailLayout::from_file(layout_path)?;
+        self.apply_layout(layout, layout_path);
+        Ok(())
+    }
+
+    /// Start tailing the files of an already parsed layout and show its tree
+    fn apply_layout(&mut self, mut layout: TailLayout, layout_path: &std::path::Path) {
         // Apply layout settings
         if let Some(poll_ms) = layout.settings.poll_interval_ms {
             self.poll_interval_ms = poll_ms;
         }
+        if let Some(idle_secs) = layout.settings.idle_timeout_secs {
+            self.idle_timeout = Duration::from_secs(idle_secs);
+        }
         
-        // Add all files from the layout
-        let file_paths = layout.get_all_file_paths();
-        for (path, custom_name, group_id, paused) in file_paths {
-            if let Ok(mut file) = TailedFile::new(path.clone()) {
-                if let Some(name) = custom_name {
-                    file.display_name = name;
+        // Add all files from the layout; relative paths are relative to the layout file
+        let layout_dir = layout_path
+            .parent()
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_default();
+        for (entry, group_id) in layout.get_all_file_entries() {
+            let path = entry.path;
+            let resolved = tail_layout::resolve_entry_path(&path, &layout_dir);
+            let mut file = match TailedFile::new(resolved.clone()) {
+                Ok(file) => file,
+                Err(e) => {
+                    warn!("Skipping layout file {}: {}", resolved.display(), e);
+                    self.layout_problems.push(format!("{}: {}", resolved.display(), e));
+                    continue;
+                }
+            };
+            if let Some(name) = entry.name {
+                file.display_name = name;
+            }
+            file.group_id = Some(group_id.clone());
+            file.paused = entry.paused;  // Apply paused setting from YAML
+            match entry.start_at {
+                None => file.start_with_last_lines(self.initial_lines),
+                Some(tail_layout::StartAt::End) => {}
+                Some(tail_layout::StartAt::Beginning) => file.start_from_beginning(),
+                Some(tail_layout::StartAt::LastLines(lines)) => file.start_with_last_lines(lines),
+            }
+            if let Some(color) = entry.color {
+                file.color = log_parser::LogColorScheme::parse_hex_color(&color);
+                if file.color.is_none() {
+                    self.layout_problems.push(format!("{}: invalid color '{}'", resolved.display(), color));
                 }
-                file.group_id = Some(group_id.clone());
-                file.paused = paused;  // Apply paused setting from YAML
-                
-                // Store the index before pushing
-                let file_idx = self.files.len();
-                self.files.push(file);
-                
-                // Update the layout to link to this file
-                layout.link_file_to_index(&path, &group_id, file_idx);
             }
+            
+            // Store the index before pushing
+            let file_idx = self.files.len();
+            self.files.push(file);
+            
+            // Update the layout to link to this file
+            layout.link_file_to_index(&path, &group_id, file_idx);
         }
         
+        if let Some(states) = self.group_collapse_states.get(layout_path) {
+            layout.restore_collapse_states(states);
+        }
         self.layout = Some(layout);
-        Ok(())
+        self.layout_path = Some(layout_path.to_path_buf());
+    }
+
+    /// Note the current layout's expand/collapse choices so a reload can restore them
+    fn remember_group_states(&mut self) {
+        if let (Some(layout), Some(layout_path)) = (&self.layout, &self.layout_path) {
+            self.group_collapse_states
+                .entry(layout_path.clone())
+                .or_default()
+                .extend(layout.collapse_states());
+        }
+    }
+
+    /// Snapshot of every tailed file's state and totals right now
+    fn status_report(&self) -> status_report::StatusReport {
+        let files = self.files.iter().map(|file| status_report::FileStatus {
+            name: file.display_name.clone(),
+            path: file.path.to_string_lossy().to_string(),
+            size: file.last_size,
+            state: if file.missing {
+                "missing"
+            } else if file.paused || self.paused_all {
+                "paused"
+            } else if file.muted {
+                "muted"
+            } else if file.is_active {
+                "active"
+            } else {
+                "idle"
+            },
+            lines_read: file.total_lines_read,
+            bytes_read: file.total_bytes_read,
+            level_totals: file.level_totals.clone(),
+        }).collect();
+
+        status_report::StatusReport {
+            title: self.layout.as_ref().map_or_else(|| "vis-grep tail status".to_string(), |layout| format!("{} status", layout.name)),
+            generated: std::time::SystemTime::now(),
+            files,
+            lines_received: self.total_lines_received,
+            lines_in_buffer: self.output_buffer.len(),
+            lines_dropped: self.lines_dropped,
+        }
+    }
+
+    /// Snapshot of the tailed files as a layout, for "Save as layout". A loaded layout keeps
+    /// its groups and file order unless regrouping by directory was asked for.
+    fn to_layout(&self, name: &str, group_by_directory: bool) -> TailLayout {
+        if let (Some(loaded), false) = (&self.layout, group_by_directory) {
+            let mut layout = loaded.clone();
+            layout.name = name.to_string();
+            // Included groups are already merged in, and paths are written out resolved
+            layout.include.clear();
+            layout.for_each_file_entry_mut(|entry| {
+                if let Some(file) = entry.tailed_file_idx.and_then(|idx| self.files.get(idx)) {
+                    entry.path = file.path.clone();
+                    entry.paused = file.paused;
+                    entry.color = file.color.map(color_to_hex);
+                }
+            });
+            return layout;
+        }
+
+        let files: Vec<(PathBuf, String, bool, Option<String>)> = self
+            .files
+            .iter()
+            .map(|file| {
+                let color = file.color.map(color_to_hex);
+                (file.path.clone(), file.display_name.clone(), file.paused, color)
+            })
+            .collect();
+        TailLayout::from_files(name, &files, group_by_directory)
+    }
+
+    /// Color for a line's [source] tag: the user's choice for its file, else one derived
+    /// from the name
+    fn file_color(&self, line: &LogLine) -> egui::Color32 {
+        self.files
+            .iter()
+            .find(|f| f.path == line.source_path)
+            .and_then(|f| f.color)
+            .unwrap_or_else(|| get_color_for_file(&line.source_file))
+    }
+
+    /// Drop every buffered output line that came from the given file. Matched by path, as
+    /// files in different directories can share a name.
+    fn clear_file_output(&mut self, path: &std::path::Path) {
+        let before = self.output_buffer.len();
+        self.output_buffer.retain(|line| line.source_path != path);
+        self.output_rows = None;
+        self.expanded_lines.retain(|seq| {
+            self.output_buffer.binary_search_by_key(seq, |line| line.seq).is_ok()
+        });
+        self.buffer_level_counts.clear();
+        for line in &self.output_buffer {
+            *self.buffer_level_counts.entry(line.level).or_default() += 1;
+        }
+        info!("Cleared {} output lines from {}", before - self.output_buffer.len(), path.display());
+    }
+
+    /// Make room in the full output buffer. Normally the oldest line goes, but with
+    /// keep_error_lines set, up to that many errors at the front are stepped over so the
+    /// oldest ordinary line is dropped instead.
+    fn drop_oldest_line(buffer: &mut VecDeque<LogLine>, keep_error_lines: usize) -> Option<LogLine> {
+        let is_error = |line: &LogLine| matches!(line.level, log_parser::LogLevel::Error | log_parser::LogLevel::Fatal);
+        let victim = buffer
+            .iter()
+            .take(keep_error_lines + 1)
+            .position(|line| !is_error(line))
+            .unwrap_or(0);
+        buffer.remove(victim)
+    }
+
+    /// Add a line to the output buffer, trimming it to capacity, and keep the output rows
+    /// in step without going over the rest of the buffer
+    fn push_output_line(&mut self, log_line: LogLine) {
+        let mut rows = self.output_rows.take();
+        if let Some(rows) = &mut rows {
+            self.add_output_row(rows, &log_line);
+        }
+        *self.buffer_level_counts.entry(log_line.level).or_default() += 1;
+        self.output_buffer.push_back(log_line);
+        self.total_lines_received += 1;
+
+        if self.output_buffer.len() > self.max_buffer_lines {
+            let dropped = Self::drop_oldest_line(&mut self.output_buffer, self.keep_error_lines);
+            self.lines_dropped += 1;
+            if let Some(count) = dropped.as_ref().and_then(|line| self.buffer_level_counts.get_mut(&line.level)) {
+                *count = count.saturating_sub(1);
+            }
+            // The dropped line may come from the middle of the buffer, so it is forgotten
+            // by seq rather than by everything before the new front
+            if let Some(line) = &dropped {
+                self.expanded_lines.remove(&line.seq);
+            }
+            if let (Some(current), Some(line)) = (&mut rows, dropped) {
+                if !self.remove_output_row(current, &line) {
+                    rows = None;
+                }
+            }
+        }
+        self.output_rows = rows;
+    }
+
+    /// The filters output rows would be chosen by now
+    fn output_row_filters(&self) -> OutputRowFilters {
+        let apply_tree_filter = self.tree_filter.active && self.tree_filter.apply_to_output;
+        OutputRowFilters {
+            isolated_file: self.isolated_file.clone(),
+            muted_sources: self.files.iter()
+                .filter(|file| file.muted)
+                .map(|file| file.path.clone())
+                .collect(),
+            // Resolved once per source file rather than once per line
+            tree_sources: apply_tree_filter.then(|| {
+                self.files.iter()
+                    .filter(|file| filter::tree::is_file_visible(
+                        &self.tree_filter,
+                        &file.path.to_string_lossy(),
+                        &file.display_name
+                    ))
+                    .map(|file| file.path.clone())
+                    .collect()
+            }),
+            level: (
+                self.log_level_filter.active,
+                self.log_level_filter.minimum_level,
+                self.log_level_filter.show_unknown,
+            ),
+            grep: (self.output_grep.query.clone(), self.output_grep.case_sensitive),
+            collapse_repeats: self.collapse_repeats,
+        }
+    }
+
+    fn shows_output_line(&self, filters: &OutputRowFilters, line: &LogLine) -> bool {
+        filters.isolated_file.as_ref().is_none_or(|path| *path == line.source_path) &&
+            !filters.muted_sources.contains(&line.source_path) &&
+            filters.tree_sources.as_ref().is_none_or(|sources| sources.contains(&line.source_path)) &&
+            self.log_level_filter.should_show_level(line.level) &&
+            self.output_grep.matches_line(&line.content)
+    }
+
+    /// Add a line below the existing rows if it passes the filters, folding it into the
+    /// last row when it repeats that row's line
+    fn add_output_row(&self, rows: &mut OutputRows, line: &LogLine) {
+        if !self.shows_output_line(&rows.filters, line) {
+            return;
+        }
+        if rows.filters.collapse_repeats {
+            if let Some((last_seq, count)) = rows.rows.back_mut() {
+                let repeats = self.find_output_line(*last_seq)
+                    .is_some_and(|last| last.content == line.content && last.source_file == line.source_file);
+                if repeats {
+                    *last_seq = line.seq;
+                    *count += 1;
+                    return;
+                }
+            }
+        }
+        rows.rows.push_back((line.seq, 1));
+    }
+
+    /// Take a line dropped from the buffer out of its row. False if the rows can't follow
+    /// the drop (the newest line of a collapsed run went first) and need rebuilding.
+    fn remove_output_row(&self, rows: &mut OutputRows, line: &LogLine) -> bool {
+        if !self.shows_output_line(&rows.filters, line) {
+            return true;
+        }
+        // A shown line belongs to the first row whose line is at or after it
+        let row = rows.rows.partition_point(|(seq, _)| *seq < line.seq);
+        match rows.rows.get_mut(row) {
+            Some((seq, 1)) if *seq == line.seq => {
+                rows.rows.remove(row);
+                true
+            }
+            Some((seq, count)) if *seq != line.seq && *count > 1 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Bring the output rows up to date, going over the whole buffer only when a filter
+    /// changed or the buffer was cleared
+    fn refresh_output_rows(&mut self) {
+        let filters = self.output_row_filters();
+        if self.output_rows.as_ref().is_some_and(|rows| rows.filters == filters) {
+            return;
+        }
+        let mut rows = OutputRows { filters, rows: VecDeque::new() };
+        for line in &self.output_buffer {
+            self.add_output_row(&mut rows, line);
+        }
+        self.output_rows = Some(rows);
+    }
+
+    /// Show an over-long output line in full, or cut it short again
+    fn toggle_expanded_line(&mut self, seq: u64) {
+        if !self.expanded_lines.remove(&seq) {
+            self.expanded_lines.insert(seq);
+        }
+    }
+
+    /// Look up a line in the output buffer by its seq
+    fn find_output_line(&self, seq: u64) -> Option<&LogLine> {
+        self.output_buffer
+            .binary_search_by_key(&seq, |line| line.seq)
+            .ok()
+            .map(|idx| &self.output_buffer[idx])
+    }
+
+    fn toggle_output_bookmark(&mut self, seq: u64) {
+        if self.output_bookmarks.remove(&seq).is_some() {
+            return;
+        }
+        if let Some(line) = self.find_output_line(seq) {
+            let bookmark = OutputBookmark {
+                timestamp: line.timestamp,
+                source_file: line.source_file.clone(),
+                content: line.content.clone(),
+            };
+            self.output_bookmarks.insert(seq, bookmark);
+        }
+    }
+
+    /// Scroll the output to the next/previous bookmark still in the buffer, wrapping around
+    fn jump_to_output_bookmark(&mut self, forward: bool) {
+        let live: Vec<u64> = self
+            .output_bookmarks
+            .keys()
+            .copied()
+            .filter(|seq| self.find_output_line(*seq).is_some())
+            .collect();
+
+        let target = match (self.last_bookmark_jump, forward) {
+            (Some(current), true) => live.iter().find(|&&seq| seq > current).or(live.first()),
+            (Some(current), false) => live.iter().rev().find(|&&seq| seq < current).or(live.last()),
+            (None, true) => live.first(),
+            (None, false) => live.last(),
+        };
+
+        if let Some(&seq) = target {
+            self.last_bookmark_jump = Some(seq);
+            self.output_scroll_to_seq = Some(seq);
+            self.auto_scroll = false;
+        }
+    }
+
+    /// Scroll the output to the next/previous ERROR or FATAL row, starting from the last
+    /// one jumped to (or the bottom of the view) and wrapping around. Only rows shown
+    /// under the current filters are candidates.
+    fn jump_to_error_line(&mut self, forward: bool) {
+        self.refresh_output_rows();
+        let Some(rows) = &self.output_rows else {
+            return;
+        };
+        let is_error = |line: &LogLine| matches!(line.level, log_parser::LogLevel::Error | log_parser::LogLevel::Fatal);
+        let errors: Vec<u64> = rows
+            .rows
+            .iter()
+            .map(|(seq, _)| *seq)
+            .filter(|seq| self.find_output_line(*seq).is_some_and(is_error))
+            .collect();
+        let current = self
+            .last_error_jump
+            .filter(|seq| self.find_output_line(*seq).is_some())
+            .or(self.output_bottom_seq);
+
+        let target = match (current, forward) {
+            (Some(current), true) => errors.iter().find(|&&seq| seq > current).or(errors.first()),
+            (Some(current), false) => errors.iter().rev().find(|&&seq| seq < current).or(errors.last()),
+            (None, true) => errors.first(),
+            (None, false) => errors.last(),
+        };
+
+        if let Some(&seq) = target {
+            self.last_error_jump = Some(seq);
+            self.output_scroll_to_seq = Some(seq);
+            self.auto_scroll = false;
+        }
+    }
+
+    /// Forget bookmarks and expanded lines whose lines have been dropped from the buffer.
+    /// Lines can go from the middle (keep_error_lines), so each is looked up rather than
+    /// compared to the front.
+    fn prune_output_bookmarks(&mut self) {
+        let dropped: Vec<u64> = self
+            .output_bookmarks
+            .keys()
+            .chain(&self.expanded_lines)
+            .copied()
+            .filter(|seq| self.find_output_line(*seq).is_none())
+            .collect();
+        for seq in dropped {
+            self.output_bookmarks.remove(&seq);
+            self.expanded_lines.remove(&seq);
+        }
     }
 }
 
@@ -442,17 +1544,28 @@ struct VisGrepApp {
     // Shared state (used across modes)
     preview: FilePreview,
     preview_scroll_offset: f32,
+    // Height of one preview line as last rendered, for turning line indices into offsets
+    preview_row_pitch: f32,
     should_scroll_to_match: bool,
     scroll_to_selected_result: bool,
 
     input_handler: InputHandler,
     marks: HashMap<char, usize>,
+    show_help: bool,
+    command_palette: CommandPalette,
 
     config: Config,
     theme: Theme,
 
     // Log level detection
     log_detector: log_parser::LogLevelDetector,
+
+    // Masks sensitive text before display/copy/export
+    redactor: Redactor,
+    sound_alert: alert::SoundAlert,
+
+    // Font size changed by zooming, saved as the default on exit
+    font_zoomed: bool,
 }
 
 impl Default for VisGrepApp {
@@ -467,12 +1580,37 @@ impl VisGrepApp {
         let config = Config::load();
         let theme = config.theme;
 
+        let mut grep_state = GrepState::new(&config);
         let mut tail_state = TailState::new(&config);
+        tail_state.rebuild_highlight_rules(&config.highlight_rules);
+
+        if let Some(session) = &startup_config.session {
+            // An empty path or pattern keeps the default rather than restoring nothing
+            let mut grep = session.grep.clone();
+            if grep.search_path.is_empty() {
+                grep.search_path = grep_state.search_path.clone();
+            }
+            if grep.file_pattern.is_empty() {
+                grep.file_pattern = grep_state.file_pattern.clone();
+            }
+            grep_state.apply_search_params(grep);
+            if let Some(font_size) = session.font_size {
+                tail_state.font_size = font_size;
+            }
+        }
+
+        // Command-line grep inputs win over the session; a query given there runs at once
+        let run_query = startup_config.grep_args.query.is_some();
+        startup_config.grep_args.apply(&mut grep_state);
+
+        tail_state.initial_lines = startup_config.tail_lines;
 
         // Load layout file if provided
         if let Some(layout_path) = &startup_config.tail_layout {
             if let Err(e) = tail_state.load_layout(layout_path) {
+                // The error already names the file that failed, which may be an include
                 eprintln!("Failed to load layout file: {}", e);
+                tail_state.layout_problems.push(e);
             }
         }
 
@@ -483,25 +1621,42 @@ impl VisGrepApp {
             }
         }
 
-        Self {
+        for dir in &startup_config.tail_dirs {
+            if let Err(e) = tail_state.add_directory(&dir.path, &dir.pattern, dir.group) {
+                eprintln!("{}", e);
+            }
+        }
+
+        let mut app = Self {
             mode: startup_config.mode,
 
-            grep_state: GrepState::new(&config),
+            grep_state,
             tail_state,
 
             preview: FilePreview::new(),
             preview_scroll_offset: 0.0,
+            preview_row_pitch: config.ui.font_size + 4.0,
             should_scroll_to_match: false,
             scroll_to_selected_result: false,
 
             input_handler: InputHandler::new(),
             marks: HashMap::new(),
+            show_help: false,
+            command_palette: CommandPalette::new(),
 
+            redactor: Redactor::from_config(&config.redaction),
+            sound_alert: alert::SoundAlert::from_config(&config.sound_alert),
+            font_zoomed: false,
             config,
             theme,
 
             log_detector: log_parser::LogLevelDetector::new(),
+        };
+
+        if run_query && !app.grep_state.search_query.is_empty() {
+            app.perform_search();
         }
+        app
     }
 
     /// Expand ~ to home directory
@@ -527,38 +1682,166 @@ impl VisGrepApp {
         );
         self.grep_state.searching = true;
         self.grep_state.pending_search = false;
-        let start = Instant::now();
-        self.grep_state.results = self.grep_state.search_engine.search(
-            &expanded_path,
-            &self.grep_state.file_pattern,
-            &self.grep_state.search_query,
-            self.grep_state.case_sensitive,
-            self.grep_state.use_regex,
-            self.grep_state.recursive,
-            self.grep_state.file_age_hours,
-        );
-        let duration = start.elapsed();
-        info!(
-            "Search completed in {:.2}s: found {} matches in {} files",
-            duration.as_secs_f64(),
-            self.grep_state
-                .results
-                .iter()
-                .map(|r| r.matches.len())
-                .sum::<usize>(),
-            self.grep_state.results.len()
-        );
-        self.grep_state.searching = false;
+        self.grep_state.search_started = Instant::now();
+        self.grep_state.files_scanned = None;
+        self.grep_state.search_duration = None;
+        self.grep_state.results.clear();
         self.grep_state.selected_result = None;
+        self.grep_state.collapsing_state.clear();
         self.grep_state.last_search_time = Instant::now();
+        self.grep_state.watch_change_time = None;
+        self.grep_state.restore_selection = None;
 
-        // Initialize all headers as expanded for new search
-        self.grep_state.collapsing_state.clear();
-        for i in 0..self.grep_state.results.len() {
-            self.grep_state.collapsing_state.insert(i, true);
+        // Compile once up front; the engine reuses the last compilation if nothing changed
+        let matcher = match self.grep_state.search_engine.compile_query(
+            &self.grep_state.search_query,
+            self.grep_state.case_sensitive,
+            self.grep_state.search_mode,
+        ) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                info!("Invalid search pattern '{}': {}", self.grep_state.search_query, e);
+                self.grep_state.searching = false;
+                self.grep_state.search_receiver = None;
+                return;
+            }
+        };
+        self.grep_state.search_engine.file_pattern_ignore_case = self.grep_state.file_pattern_ignore_case;
+        self.grep_state.search_engine.follow_symlinks = self.grep_state.follow_symlinks;
+        // Compile the glob once for the whole walk; a bad one is reported, not searched
+        let file_pattern = match self.grep_state.search_engine.file_pattern(&self.grep_state.file_pattern) {
+            Ok(file_pattern) => file_pattern,
+            Err(e) => {
+                info!("Invalid file pattern '{}': {}", self.grep_state.file_pattern, e);
+                self.grep_state.file_pattern_error = Some(e);
+                self.grep_state.searching = false;
+                self.grep_state.search_receiver = None;
+                return;
+            }
+        };
+        self.grep_state.file_pattern_error = None;
+        self.grep_state.last_search = Some(self.grep_state.search_params());
+        self.grep_state.executed_pattern =
+            Some(matcher.executed_pattern(self.grep_state.case_sensitive));
+        self.grep_state.search_matcher = Some((matcher.clone(), self.grep_state.search_mode));
+
+        // Run the search on a background thread; results stream back through the
+        // channel and are drained each frame by poll_search_results(). Replacing the
+        // receiver makes a search still running stop at the next result it sends.
+        let (sender, receiver) = mpsc::channel();
+        self.grep_state.search_receiver = Some(receiver);
+
+        let engine = self.grep_state.search_engine.clone();
+        let recursive = self.grep_state.recursive;
+        let file_age_hours = self.grep_state.file_age_hours;
+        let file_names_only = self.grep_state.file_names_only;
+
+        std::thread::spawn(move || {
+            if file_names_only {
+                engine.search_file_names(&expanded_path, &file_pattern, &matcher, recursive, file_age_hours, sender);
+            } else {
+                engine.search(
+                    &expanded_path,
+                    &file_pattern,
+                    &matcher,
+                    recursive,
+                    file_age_hours,
+                    sender,
+                );
+            }
+        });
+    }
+
+    /// Re-run the last search with the parameters it ran with, restoring them in the
+    /// inputs even if they have been edited since
+    fn repeat_last_search(&mut self) {
+        if let Some(params) = self.grep_state.last_search.clone() {
+            self.grep_state.apply_search_params(params);
+            self.mode = AppMode::Grep;
+            self.perform_search();
         }
     }
 
+    /// Move any results the background search has produced into grep_state
+    fn poll_search_results(&mut self) {
+        let Some(receiver) = &self.grep_state.search_receiver else {
+            return;
+        };
+
+        loop {
+            match receiver.try_recv() {
+                Ok(SearchEvent::FilesScanned(count)) => {
+                    self.grep_state.files_scanned = Some(count);
+                }
+                Ok(SearchEvent::Result(result)) => {
+                    // New headers start expanded
+                    let idx = self.grep_state.results.len();
+                    self.grep_state.collapsing_state.insert(idx, true);
+
+                    // Re-select the previously selected match if it's still there
+                    if let Some((path, line)) = &self.grep_state.restore_selection {
+                        if result.file_path == *path {
+                            if let Some(match_idx) = result.matches.iter().position(|m| m.line_number == *line) {
+                                self.grep_state.selected_result = Some(idx * 10000 + match_idx);
+                            }
+                            self.grep_state.restore_selection = None;
+                        }
+                    }
+
+                    self.grep_state.results.push(result);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    let duration = self.grep_state.search_started.elapsed();
+                    self.grep_state.search_duration = Some(duration);
+                    info!(
+                        "Search completed in {:.2}s: found {} matches in {} files",
+                        duration.as_secs_f64(),
+                        self.grep_state
+                            .results
+                            .iter()
+                            .map(|r| r.matches.len())
+                            .sum::<usize>(),
+                        self.grep_state.results.len()
+                    );
+                    self.grep_state.searching = false;
+                    self.grep_state.search_receiver = None;
+                    self.grep_state.restore_selection = None;
+                    if self.grep_state.search_mode == SearchMode::Fuzzy {
+                        self.rank_results_by_score();
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Order result files best fuzzy score first, keeping the selection and the
+    /// expanded/collapsed state with their files
+    fn rank_results_by_score(&mut self) {
+        let selected = self.grep_state.selected_result.map(|id| (id / 10000, id % 10000));
+        let mut order: Vec<usize> = (0..self.grep_state.results.len()).collect();
+        order.sort_by_key(|&idx| std::cmp::Reverse(self.grep_state.results[idx].best_score));
+
+        let mut old_results: Vec<Option<SearchResult>> =
+            self.grep_state.results.drain(..).map(Some).collect();
+        let mut collapsing_state = HashMap::new();
+        for (new_idx, &old_idx) in order.iter().enumerate() {
+            if let Some(result) = old_results[old_idx].take() {
+                self.grep_state.results.push(result);
+            }
+            if let Some(&open) = self.grep_state.collapsing_state.get(&old_idx) {
+                collapsing_state.insert(new_idx, open);
+            }
+            if let Some((file_idx, match_idx)) = selected {
+                if file_idx == old_idx {
+                    self.grep_state.selected_result = Some(new_idx * 10000 + match_idx);
+                }
+            }
+        }
+        self.grep_state.collapsing_state = collapsing_state;
+    }
+
     fn poll_tail_files(&mut self) {
         if self.tail_state.paused_all {
             return;
@@ -571,11 +1854,15 @@ impl VisGrepApp {
         if elapsed < std::time::Duration::from_millis(self.tail_state.poll_interval_ms) {
             return;
         }
+        let idle_timeout = self.tail_state.idle_timeout;
 
         self.tail_state.last_poll_time = now;
         
         // Collect activity changes to apply after the loop
         let mut activity_changes: Vec<(String, bool)> = Vec::new();
+        let mut alert_hit = false;
+        // Lines read this poll, added to the output buffer once the files are released
+        let mut new_output = Vec::new();
 
         // Poll each file
         for (file_idx, file) in self.tail_state.files.iter_mut().enumerate() {
@@ -606,24 +1893,25 @@ impl VisGrepApp {
                             // Detect and count log level for this line
                             let level = self.log_detector.detect(line);
                             *file.level_counts_since_last_read.entry(level).or_insert(0) += 1;
+                            *file.level_totals.entry(level).or_insert(0) += 1;
+                            if self.sound_alert.enabled && self.sound_alert.matches(line, level) {
+                                alert_hit = true;
+                            }
 
                             let log_line = LogLine {
+                                seq: self.tail_state.next_line_seq,
                                 timestamp: now,
                                 source_file: file.display_name.clone(),
+                                source_path: file.path.clone(),
                                 line_number: file.total_lines_read,
                                 content: line.clone(),
+                                level,
+                                is_json: widgets::json_view::is_json_line(line),
                             };
 
-                            self.tail_state.output_buffer.push_back(log_line);
-                            self.tail_state.total_lines_received += 1;
-
-                            // Trim buffer if over capacity
-                            if self.tail_state.output_buffer.len()
-                                > self.tail_state.max_buffer_lines
-                            {
-                                self.tail_state.output_buffer.pop_front();
-                                self.tail_state.lines_dropped += 1;
-                            }
+                            self.tail_state.level_histogram.record(now, log_line.seq, level);
+                            self.tail_state.next_line_seq += 1;
+                            new_output.push(log_line);
                         }
 
                         // If preview is in Following mode and showing this file, reload it
@@ -635,10 +1923,8 @@ impl VisGrepApp {
                             }
                         }
                     } else {
-                        // Mark as idle after 2 seconds
-                        if now.duration_since(file.last_activity)
-                            > std::time::Duration::from_secs(2)
-                        {
+                        // Mark as idle once the idle timeout passes without new lines
+                        if now.duration_since(file.last_activity) > idle_timeout {
                             if file.is_active {
                                 file.is_active = false;
                                 file.lines_since_last_read = 0;
@@ -658,6 +1944,14 @@ impl VisGrepApp {
             }
         }
         
+        for log_line in new_output {
+            self.tail_state.push_output_line(log_line);
+        }
+
+        if alert_hit {
+            self.sound_alert.trigger(now);
+        }
+
         // Apply activity changes after the loop
         for (group_id, active) in activity_changes {
             self.propagate_activity_to_group(&group_id, active);
@@ -679,11 +1973,29 @@ impl VisGrepApp {
         if let Some(file_idx) = self.tail_state.preview_selected_file {
             if file_idx < self.tail_state.files.len() {
                 let file = &self.tail_state.files[file_idx];
+                // Streams have no file to read back, so the preview shows what has arrived
+                let content = match &file.stream {
+                    Some(stream) => Ok((0, stream.recent_lines(match self.tail_state.preview_mode {
+                        PreviewMode::Following => self.tail_state.preview_follow_lines,
+                        PreviewMode::Paused => usize::MAX,
+                    }))),
+                    None => self.read_file_for_preview(&file.path),
+                };
 
-                match self.read_file_for_preview(&file.path) {
-                    Ok(lines) => {
-                        self.tail_state.preview_content = lines;
+                match content {
+                    Ok((first_line, lines)) => {
+                        let old_content = std::mem::replace(&mut self.tail_state.preview_content, lines);
+                        // Keep marks, folds and pins on the same file lines as the window over the file moves
+                        let old_first_line = std::mem::replace(&mut self.tail_state.preview_first_line, first_line);
+                        self.tail_state.text_viewer_state.shift_lines(old_first_line, first_line);
+                        self.tail_state.text_viewer_state.update_line_formats(
+                            &old_content,
+                            &self.tail_state.preview_content,
+                            old_first_line,
+                            first_line,
+                        );
                         self.tail_state.preview_needs_reload = false;
+                        self.apply_pending_marks();
                         
                         // Update filter matches if filter is active
                         if self.tail_state.preview_filter.active {
@@ -702,7 +2014,8 @@ impl VisGrepApp {
         }
     }
 
-    fn read_file_for_preview(&self, path: &PathBuf) -> std::io::Result<Vec<String>> {
+    /// The preview lines for `path` and the 0-indexed file line the first of them is
+    fn read_file_for_preview(&self, path: &PathBuf) -> std::io::Result<(usize, Vec<String>)> {
         use std::io::{BufRead, BufReader};
 
         if self.tail_state.preview_mode == PreviewMode::Following {
@@ -712,22 +2025,24 @@ impl VisGrepApp {
 
             let mut lines: VecDeque<String> =
                 VecDeque::with_capacity(self.tail_state.preview_follow_lines);
+            let mut first_line = 0;
 
             for line in reader.lines() {
                 if let Ok(line_str) = line {
                     if lines.len() >= self.tail_state.preview_follow_lines {
                         lines.pop_front();
+                        first_line += 1;
                     }
                     lines.push_back(line_str);
                 }
             }
 
-            Ok(lines.into_iter().collect())
+            Ok((first_line, lines.into_iter().collect()))
         } else {
             // Read entire file for paused mode
             let file = File::open(path)?;
             let reader = BufReader::new(file);
-            reader.lines().collect()
+            Ok((0, reader.lines().collect::<std::io::Result<_>>()?))
         }
     }
 }
@@ -737,8 +2052,30 @@ impl eframe::App for VisGrepApp {
         // Apply theme
         self.theme.apply(ctx);
         
-        // Process keyboard input and handle navigation commands
-        if let Some(command) = self.input_handler.process_input(ctx) {
+        // ? toggles the keyboard help overlay, Escape closes it
+        if !ctx.wants_keyboard_input() {
+            ctx.input(|i| {
+                if i.key_pressed(egui::Key::Questionmark) {
+                    self.show_help = !self.show_help;
+                } else if self.show_help && i.key_pressed(egui::Key::Escape) {
+                    self.show_help = false;
+                }
+            });
+        }
+        widgets::help_overlay::show_help_overlay(ctx, &mut self.show_help);
+
+        // Ctrl+P opens the command palette
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::P)) {
+            self.command_palette.toggle();
+        }
+        self.handle_zoom(ctx);
+        if self.command_palette.open {
+            let entries = self.palette_entries();
+            if let Some(action) = self.command_palette.show(ctx, &entries) {
+                self.execute_palette_action(action);
+            }
+        } else if let Some(command) = self.input_handler.process_input(ctx) {
+            // Process keyboard input and handle navigation commands
             self.handle_navigation_command(command);
         }
 
@@ -834,6 +2171,9 @@ impl eframe::App for VisGrepApp {
                                     self.render_tail_preview(ui_right);
                                 });
                         });
+                    if self.tail_state.show_highlight_rules {
+                        self.render_highlight_rules_window(ui.ctx());
+                    }
                 },
                 AppMode::Test => {
                     Splitter::new("test_split", SplitterAxis::Vertical)
@@ -852,9 +2192,12 @@ impl eframe::App for VisGrepApp {
             }
         });
 
+        // Drain streamed search results (a search keeps running across mode switches)
+        self.poll_search_results();
+
         // Mode-specific background tasks
         match self.mode {
-            AppMode::Grep => self.handle_grep_mode_background_tasks(),
+            AppMode::Grep => self.handle_grep_mode_background_tasks(ctx),
             AppMode::Tail => {
                 // Poll files for updates
                 self.poll_tail_files();
@@ -871,28 +2214,110 @@ impl eframe::App for VisGrepApp {
         if self.mode == AppMode::Tail && !self.tail_state.paused_all {
             ctx.request_repaint();
         }
+
+        // Keep repainting while a search is streaming in results
+        if self.grep_state.searching {
+            ctx.request_repaint();
+        }
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         // egui will automatically save persisted memory when this is called
         log::info!("Application exiting, persisted data will be saved");
+        if self.font_zoomed {
+            self.config.ui.font_size = self.current_font_size();
+            if let Err(e) = self.config.save() {
+                log::error!("Failed to save font size: {}", e);
+            }
+        }
+        if self.config.ui.restore_session {
+            if let Err(e) = self.current_session().save() {
+                log::error!("Failed to save session: {}", e);
+            }
+        }
     }
 }
 
 impl VisGrepApp {
+    fn current_font_size(&self) -> f32 {
+        match self.mode {
+            AppMode::Tail => self.tail_state.font_size,
+            AppMode::Grep | AppMode::Test => self.grep_state.font_size,
+        }
+    }
+
+    /// Ctrl+scroll and Ctrl+= / Ctrl+- zoom the current mode's font
+    fn handle_zoom(&mut self, ctx: &egui::Context) {
+        let (factor, step) = ctx.input_mut(|i| {
+            let step = if i.consume_key(egui::Modifiers::COMMAND, egui::Key::Equals)
+                || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Plus)
+            {
+                1.0
+            } else if i.consume_key(egui::Modifiers::COMMAND, egui::Key::Minus) {
+                -1.0
+            } else {
+                0.0
+            };
+            (i.zoom_delta(), step)
+        });
+        if factor == 1.0 && step == 0.0 {
+            return;
+        }
+
+        let size = (self.current_font_size() * factor + step).clamp(*FONT_SIZE_RANGE.start(), *FONT_SIZE_RANGE.end());
+        let size = (size * 2.0).round() / 2.0;
+        match self.mode {
+            AppMode::Tail => self.tail_state.font_size = size,
+            AppMode::Grep | AppMode::Test => self.grep_state.font_size = size,
+        }
+        self.font_zoomed = true;
+    }
+
+    /// Snapshot of what's open, for restore_session
+    fn current_session(&self) -> session::Session {
+        // Files from the layout are reopened through it; only the extras are listed
+        let tail_layout = self.tail_state.layout_path.clone();
+        let tail_files = self.tail_state.files
+            .iter()
+            .filter(|file| tail_layout.is_none() || file.group_id.is_none())
+            .map(|file| file.path.clone())
+            .collect();
+
+        session::Session {
+            mode: match self.mode {
+                AppMode::Tail => session::SessionMode::Tail,
+                AppMode::Grep | AppMode::Test => session::SessionMode::Grep,
+            },
+            tail_layout,
+            tail_files,
+            grep: self.grep_state.search_params(),
+            font_size: Some(self.tail_state.font_size),
+        }
+    }
+
     fn select_match(&mut self, result_id: usize, file_path: &std::path::Path, line_number: usize) {
         self.grep_state.selected_result = Some(result_id);
         self.preview.load_file(file_path, line_number);
+        info!("Match selected: file line {}", line_number);
+        self.scroll_preview_to_target();
+    }
+
+    /// Scroll the preview so the target line (or its hex row) sits near the top of the viewport
+    fn scroll_preview_to_target(&mut self) {
+        let target = if self.hex_view_shown() {
+            self.preview.hex_target_row
+        } else {
+            self.preview.target_line_in_preview
+        };
 
         // Calculate scroll offset to center the target line in viewport
-        if let Some(target_line_idx) = self.preview.target_line_in_preview {
-            let line_height = 14.0; // egui code editor default line height
+        if let Some(target_line_idx) = target {
             let lines_above_target = 10;
             let scroll_to_line = target_line_idx.saturating_sub(lines_above_target);
-            self.preview_scroll_offset = scroll_to_line as f32 * line_height;
+            self.preview_scroll_offset = scroll_to_line as f32 * self.preview_row_pitch;
             self.should_scroll_to_match = true; // Flag that we want to scroll
-            info!("Match selected: file line {}, preview line index {}, scroll to line {} (show {} lines above), offset {}px",
-                  line_number, target_line_idx, scroll_to_line, lines_above_target, self.preview_scroll_offset);
+            info!("Preview line index {}, scroll to line {} (show {} lines above), offset {}px",
+                  target_line_idx, scroll_to_line, lines_above_target, self.preview_scroll_offset);
         }
     }
 
@@ -947,8 +2372,46 @@ impl VisGrepApp {
         }
     }
 
+    /// Names-only results have no matches to step through, so n/p and the file keys step
+    /// through the files instead, wrapping at either end
+    fn select_adjacent_file_name(&mut self, forward: bool) {
+        let count = self.grep_state.results.len();
+        if count == 0 {
+            return;
+        }
+        let file_idx = match self.grep_state.selected_result {
+            Some(id) if forward => (id / 10000 + 1) % count,
+            Some(id) => (id / 10000 % count + count - 1) % count,
+            None if forward => 0,
+            None => count - 1,
+        };
+        let file_path = self.grep_state.results[file_idx].file_path.clone();
+        self.select_match_with_keyboard(file_idx * 10000, &file_path, 1);
+    }
+
     fn handle_navigation_command(&mut self, command: NavigationCommand) {
+        let names_only = self.grep_state.last_search.as_ref().is_some_and(|search| search.file_names_only);
         match command {
+            NavigationCommand::NextMatch | NavigationCommand::NextFile if names_only => {
+                self.select_adjacent_file_name(true)
+            }
+            NavigationCommand::PreviousMatch | NavigationCommand::PreviousFile if names_only => {
+                self.select_adjacent_file_name(false)
+            }
+            NavigationCommand::NextMatchWithCount(count) | NavigationCommand::NextFileWithCount(count)
+                if names_only =>
+            {
+                for _ in 0..count {
+                    self.select_adjacent_file_name(true);
+                }
+            }
+            NavigationCommand::PreviousMatchWithCount(count) | NavigationCommand::PreviousFileWithCount(count)
+                if names_only =>
+            {
+                for _ in 0..count {
+                    self.select_adjacent_file_name(false);
+                }
+            }
             NavigationCommand::NextMatch => self.select_next_match(),
             NavigationCommand::PreviousMatch => self.select_previous_match(),
             NavigationCommand::FirstMatch => self.select_first_match(),
@@ -978,9 +2441,25 @@ impl VisGrepApp {
                 }
             }
             NavigationCommand::YankMatchedLine => self.yank_matched_line(),
+            NavigationCommand::YankLocation => self.yank_location(),
             NavigationCommand::OpenInExplorer => self.open_in_explorer(),
             NavigationCommand::SetMark(ch) => self.set_mark(ch),
             NavigationCommand::GotoMark(ch) => self.goto_mark(ch),
+            NavigationCommand::ToggleJsonView => self.toggle_json_view(),
+            NavigationCommand::CenterView => self.scroll_preview_to_target(),
+            // Folding is only in the tail preview
+            NavigationCommand::ToggleFold => {}
+        }
+    }
+
+    fn toggle_json_view(&mut self) {
+        if self.grep_state.json_view.is_some() {
+            self.grep_state.json_view = None;
+        } else if let Some(matched_line) = &self.preview.matched_line_text {
+            self.grep_state.json_view = widgets::json_view::pretty_print_json(matched_line);
+            if self.grep_state.json_view.is_none() {
+                info!("Matched line is not JSON");
+            }
         }
     }
 
@@ -1025,7 +2504,7 @@ impl VisGrepApp {
             return;
         }
         let file_path = &self.grep_state.results[current_file_idx].file_path;
-        self.open_file_in_editor(file_path);
+        self.open_file_in_editor(file_path, None);
     }
     
     fn open_in_explorer(&self) {
@@ -1047,7 +2526,7 @@ impl VisGrepApp {
     }
     
     /// Open a file in the configured editor
-    fn open_file_in_editor(&self, file_path: &std::path::Path) {
+    fn open_file_in_editor(&self, file_path: &std::path::Path, line: Option<usize>) {
         // Try config first, then environment variables
         let editor_config = if let Some(ref editor) = self.config.editor {
             Some((editor.command.clone(), editor.args.clone()))
@@ -1069,13 +2548,13 @@ impl VisGrepApp {
         };
         
         if let Some((command, args)) = editor_config {
-            info!("Opening file in editor: {} {:?} {:?}", command, args, file_path);
+            info!("Opening file in editor: {} {:?} {:?} line {:?}", command, args, file_path, line);
             
             let mut cmd = std::process::Command::new(&command);
             for arg in &args {
                 cmd.arg(arg);
             }
-            cmd.arg(file_path);
+            cmd.args(Self::editor_file_args(&command, file_path, line));
             
             match cmd.spawn() {
                 Ok(_) => {
@@ -1084,17 +2563,17 @@ impl VisGrepApp {
                 Err(e) => {
                     info!("Failed to open editor: {}", e);
                     // Fall back to trying common editors
-                    self.try_fallback_editors(file_path);
+                    self.try_fallback_editors(file_path, line);
                 }
             }
         } else {
             // No editor configured, try common ones
-            self.try_fallback_editors(file_path);
+            self.try_fallback_editors(file_path, line);
         }
     }
     
     /// Try common editors as fallback
-    fn try_fallback_editors(&self, file_path: &std::path::Path) {
+    fn try_fallback_editors(&self, file_path: &std::path::Path, line: Option<usize>) {
         #[cfg(target_os = "windows")]
         let editors = vec!["notepad++.exe", "notepad.exe"];
         
@@ -1103,7 +2582,7 @@ impl VisGrepApp {
         
         for editor in editors {
             if std::process::Command::new(editor)
-                .arg(file_path)
+                .args(Self::editor_file_args(editor, file_path, line))
                 .spawn()
                 .is_ok()
             {
@@ -1114,6 +2593,29 @@ impl VisGrepApp {
         
         info!("Could not find any editor to open file");
     }
+
+    /// Build the file argument(s) for an editor, using its syntax for jumping to a line
+    fn editor_file_args(command: &str, file_path: &std::path::Path, line: Option<usize>) -> Vec<String> {
+        let path = file_path.to_string_lossy().to_string();
+        let Some(line) = line else {
+            return vec![path];
+        };
+
+        let editor_name = std::path::Path::new(command)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match editor_name.as_str() {
+            "code" | "code-insiders" | "codium" => vec!["--goto".to_string(), format!("{}:{}", path, line)],
+            "subl" | "sublime_text" | "zed" | "hx" | "helix" => vec![format!("{}:{}", path, line)],
+            "kate" => vec!["--line".to_string(), line.to_string(), path],
+            "notepad++" => vec![format!("-n{}", line), path],
+            "notepad" => vec![path],
+            // vim, nvim, nano, emacs, gedit, micro, ...
+            _ => vec![format!("+{}", line), path],
+        }
+    }
     
     /// Open a file path in the system file explorer (reusable static method)
     fn open_path_in_explorer(file_path: &std::path::Path) {
@@ -1202,10 +2704,22 @@ impl VisGrepApp {
         }
     }
 
+    /// Put text on the clipboard, logging `what` was copied
+    fn copy_to_clipboard(text: &str, what: &str) {
+        match Clipboard::new() {
+            Ok(mut clipboard) => match clipboard.set_text(text.to_string()) {
+                Ok(_) => info!("Copied {} ({} chars) to clipboard", what, text.len()),
+                Err(e) => log::error!("Failed to copy {}: {}", what, e),
+            },
+            Err(e) => log::error!("Failed to access clipboard: {}", e),
+        }
+    }
+
     fn yank_matched_line(&mut self) {
         if let Some(matched_line) = &self.preview.matched_line_text {
+            let matched_line = self.redactor.redact(matched_line);
             match Clipboard::new() {
-                Ok(mut clipboard) => match clipboard.set_text(matched_line.clone()) {
+                Ok(mut clipboard) => match clipboard.set_text(matched_line.to_string()) {
                     Ok(_) => info!(
                         "Yanked matched line ({} chars) to clipboard",
                         matched_line.len()
@@ -1219,6 +2733,27 @@ impl VisGrepApp {
         }
     }
 
+    /// Copy the selected match's location, formatted with `ui.location_format`
+    fn yank_location(&mut self) {
+        let Some(line_number) = self.selected_match_line() else {
+            info!("No match selected to yank location of");
+            return;
+        };
+        let Some(path) = self.previewed_file() else {
+            return;
+        };
+        let path = path.canonicalize().unwrap_or(path);
+        let location = links::format_location(&self.config.ui.location_format, &path, line_number);
+
+        match Clipboard::new() {
+            Ok(mut clipboard) => match clipboard.set_text(location.clone()) {
+                Ok(_) => info!("Yanked location {} to clipboard", location),
+                Err(e) => info!("Failed to yank location to clipboard: {}", e),
+            },
+            Err(e) => info!("Failed to access clipboard: {}", e),
+        }
+    }
+
     fn select_first_match(&mut self) {
         if self.grep_state.results.is_empty() {
             return;
@@ -1408,96 +2943,302 @@ impl VisGrepApp {
         let should_scroll = self.scroll_to_selected_result;
         self.scroll_to_selected_result = false; // Reset flag
 
-        for (file_idx, result) in self.grep_state.results.iter().enumerate() {
-            let file_name = result
-                .file_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
-
-            // Apply filename filter
-            if !filter.is_empty() && !file_name.to_lowercase().contains(&filter) {
-                continue;
+        // Files passing the filename filter
+        let visible: Vec<usize> = self
+            .grep_state
+            .results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| {
+                filter.is_empty() || Self::result_file_name(result).to_lowercase().contains(&filter)
+            })
+            .map(|(file_idx, _)| file_idx)
+            .collect();
+
+        let names_only = self.grep_state.last_search.as_ref().is_some_and(|search| search.file_names_only);
+        if names_only {
+            self.render_file_name_results(ui, &visible, should_scroll, &mut clicked_match);
+        } else if self.grep_state.unique_lines {
+            self.render_unique_lines(ui, &visible, should_scroll, &mut clicked_match);
+        } else if self.grep_state.group_by_directory {
+            let dirs_open = self.grep_state.dir_headers_open.take();
+            for (dir, file_indices) in search::group_by_directory(&self.grep_state.results, &visible) {
+                let unit = self.config.ui.match_count_unit;
+                let match_count: usize = file_indices
+                    .iter()
+                    .map(|&file_idx| self.grep_state.results[file_idx].count(unit))
+                    .sum();
+                egui::CollapsingHeader::new(
+                    egui::RichText::new(format!(
+                        "📁 {} ({} {} in {} files)",
+                        dir.display(),
+                        match_count,
+                        unit.label(),
+                        file_indices.len()
+                    ))
+                    .strong(),
+                )
+                .id_salt(("result_dir", &dir))
+                .default_open(true)
+                .open(dirs_open)
+                .show(ui, |ui| {
+                    for file_idx in file_indices {
+                        self.render_result_file(ui, file_idx, should_scroll, &mut clicked_match);
+                    }
+                });
             }
+        } else {
+            for file_idx in visible {
+                self.render_result_file(ui, file_idx, should_scroll, &mut clicked_match);
+            }
+        }
 
-            // Get current open state, default to true if not set
-            let is_open = *self
-                .grep_state
-                .collapsing_state
-                .get(&file_idx)
-                .unwrap_or(&true);
-
-            let header_id = ui.make_persistent_id(format!("header_{}", file_idx));
-
-            // Load the state from egui's storage (respects user clicks)
-            let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(
-                ui.ctx(),
-                header_id,
-                is_open,
-            );
+        // Handle match selection after iteration is complete
+        if let Some((result_id, file_path, line_number)) = clicked_match {
+            self.select_match(result_id, &file_path, line_number);
+        }
+    }
 
-            // Only force the state if our tracked state differs from egui's state
-            // This allows user clicks to work, but also allows Expand/Collapse All buttons to work
-            if state.is_open() != is_open {
-                state.set_open(is_open);
-                state.store(ui.ctx());
+    /// Results of a file-name search: one row per file, which previews it from the top
+    fn render_file_name_results(
+        &mut self,
+        ui: &mut egui::Ui,
+        visible: &[usize],
+        should_scroll: bool,
+        clicked_match: &mut Option<(usize, std::path::PathBuf, usize)>,
+    ) {
+        for &file_idx in visible {
+            let path = &self.grep_state.results[file_idx].file_path;
+            let result_id = file_idx * 10000;
+            let is_selected = self.grep_state.selected_result == Some(result_id);
+            let response = ui.selectable_label(is_selected, format!("📄 {}", path.display()));
+            if response.clicked() {
+                *clicked_match = Some((result_id, path.clone(), 1));
             }
+            if response.double_clicked() {
+                self.open_file_in_editor(path, None);
+            }
+            if is_selected && should_scroll {
+                response.scroll_to_me(Some(egui::Align::Center));
+            }
+        }
+    }
 
-            state
-                .show_header(ui, |ui| {
-                    ui.label(format!("{} ({} matches)", file_name, result.matches.len()));
-                })
-                .body(|ui| {
-                    for (match_idx, m) in result.matches.iter().enumerate() {
+    /// One row per distinct matched line with its occurrence count; expanding a row
+    /// lists where it occurred, and clicking an occurrence selects that match
+    fn render_unique_lines(
+        &mut self,
+        ui: &mut egui::Ui,
+        visible: &[usize],
+        should_scroll: bool,
+        clicked_match: &mut Option<(usize, std::path::PathBuf, usize)>,
+    ) {
+        let results = &self.grep_state.results;
+        for line in search::unique_lines(results, visible) {
+            let text = self.redactor.redact(&line.text);
+            egui::CollapsingHeader::new(format!("×{}  {}", line.occurrences.len(), text))
+                .id_salt(("unique_line", &line.text))
+                .default_open(false)
+                .show(ui, |ui| {
+                    for &(file_idx, match_idx) in &line.occurrences {
+                        let result = &results[file_idx];
+                        let line_number = result.matches[match_idx].line_number;
                         let result_id = file_idx * 10000 + match_idx;
                         let is_selected = self.grep_state.selected_result == Some(result_id);
-
-                        let label = format!("  Line {}: {}", m.line_number, m.line_text.trim());
-
-                        let response = ui.selectable_label(is_selected, label);
-
+                        let response = ui.selectable_label(
+                            is_selected,
+                            format!("  {}:{}", result.file_path.display(), line_number),
+                        );
                         if response.clicked() {
-                            clicked_match =
-                                Some((result_id, result.file_path.clone(), m.line_number));
+                            *clicked_match = Some((result_id, result.file_path.clone(), line_number));
+                        }
+                        if response.double_clicked() {
+                            self.open_file_in_editor(&result.file_path, Some(line_number));
                         }
-
-                        // Scroll to this item if it's selected and we should scroll
                         if is_selected && should_scroll {
                             response.scroll_to_me(Some(egui::Align::Center));
                         }
                     }
                 });
+        }
+    }
 
-            // Re-load state to get updated open/close status after user interaction
-            let updated_state = egui::collapsing_header::CollapsingState::load_with_default_open(
-                ui.ctx(),
-                header_id,
-                is_open,
-            );
-            self.grep_state
-                .collapsing_state
-                .insert(file_idx, updated_state.is_open());
+    fn result_file_name(result: &SearchResult) -> &str {
+        result
+            .file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+    }
+
+    /// One file's collapsing header and its match rows
+    fn render_result_file(
+        &mut self,
+        ui: &mut egui::Ui,
+        file_idx: usize,
+        should_scroll: bool,
+        clicked_match: &mut Option<(usize, std::path::PathBuf, usize)>,
+    ) {
+        let result = &self.grep_state.results[file_idx];
+        let file_name = Self::result_file_name(result);
+
+        // Get current open state, default to true if not set
+        let is_open = *self
+            .grep_state
+            .collapsing_state
+            .get(&file_idx)
+            .unwrap_or(&true);
+
+        let header_id = ui.make_persistent_id(format!("header_{}", file_idx));
+
+        // Load the state from egui's storage (respects user clicks)
+        let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(
+            ui.ctx(),
+            header_id,
+            is_open,
+        );
+
+        // Only force the state if our tracked state differs from egui's state
+        // This allows user clicks to work, but also allows Expand/Collapse All buttons to work
+        if state.is_open() != is_open {
+            state.set_open(is_open);
+            state.store(ui.ctx());
         }
 
-        // Handle match selection after iteration is complete
-        if let Some((result_id, file_path, line_number)) = clicked_match {
-            self.select_match(result_id, &file_path, line_number);
+        state
+            .show_header(ui, |ui| {
+                let unit = self.config.ui.match_count_unit;
+                ui.label(format!("{} ({} {})", file_name, result.count(unit), unit.label()));
+            })
+            .body(|ui| {
+                let mut handle_row = |response: &egui::Response, result_id: usize, line_number: usize| {
+                    if response.clicked() {
+                        *clicked_match = Some((result_id, result.file_path.clone(), line_number));
+                    }
+                    // Double-click opens the match in the editor; the first click has already selected it
+                    if response.double_clicked() {
+                        self.open_file_in_editor(&result.file_path, Some(line_number));
+                    }
+
+                    // Scroll to this item if it's selected and we should scroll
+                    if self.grep_state.selected_result == Some(result_id) && should_scroll {
+                        response.scroll_to_me(Some(egui::Align::Center));
+                    }
+                };
+
+                // A query with capture groups shows them as a table instead of whole lines
+                let group_count = result.matches.iter().map(|m| m.captures.len()).max().unwrap_or(0);
+                if group_count > 0 {
+                    egui::Grid::new(("captures", file_idx))
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new("Line").strong());
+                            for group in 1..=group_count {
+                                ui.label(egui::RichText::new(format!("${}", group)).strong());
+                            }
+                            ui.end_row();
+
+                            for (match_idx, m) in result.matches.iter().enumerate() {
+                                let result_id = file_idx * 10000 + match_idx;
+                                let is_selected = self.grep_state.selected_result == Some(result_id);
+                                let response = ui.selectable_label(is_selected, format!("{}", m.line_number))
+                                    .on_hover_text(self.redactor.redact(m.line_text.trim()));
+                                for capture in &m.captures {
+                                    ui.monospace(self.redactor.redact(capture));
+                                }
+                                ui.end_row();
+                                handle_row(&response, result_id, m.line_number);
+                            }
+                        });
+                    return;
+                }
+
+                for (match_idx, m) in result.matches.iter().enumerate() {
+                    let result_id = file_idx * 10000 + match_idx;
+                    let is_selected = self.grep_state.selected_result == Some(result_id);
+
+                    // Redaction shifts the match columns, so redacted lines are shown without the highlight
+                    let redacted = self.redactor.redact(m.line_text.trim());
+                    let job = match redacted {
+                        std::borrow::Cow::Borrowed(_) => Self::match_row_job(ui, m),
+                        std::borrow::Cow::Owned(_) => None,
+                    };
+                    let response = match job {
+                        Some(job) => ui.selectable_label(is_selected, job),
+                        None => ui.selectable_label(is_selected, format!("  Line {}: {}", m.line_number, redacted)),
+                    };
+                    handle_row(&response, result_id, m.line_number);
+                }
+            });
+
+        // Re-load state to get updated open/close status after user interaction
+        let updated_state = egui::collapsing_header::CollapsingState::load_with_default_open(
+            ui.ctx(),
+            header_id,
+            is_open,
+        );
+        self.grep_state
+            .collapsing_state
+            .insert(file_idx, updated_state.is_open());
+    }
+
+    /// Result row text with the matched range highlighted, or None if the stored
+    /// columns don't fall on the trimmed line
+    fn match_row_job(ui: &egui::Ui, m: &search::MatchInfo) -> Option<egui::text::LayoutJob> {
+        let trimmed = m.line_text.trim();
+        let lead = m.line_text.len() - m.line_text.trim_start().len();
+        let start = m.column_start.checked_sub(lead)?;
+        let end = m.column_end.checked_sub(lead)?.min(trimmed.len());
+        if start >= end || !trimmed.is_char_boundary(start) || !trimmed.is_char_boundary(end) {
+            return None;
         }
+
+        let font_id = egui::TextStyle::Button.resolve(ui.style());
+        let plain = egui::TextFormat::simple(font_id.clone(), ui.visuals().text_color());
+        let highlighted = egui::TextFormat {
+            color: egui::Color32::BLACK,
+            background: egui::Color32::from_rgb(255, 200, 100),
+            ..egui::TextFormat::simple(font_id, ui.visuals().text_color())
+        };
+
+        let mut job = egui::text::LayoutJob::default();
+        job.append(&format!("  Line {}: {}", m.line_number, &trimmed[..start]), 0.0, plain.clone());
+        job.append(&trimmed[start..end], 0.0, highlighted);
+        job.append(&trimmed[end..], 0.0, plain);
+        Some(job)
     }
 
     fn render_preview(&mut self, ui: &mut egui::Ui) {
-        if let Some(preview_text) = &self.preview.content {
-            // Check if we should try syntax highlighting based on selected result
-            let should_highlight = if let Some(selected_id) = self.grep_state.selected_result {
-                let file_idx = selected_id / 10000;
-                self.grep_state
-                    .results
-                    .get(file_idx)
-                    .map(|r| self.should_highlight_file(&r.file_path))
-                    .unwrap_or(false)
-            } else {
-                false
-            };
+        // Hex and code views are single TextEdits at the monospace style's size; the plain
+        // view is one label per line at the configured font size
+        let plain_font = egui::FontId::new(self.grep_state.font_size, egui::FontFamily::Monospace);
+        self.preview_row_pitch = if self.hex_view_shown() || self.selected_file_is_highlighted() {
+            ui.text_style_height(&egui::TextStyle::Monospace)
+        } else {
+            ui.fonts(|f| f.row_height(&plain_font)) + ui.spacing().item_spacing.y
+        };
+
+        if self.hex_view_shown() {
+            if let Some(hex) = &self.preview.hex_content {
+                let mut hex = hex.as_str();
+                ui.add(
+                    egui::TextEdit::multiline(&mut hex)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(f32::INFINITY),
+                );
+                return;
+            }
+        }
+
+        let display_text = self.preview.display_text(
+            self.grep_state.show_line_numbers,
+            self.grep_state.show_whitespace,
+        );
+        if let Some(preview_text) = &display_text {
+            let preview_text = self.redactor.redact(preview_text);
+            let mut preview_text = preview_text.as_ref();
+
+            let should_highlight = self.selected_file_is_highlighted();
 
             if should_highlight {
                 // Use egui_extras syntax highlighting
@@ -1517,7 +3258,7 @@ impl VisGrepApp {
                 };
 
                 ui.add(
-                    egui::TextEdit::multiline(&mut preview_text.as_str())
+                    egui::TextEdit::multiline(&mut preview_text)
                         .code_editor()
                         .desired_width(f32::INFINITY)
                         .desired_rows(100)
@@ -1533,54 +3274,55 @@ impl VisGrepApp {
         }
     }
 
+    /// True when the preview shows the hex dump. Its ASCII column can't be redacted, so
+    /// the text view is shown instead while redaction is on.
+    fn hex_view_shown(&self) -> bool {
+        self.preview.show_hex && !self.redactor.enabled
+    }
+
+    /// True when the selected result's file gets syntax highlighting in the preview
+    fn selected_file_is_highlighted(&self) -> bool {
+        self.grep_state
+            .selected_result
+            .and_then(|id| self.grep_state.results.get(id / 10000))
+            .map(|r| self.should_highlight_file(&r.file_path))
+            .unwrap_or(false)
+    }
+
     fn render_matched_line_focus(&self, ui: &mut egui::Ui) {
         use egui::{Color32, RichText};
 
         if let Some(matched_line) = &self.preview.matched_line_text {
+            let matched_line = self.redactor.redact(matched_line);
             ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
             
             // Apply custom font size
             let font_id = egui::FontId::new(self.grep_state.font_size, egui::FontFamily::Monospace);
             ui.style_mut().text_styles.insert(egui::TextStyle::Monospace, font_id);
 
-            let highlight_color = Color32::from_rgb(255, 200, 100); // Orange/yellow
-            let highlight_bg = Color32::from_rgb(80, 60, 40); // Brown background
-
-            // Use highlight pattern if specified, otherwise use search query
-            let pattern_to_use = if !self.grep_state.fix_highlight_pattern.is_empty() {
-                &self.grep_state.fix_highlight_pattern
-            } else {
-                &self.grep_state.search_query
-            };
-
-            let has_pattern = !pattern_to_use.is_empty();
+            let patterns = self.active_highlight_patterns();
+            let segments = highlight::highlight_segments(&matched_line, &patterns);
 
-            if has_pattern && matched_line.contains(pattern_to_use) {
-                // Render with highlighted pattern
+            if segments.iter().any(|(_, pattern_idx)| pattern_idx.is_some()) {
+                // Render with each pattern in its own color
                 ui.horizontal_wrapped(|ui| {
                     ui.spacing_mut().item_spacing.x = 0.0;
 
-                    let parts: Vec<&str> = matched_line.split(pattern_to_use).collect();
-
-                    for (i, part) in parts.iter().enumerate() {
-                        if !part.is_empty() {
-                            ui.label(*part);
-                        }
-
-                        // Add highlighted pattern between parts (except after last part)
-                        if i < parts.len() - 1 {
-                            ui.label(
-                                RichText::new(pattern_to_use)
-                                    .color(highlight_color)
-                                    .background_color(highlight_bg)
-                                    .strong(),
-                            );
+                    for (text, pattern_idx) in segments {
+                        match pattern_idx {
+                            Some(idx) => {
+                                let (color, background) = highlight::pattern_colors(idx);
+                                ui.label(RichText::new(text).color(color).background_color(background).strong());
+                            }
+                            None => {
+                                ui.label(text);
+                            }
                         }
                     }
                 });
             } else {
                 // Just show the line normally
-                ui.label(matched_line);
+                ui.label(matched_line.as_ref());
             }
         } else {
             ui.label(
@@ -1604,9 +3346,11 @@ impl VisGrepApp {
                 ui.style_mut().text_styles.insert(egui::TextStyle::Monospace, font_id);
 
                 let match_line_bg = Color32::from_rgb(60, 60, 80); // Subtle blue-gray for matched line
+                let text_color = ui.visuals().text_color();
+                let mut clicked_link = None;
 
-                for line in text.lines() {
-                    let is_match_line = line.starts_with(">>>");
+                for (idx, line) in text.lines().enumerate() {
+                    let is_match_line = Some(idx) == self.preview.target_line_in_preview;
 
                     // Apply background color for matched line
                     if is_match_line {
@@ -1615,16 +3359,43 @@ impl VisGrepApp {
                             .inner_margin(egui::Margin::symmetric(4.0, 2.0));
 
                         frame.show(ui, |ui| {
-                            ui.label(line);
+                            if let Some(link) = links::render_linkified_text(ui, line, text_color) {
+                                clicked_link = Some(link);
+                            }
                         });
                     } else {
                         // Regular line
-                        ui.label(line);
+                        if let Some(link) = links::render_linkified_text(ui, line, text_color) {
+                            clicked_link = Some(link);
+                        }
                     }
                 }
+
+                if let Some(link) = clicked_link {
+                    self.open_preview_link(&link);
+                }
             });
     }
 
+    /// Open a file:line reference clicked in the grep preview, resolving relative
+    /// paths against the directory of the previewed file
+    fn open_preview_link(&self, link: &links::FileLink) {
+        let Some(result) = self
+            .grep_state
+            .selected_result
+            .and_then(|id| self.grep_state.results.get(id / 10000))
+        else {
+            return;
+        };
+
+        let path = links::resolve_link_path(&link.path, &result.file_path);
+        if path.exists() {
+            self.open_file_in_editor(&path, Some(link.line));
+        } else {
+            info!("Linked file not found: {:?}", path);
+        }
+    }
+
     fn should_highlight_file(&self, path: &std::path::Path) -> bool {
         if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
             matches!(
@@ -1682,6 +3453,10 @@ impl VisGrepApp {
                         log::error!("Failed to save config: {}", e);
                     }
                 }
+
+                // Redaction toggle (masks configured patterns for screen sharing)
+                ui.toggle_value(&mut self.redactor.enabled, "🔒 Redact")
+                    .on_hover_text("Mask sensitive patterns (config: redaction.patterns)");
                 
                 ui.separator();
                 
@@ -1712,28 +3487,37 @@ impl VisGrepApp {
 
 
 
+    /// Patterns highlighted in the matched line: the comma-separated highlight field,
+    /// or the search query as a single pattern when that's empty
+    fn active_highlight_patterns(&self) -> Vec<&str> {
+        if self.grep_state.fix_highlight_pattern.trim().is_empty() {
+            vec![self.grep_state.search_query.as_str()]
+                .into_iter()
+                .filter(|query| !query.is_empty())
+                .collect()
+        } else {
+            highlight::parse_patterns(&self.grep_state.fix_highlight_pattern)
+        }
+    }
+
     /// Render the highlight pattern field
     fn render_highlight_pattern_field(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.label("Highlight pattern in Matched Line (e.g., 150= or fn):");
+            ui.label("Highlight patterns in Matched Line (e.g., 35=, 55=, ClOrdID=):");
             let response = ui.add(
                 egui::TextEdit::singleline(&mut self.grep_state.fix_highlight_pattern)
-                    .desired_width(150.0)
-                    .hint_text("uses search query if empty"),
+                    .desired_width(200.0)
+                    .hint_text("comma-separated; uses search query if empty"),
             );
 
-            // Show active indicator
-            let active_pattern = if !self.grep_state.fix_highlight_pattern.is_empty() {
-                &self.grep_state.fix_highlight_pattern
-            } else {
-                &self.grep_state.search_query
-            };
-
-            if !active_pattern.is_empty() {
-                ui.label(
-                    egui::RichText::new(format!("✓ Active: '{}'", active_pattern))
-                        .color(egui::Color32::from_rgb(100, 255, 100)),
-                );
+            // Show active indicator, each pattern in its highlight color
+            let patterns = self.active_highlight_patterns();
+            if !patterns.is_empty() {
+                ui.label(egui::RichText::new("✓ Active:").color(egui::Color32::from_rgb(100, 255, 100)));
+                for (idx, pattern) in patterns.iter().enumerate() {
+                    let (color, background) = highlight::pattern_colors(idx);
+                    ui.label(egui::RichText::new(*pattern).color(color).background_color(background));
+                }
             }
 
             if ui.small_button("Clear").clicked() {
@@ -1799,6 +3583,8 @@ impl VisGrepApp {
             if ui.small_button("Clear").clicked() {
                 self.grep_state.file_pattern.clear();
             }
+            ui.checkbox(&mut self.grep_state.file_pattern_ignore_case, "Any case")
+                .on_hover_text("Match the file pattern regardless of case, e.g. *.log also finds APP.LOG");
         });
     }
 
@@ -1815,35 +3601,160 @@ impl VisGrepApp {
                 self.render_patterns_dropdown(ui);
             }
 
-            // Debounced auto-search: trigger search 500ms after typing stops
+            // Invalid regex: say why instead of searching and finding nothing
+            self.grep_state.refresh_query_error();
+            if let Some(error) = &self.grep_state.query_error {
+                ui.label(egui::RichText::new(format!("⚠ {}", search::error_summary(error))).color(egui::Color32::from_rgb(255, 100, 100)))
+                    .on_hover_text(egui::RichText::new(error).monospace());
+            }
+            let can_search = self.grep_state.query_error.is_none() && !self.grep_state.search_query.is_empty();
+
+            // Debounced auto-search: trigger search once typing stops for search_debounce_ms
             if response.changed() {
-                self.grep_state.pending_search = true;
+                self.grep_state.pending_search = can_search && self.config.ui.search_debounce_ms > 0;
                 self.grep_state.last_search_time = Instant::now();
             }
 
-            if response.lost_focus()
-                && ui.input(|i| i.key_pressed(egui::Key::Enter))
-                && !self.grep_state.search_query.is_empty()
-            {
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) && can_search {
                 self.perform_search();
             }
 
             ui.checkbox(&mut self.grep_state.case_sensitive, "Case Sensitive");
-            ui.checkbox(&mut self.grep_state.use_regex, "Regex");
+            ui.checkbox(&mut self.grep_state.file_names_only, "Names only")
+                .on_hover_text("Match the query against file names instead of their contents");
+            egui::ComboBox::from_id_salt("search_mode")
+                .selected_text(self.grep_state.search_mode.label())
+                .width(70.0)
+                .show_ui(ui, |ui| {
+                    for mode in SearchMode::ALL {
+                        ui.selectable_value(&mut self.grep_state.search_mode, mode, mode.label());
+                    }
+                })
+                .response
+                .on_hover_text("Literal text, regular expression, or fuzzy (characters in order, best matches first)");
+            if self.grep_state.search_mode == SearchMode::Regex {
+                self.render_effective_regex_flags(ui);
+            }
             ui.checkbox(&mut self.grep_state.recursive, "Recursive");
+            ui.checkbox(&mut self.grep_state.follow_symlinks, "Follow links")
+                .on_hover_text("Descend into symlinked directories when searching subdirectories");
 
-            if ui.button("Search").clicked() && !self.grep_state.search_query.is_empty() {
+            if ui.checkbox(&mut self.grep_state.watch_enabled, "Watch")
+                .on_hover_text("Re-run the search when matching files change")
+                .changed()
+            {
+                if self.grep_state.watch_enabled {
+                    self.start_watch(ui.ctx());
+                } else {
+                    self.stop_watch();
+                }
+            }
+
+            if ui.add_enabled(can_search, egui::Button::new("Search")).clicked() {
                 self.perform_search();
             }
+
+            // F5 or the button re-runs the last search as it was run
+            let repeat_tooltip = self
+                .grep_state
+                .last_search
+                .as_ref()
+                .map_or_else(|| "No search has run yet".to_string(), |params| params.describe());
+            let repeat_clicked = ui
+                .add_enabled(self.grep_state.last_search.is_some(), egui::Button::new("↻ Repeat"))
+                .on_hover_text(repeat_tooltip)
+                .on_disabled_hover_text("No search has run yet")
+                .clicked();
+            if repeat_clicked || ui.input(|i| i.key_pressed(egui::Key::F5)) {
+                self.repeat_last_search();
+            }
+        });
+    }
+
+    /// The inline flags a regex query is compiled with, next to the Case Sensitive checkbox
+    /// that sets them. There are no m/s flags: each line is matched on its own, so they
+    /// would never change a match.
+    fn render_effective_regex_flags(&self, ui: &mut egui::Ui) {
+        let inline = search::inline_flags(self.grep_state.case_sensitive);
+        if !inline.is_empty() {
+            ui.label(egui::RichText::new(inline).monospace().weak())
+                .on_hover_text("Effective flags the query is compiled with");
+        }
+    }
+
+    /// Start counting a saved pattern's matches under the current search settings
+    fn start_pattern_count(&mut self, pattern: String) {
+        let grep = &self.grep_state;
+        let matcher = match grep.search_engine.clone().compile_query(
+            &pattern,
+            grep.case_sensitive,
+            grep.search_mode,
+        ) {
+            Ok(matcher) => matcher,
+            Err(_) => {
+                self.grep_state.pattern_counts.insert(pattern, None);
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let mut engine = grep.search_engine.clone();
+        engine.file_pattern_ignore_case = grep.file_pattern_ignore_case;
+        engine.follow_symlinks = grep.follow_symlinks;
+        let search_path = Self::expand_tilde(&grep.search_path);
+        let Ok(file_pattern) = engine.file_pattern(&grep.file_pattern) else {
+            self.grep_state.pattern_counts.insert(pattern, None);
+            return;
+        };
+        let recursive = grep.recursive;
+        std::thread::spawn(move || {
+            let count = engine.count_matches(&search_path, &file_pattern, &matcher, recursive, PATTERN_COUNT_LIMITS);
+            let _ = sender.send(Some(count));
         });
+        self.grep_state.pattern_count_receiver = Some((pattern, receiver));
+    }
+
+    /// Pick up a finished pattern count, and forget counts made under other settings
+    fn poll_pattern_count(&mut self) {
+        let grep = &mut self.grep_state;
+        let scope = format!(
+            "{}\n{}\n{}\n{:?}\n{}",
+            grep.search_path, grep.file_pattern, grep.recursive, grep.search_mode, grep.case_sensitive
+        );
+        if scope != grep.pattern_counts_scope {
+            grep.pattern_counts_scope = scope;
+            grep.pattern_counts.clear();
+            grep.pattern_count_receiver = None;
+        }
+
+        if let Some((pattern, receiver)) = &grep.pattern_count_receiver {
+            match receiver.try_recv() {
+                Ok(count) => {
+                    grep.pattern_counts.insert(pattern.clone(), count);
+                    grep.pattern_count_receiver = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => grep.pattern_count_receiver = None,
+            }
+        }
     }
 
     /// Render the saved patterns dropdown
     fn render_patterns_dropdown(&mut self, ui: &mut egui::Ui) {
+        self.poll_pattern_count();
+        let mut hovered_pattern = None;
+
         egui::ComboBox::from_id_salt("saved_patterns")
             .selected_text("📝")
             .width(40.0)
             .show_ui(ui, |ui| {
+                ui.checkbox(&mut self.grep_state.count_patterns_on_hover, "Count matches on hover")
+                    .on_hover_text(format!(
+                        "Hovering a pattern counts its matches in the first {} files under the search path, stopping at {}",
+                        PATTERN_COUNT_LIMITS.max_files, PATTERN_COUNT_LIMITS.max_matches
+                    ));
+                ui.separator();
+
                 // Group by category if available
                 let mut by_category: std::collections::HashMap<String, Vec<&config::SavedPattern>> =
                     std::collections::HashMap::new();
@@ -1874,8 +3785,25 @@ impl VisGrepApp {
                                 pattern.name.to_string()
                             };
 
-                            let mut button = ui.selectable_label(false, label);
+                            let counting = self
+                                .grep_state
+                                .pattern_count_receiver
+                                .as_ref()
+                                .is_some_and(|(counting, _)| *counting == pattern.pattern);
+                            let count = match self.grep_state.pattern_counts.get(&pattern.pattern) {
+                                Some(Some(count)) => {
+                                    format!("  ({}{})", count.matches, if count.truncated { "+" } else { "" })
+                                }
+                                Some(None) => "  (invalid)".to_string(),
+                                None if counting => "  (…)".to_string(),
+                                None => String::new(),
+                            };
 
+                            let mut button = ui.selectable_label(false, format!("{}{}", label, count));
+
+                            if button.hovered() && self.grep_state.count_patterns_on_hover {
+                                hovered_pattern = Some(pattern.pattern.clone());
+                            }
                             if !pattern.description.is_empty() {
                                 button = button.on_hover_text(&pattern.description);
                             }
@@ -1892,6 +3820,15 @@ impl VisGrepApp {
                     }
                 }
             });
+
+        if let Some(pattern) = hovered_pattern {
+            if self.grep_state.pattern_count_receiver.is_none() && !self.grep_state.pattern_counts.contains_key(&pattern) {
+                self.start_pattern_count(pattern);
+            }
+        }
+        if self.grep_state.pattern_count_receiver.is_some() {
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        }
     }
 
     /// Render file age filter controls
@@ -1921,18 +3858,78 @@ impl VisGrepApp {
         ui.horizontal(|ui| {
             match self.mode {
                 AppMode::Grep => {
+                    let unit = self.config.ui.match_count_unit;
+                    let counted = match unit {
+                        search::CountUnit::Lines => "matching lines",
+                        search::CountUnit::Occurrences => "occurrences",
+                    };
                     let total_matches: usize = self
                         .grep_state
                         .results
                         .iter()
-                        .map(|r| r.matches.len())
+                        .map(|r| r.count(unit))
                         .sum();
                     let file_count = self.grep_state.results.len();
 
-                    ui.label(format!(
-                        "Found {} matches in {} files",
-                        total_matches, file_count
-                    ));
+                    let scanned = self
+                        .grep_state
+                        .files_scanned
+                        .map(|count| format!(" / {} files scanned", count))
+                        .unwrap_or_else(|| " files".to_string());
+                    let elapsed = self
+                        .grep_state
+                        .search_duration
+                        .unwrap_or_else(|| self.grep_state.search_started.elapsed());
+                    let names_only = self.grep_state.last_search.as_ref().is_some_and(|search| search.file_names_only);
+                    let summary = if names_only {
+                        ui.label(format!(
+                            "Found {} file names matching{}",
+                            file_count,
+                            self.grep_state.files_scanned.map(|count| format!(" of {} files", count)).unwrap_or_default()
+                        ))
+                    } else if self.grep_state.searching || self.grep_state.search_duration.is_some() {
+                        ui.label(format!(
+                            "Found {} {} in {}{} in {:.1}s",
+                            total_matches, counted, file_count, scanned, elapsed.as_secs_f64()
+                        ))
+                    } else {
+                        ui.label(format!(
+                            "Found {} {} in {} files",
+                            total_matches, counted, file_count
+                        ))
+                    };
+                    if let Some(pattern) = &self.grep_state.executed_pattern {
+                        summary.on_hover_ui(|ui| {
+                            ui.label("Executed pattern:");
+                            ui.label(egui::RichText::new(pattern).monospace());
+                        });
+                    }
+                    if ui.small_button("⇄")
+                        .on_hover_text(format!("Count {} instead", unit.toggled().label()))
+                        .clicked()
+                    {
+                        self.config.ui.match_count_unit = unit.toggled();
+                        if let Err(e) = self.config.save() {
+                            log::error!("Failed to save config: {}", e);
+                        }
+                    }
+
+                    // Which match n/p navigation is on, among the matched lines
+                    let position = self.grep_state.selected_result.and_then(|id| {
+                        search::match_position(&self.grep_state.results, id / 10000, id % 10000)
+                    });
+                    if let Some(position) = position {
+                        ui.separator();
+                        ui.label(format!(
+                            "Match {} of {} (this file) / {} of {} total",
+                            position.in_file, position.file_total, position.overall, position.total
+                        ));
+                    }
+
+                    if let Some(error) = &self.grep_state.file_pattern_error {
+                        ui.separator();
+                        ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("⚠ Invalid file pattern: {}", error));
+                    }
 
                     if self.grep_state.searching {
                         ui.spinner();
@@ -2002,19 +3999,36 @@ fn main() -> eframe::Result<()> {
         }
     }
 
-    // Determine startup configuration
-    let startup_config = match cli.command {
+    let tail_dirs: Vec<TailDirectory> = cli.dirs
+        .iter()
+        .map(|path| TailDirectory {
+            path: path.clone(),
+            pattern: cli.pattern.clone(),
+            group: cli.group,
+        })
+        .collect();
+
+    let grep_args = GrepArgs::from_cli(&cli);
+
+    // Determine startup configuration: command-line arguments, then a restored
+    // session, then ui.default_mode
+    let ui_prefs = Config::load().ui;
+    let mut startup_config = match cli.command {
         Some(Commands::Tail { files }) => {
             info!("Starting in Tail mode with files: {:?}", files);
             StartupConfig {
                 mode: AppMode::Tail,
                 tail_files: files,
                 tail_layout: cli.tail_layout,
+                tail_dirs,
+                tail_lines: cli.lines,
+                session: None,
+                grep_args: GrepArgs::default(),
             }
         }
         None => {
-            if cli.follow || !cli.files.is_empty() || cli.tail_layout.is_some() {
-                // -f flag, files provided, or layout specified
+            if cli.follow || !cli.files.is_empty() || cli.tail_layout.is_some() || !tail_dirs.is_empty() {
+                // -f flag, files or directories provided, or layout specified
                 if let Some(ref layout) = cli.tail_layout {
                     info!("Starting in Tail mode with layout file: {:?}", layout);
                 } else {
@@ -2027,15 +4041,37 @@ fn main() -> eframe::Result<()> {
                     mode: AppMode::Tail,
                     tail_files: cli.files,
                     tail_layout: cli.tail_layout,
+                    tail_dirs,
+                    tail_lines: cli.lines,
+                    session: None,
+                    grep_args: GrepArgs::default(),
                 }
-            } else {
-                // Default: Grep mode
-                info!("Starting in Grep mode (default)");
+            } else if !grep_args.is_empty() {
+                info!("Starting in Grep mode with a search from the command line");
                 StartupConfig::default()
+            } else if let Some(session) = ui_prefs.restore_session.then(session::Session::load).flatten() {
+                info!("Starting from the last session");
+                StartupConfig {
+                    mode: session.mode.into(),
+                    tail_files: session.tail_files.clone(),
+                    tail_layout: session.tail_layout.clone(),
+                    tail_dirs: Vec::new(),
+                    tail_lines: 0,
+                    session: Some(session),
+                    grep_args: GrepArgs::default(),
+                }
+            } else {
+                // Default: ui.default_mode, Grep unless configured otherwise
+                info!("Starting in {:?} mode (default)", ui_prefs.default_mode);
+                StartupConfig {
+                    mode: ui_prefs.default_mode.into(),
+                    ..StartupConfig::default()
+                }
             }
         }
     };
 
+    startup_config.grep_args = grep_args;
     info!("VisGrep starting in {:?} mode...", startup_config.mode);
 
     // Set up persistence path for window size and splitter positions
@@ -2067,6 +4103,8 @@ fn main() -> eframe::Result<()> {
             // Ensure good contrast for panels
             visuals.window_shadow = egui::epaint::Shadow::NONE;
             cc.egui_ctx.set_visuals(visuals);
+            // Ctrl+=/Ctrl+- zoom the text font instead of scaling the whole UI
+            cc.egui_ctx.options_mut(|options| options.zoom_with_keyboard = false);
             Ok(Box::new(VisGrepApp::new(startup_config)))
         }),
     )
@@ -2076,6 +4114,25 @@ fn main() -> eframe::Result<()> {
 // Helper Functions
 // ============================================================================
 
+/// Compact age such as "42s", "5m", "3h" or "2d"
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else if secs < 86400 {
+        format!("{}h", secs / 3600)
+    } else {
+        format!("{}d", secs / 86400)
+    }
+}
+
+/// "#RRGGBB" as written in layout files
+fn color_to_hex(color: egui::Color32) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.r(), color.g(), color.b())
+}
+
 // Helper function for color coding files
 fn get_color_for_file(filename: &str) -> egui::Color32 {
     use std::collections::hash_map::DefaultHasher;
@@ -2117,3 +4174,108 @@ fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
         ((b + m) * 255.0) as u8,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tailed_file(path: &str) -> TailedFile {
+        let path = PathBuf::from(path);
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        TailedFile::at_position(path, name, 0, None)
+    }
+
+    fn push_line(state: &mut TailState, file_idx: usize, content: &str, level: log_parser::LogLevel) -> u64 {
+        let file = &state.files[file_idx];
+        let seq = state.next_line_seq;
+        let line = LogLine {
+            seq,
+            timestamp: Instant::now(),
+            source_file: file.display_name.clone(),
+            source_path: file.path.clone(),
+            line_number: 1,
+            content: content.to_string(),
+            level,
+            is_json: false,
+        };
+        state.next_line_seq += 1;
+        state.push_output_line(line);
+        seq
+    }
+
+    /// Two files named app.log in different directories
+    fn same_named_files() -> TailState {
+        let mut state = TailState::new(&Config::default());
+        state.files.push(tailed_file("/var/a/app.log"));
+        state.files.push(tailed_file("/var/b/app.log"));
+        state
+    }
+
+    fn shown_contents(state: &mut TailState) -> Vec<String> {
+        state.refresh_output_rows();
+        let rows = &state.output_rows.as_ref().unwrap().rows;
+        rows.iter()
+            .map(|(seq, _)| state.find_output_line(*seq).unwrap().content.clone())
+            .collect()
+    }
+
+    #[test]
+    fn muting_a_file_keeps_a_same_named_file_shown() {
+        let mut state = same_named_files();
+        push_line(&mut state, 0, "from a", log_parser::LogLevel::Info);
+        push_line(&mut state, 1, "from b", log_parser::LogLevel::Info);
+
+        state.files[0].muted = true;
+        assert_eq!(shown_contents(&mut state), vec!["from b"]);
+    }
+
+    #[test]
+    fn clearing_a_file_keeps_a_same_named_files_lines() {
+        let mut state = same_named_files();
+        push_line(&mut state, 0, "from a", log_parser::LogLevel::Info);
+        push_line(&mut state, 1, "from b", log_parser::LogLevel::Info);
+
+        let path = state.files[0].path.clone();
+        state.clear_file_output(&path);
+        assert_eq!(shown_contents(&mut state), vec!["from b"]);
+    }
+
+    #[test]
+    fn source_tag_uses_the_color_of_the_lines_own_file() {
+        let mut state = same_named_files();
+        let red = egui::Color32::from_rgb(255, 0, 0);
+        state.files[1].color = Some(red);
+        let seq = push_line(&mut state, 1, "from b", log_parser::LogLevel::Info);
+
+        let line = state.find_output_line(seq).unwrap();
+        assert_eq!(state.file_color(line), red);
+    }
+
+    #[test]
+    fn error_jumps_skip_errors_that_are_not_shown() {
+        let mut state = same_named_files();
+        push_line(&mut state, 0, "hidden failure", log_parser::LogLevel::Error);
+        let shown = push_line(&mut state, 1, "shown failure", log_parser::LogLevel::Error);
+        push_line(&mut state, 0, "another hidden failure", log_parser::LogLevel::Fatal);
+        state.files[0].muted = true;
+
+        state.jump_to_error_line(true);
+        assert_eq!(state.output_scroll_to_seq, Some(shown));
+        state.jump_to_error_line(true);
+        assert_eq!(state.output_scroll_to_seq, Some(shown));
+        state.jump_to_error_line(false);
+        assert_eq!(state.output_scroll_to_seq, Some(shown));
+    }
+
+    #[test]
+    fn tree_filter_on_output_tells_same_named_files_apart() {
+        let mut state = same_named_files();
+        push_line(&mut state, 0, "from a", log_parser::LogLevel::Info);
+        push_line(&mut state, 1, "from b", log_parser::LogLevel::Info);
+
+        state.tree_filter.pattern = "b/app".to_string();
+        state.tree_filter.active = true;
+        state.tree_filter.apply_to_output = true;
+        assert_eq!(shown_contents(&mut state), vec!["from b"]);
+    }
+}
@@ -0,0 +1,92 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A vim-style mark as saved next to a log. The line text is kept so the mark can be
+/// found again if lines were added above it, and so the file reads as notes on its own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedMark {
+    /// 1-indexed line in the log file
+    pub line: usize,
+    pub text: String,
+}
+
+/// The marks for one log, kept in `<log>.marks` so they can be reopened later or handed
+/// to someone else along with the log
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarksFile {
+    pub marks: BTreeMap<char, SavedMark>,
+}
+
+impl MarksFile {
+    /// Where the marks for `log_path` are kept, e.g. `app.log.marks`
+    pub fn path_for(log_path: &Path) -> PathBuf {
+        let mut name = log_path.as_os_str().to_owned();
+        name.push(".marks");
+        PathBuf::from(name)
+    }
+
+    /// The saved marks for a log, if there are any that parse
+    pub fn load(log_path: &Path) -> Option<Self> {
+        let path = Self::path_for(log_path);
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_yaml::from_str(&content) {
+            Ok(marks) => {
+                info!("Loaded marks from {:?}", path);
+                Some(marks)
+            }
+            Err(e) => {
+                warn!("Failed to parse marks file {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, log_path: &Path) -> Result<PathBuf, String> {
+        let path = Self::path_for(log_path);
+        let yaml = serde_yaml::to_string(self).map_err(|e| format!("Failed to serialize marks: {}", e))?;
+        fs::write(&path, yaml).map_err(|e| format!("Failed to write marks file: {}", e))?;
+        info!("Saved {} marks to {:?}", self.marks.len(), path);
+        Ok(path)
+    }
+}
+
+impl SavedMark {
+    /// Index of the marked line in `content`, whose first entry is file line
+    /// `first_line + 1`: where it was if the text still matches there, otherwise the last
+    /// line with the same text
+    pub fn locate(&self, content: &[String], first_line: usize) -> Option<usize> {
+        let expected = self.line.checked_sub(first_line + 1);
+        if let Some(idx) = expected.filter(|&idx| content.get(idx) == Some(&self.text)) {
+            return Some(idx);
+        }
+        content.iter().rposition(|line| *line == self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_locate() {
+        let log = std::env::temp_dir().join(format!("vis-grep-marks-{}.log", std::process::id()));
+        let mut marks = MarksFile::default();
+        marks.marks.insert('a', SavedMark { line: 3, text: "boom".to_string() });
+        let path = marks.save(&log).unwrap();
+        assert!(path.to_string_lossy().ends_with(".log.marks"));
+        assert_eq!(MarksFile::load(&log), Some(marks.clone()));
+        fs::remove_file(path).unwrap();
+
+        let mark = &marks.marks[&'a'];
+        let content: Vec<String> = ["x", "boom", "y"].iter().map(|s| s.to_string()).collect();
+        // Content starting at file line 2: line 3 is index 1
+        assert_eq!(mark.locate(&content, 1), Some(1));
+        // Lines shifted: found by text instead
+        assert_eq!(mark.locate(&content, 0), Some(1));
+        assert_eq!(mark.locate(&content[..1], 1), None);
+    }
+}
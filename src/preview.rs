@@ -1,59 +1,177 @@
 use log::info;
 use memmap2::Mmap;
+use std::borrow::Cow;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Files at or above this size are read through a memory map rather than a BufReader
+pub const MMAP_THRESHOLD: u64 = 10 * 1024 * 1024;
+
+/// Hex view window: bytes shown before and after the start of the target line
+const HEX_BYTES_BEFORE: usize = 512;
+const HEX_BYTES_AFTER: usize = 1536;
+const HEX_BYTES_PER_ROW: usize = 16;
+
+/// How much of the file start is sniffed for binary content
+const BINARY_SNIFF_LEN: usize = 8192;
+
+/// A line of the preview window; the gutter is added when rendering
+pub struct PreviewLine {
+    pub number: usize, // 1-based line number in the file
+    pub text: String,
+}
 
 pub struct FilePreview {
-    pub content: Option<String>,
-    pub target_line_in_preview: Option<usize>, // Which line in the preview content has the >>>
+    pub lines: Vec<PreviewLine>,
+    pub error: Option<String>,
+    pub target_line_in_preview: Option<usize>, // Which entry of `lines` is the target (>>>)
     pub matched_line_text: Option<String>,     // The actual matched line text (without >>> marker)
+    pub hex_content: Option<String>,           // Hex dump of the window around the target line
+    pub hex_target_row: Option<usize>,         // Which hex row holds the start of the target line
+    pub is_binary: bool,                       // File looks binary; hex view is forced on
+    pub show_hex: bool,                        // Hex view currently shown
+    hex_preferred: bool,                       // User's hex toggle, applied to text files
+    // File and byte offset of the target line, kept so the hex window is only built
+    // once hex view is actually shown
+    hex_source: Option<(PathBuf, usize)>,
 }
 
 impl FilePreview {
     pub fn new() -> Self {
         Self {
-            content: None,
+            lines: Vec::new(),
+            error: None,
             target_line_in_preview: None,
             matched_line_text: None,
+            hex_content: None,
+            hex_target_row: None,
+            is_binary: false,
+            show_hex: false,
+            hex_preferred: false,
+            hex_source: None,
+        }
+    }
+
+    /// Switch between text and hex views. Sticks for later text files; binary files
+    /// always open in hex.
+    pub fn toggle_hex(&mut self) {
+        self.show_hex = !self.show_hex;
+        self.hex_preferred = self.show_hex;
+        self.ensure_hex_window();
+    }
+
+    /// Build the hex dump around the target line if hex view is on and it isn't built yet
+    fn ensure_hex_window(&mut self) {
+        if !self.show_hex || self.hex_content.is_some() {
+            return;
+        }
+        let Some((path, target_offset)) = &self.hex_source else {
+            return;
+        };
+        match Self::load_hex_window(path, *target_offset) {
+            Ok((dump, target_row)) => {
+                self.hex_content = Some(dump);
+                self.hex_target_row = Some(target_row);
+            }
+            Err(e) => info!("Error loading hex view for {:?}: {}", path, e),
         }
     }
 
     /// Load a preview window around the specified line number
     /// For performance, we only load a window of lines around the target
     pub fn load_file(&mut self, path: &Path, target_line: usize) {
-        self.content = None;
+        self.lines.clear();
+        self.error = None;
         self.target_line_in_preview = None;
         self.matched_line_text = None;
+        self.hex_content = None;
+        self.hex_target_row = None;
+        self.hex_source = None;
+
+        self.is_binary = Self::sniff_binary(path).unwrap_or(false);
+        self.show_hex = self.hex_preferred || self.is_binary;
 
         match self.load_preview_fast(path, target_line) {
-            Ok((text, preview_line, matched_text)) => {
-                let total_lines = text.lines().count();
+            Ok((lines, preview_line, matched_text, target_offset)) => {
                 info!(
                     "Preview loaded: target_line={}, preview_line_index={}, total_preview_lines={}",
-                    target_line, preview_line, total_lines
+                    target_line, preview_line, lines.len()
                 );
-                self.content = Some(text);
+                self.lines = lines;
                 self.target_line_in_preview = Some(preview_line);
                 self.matched_line_text = Some(matched_text);
+                self.hex_source = Some((path.to_path_buf(), target_offset));
+                self.ensure_hex_window();
             }
             Err(e) => {
                 info!("Error loading preview for {:?}: {}", path, e);
-                self.content = Some(format!("Error loading preview for {:?}", path));
+                self.error = Some(format!("Error loading preview for {:?}", path));
                 self.target_line_in_preview = None;
                 self.matched_line_text = None;
             }
         }
     }
 
+    /// First and last file line numbers in the loaded window
+    pub fn line_range(&self) -> Option<(usize, usize)> {
+        Some((self.lines.first()?.number, self.lines.last()?.number))
+    }
+
+    /// Index into `lines` of a file line number, if it's inside the loaded window
+    pub fn index_of_line(&self, line_number: usize) -> Option<usize> {
+        self.lines.binary_search_by_key(&line_number, |line| line.number).ok()
+    }
+
+    /// The previewed lines as they are in the file, without gutter or markers, for copying
+    pub fn plain_text(&self) -> Option<String> {
+        if self.error.is_some() || self.lines.is_empty() {
+            return None;
+        }
+        Some(self.lines.iter().map(|line| line.text.as_str()).collect::<Vec<_>>().join("\n"))
+    }
+
+    /// The preview window as text, optionally with the `>>> NNNN |` gutter and with
+    /// tabs/trailing spaces drawn as visible glyphs. None until a file is loaded.
+    pub fn display_text(&self, show_line_numbers: bool, show_whitespace: bool) -> Option<String> {
+        if let Some(error) = &self.error {
+            return Some(error.clone());
+        }
+        if self.lines.is_empty() {
+            return None;
+        }
+
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                let text = if show_whitespace {
+                    visualize_whitespace(&line.text)
+                } else {
+                    Cow::Borrowed(line.text.as_str())
+                };
+                if !show_line_numbers {
+                    text.into_owned()
+                } else if Some(idx) == self.target_line_in_preview {
+                    format!(">>> {:4} | {}", line.number, text)
+                } else {
+                    format!("    {:4} | {}", line.number, text)
+                }
+            })
+            .collect();
+        Some(lines.join("\n"))
+    }
+
     /// Fast preview loading using buffered reading
     /// Shows context_lines before and after the target line
-    /// Returns (preview_text, line_number_in_preview_where_target_is, matched_line_text)
+    /// Returns (preview_lines, line_number_in_preview_where_target_is, matched_line_text,
+    /// byte offset where the target line starts)
     fn load_preview_fast(
         &self,
         path: &Path,
         target_line: usize,
-    ) -> std::io::Result<(String, usize, String)> {
+    ) -> std::io::Result<(Vec<PreviewLine>, usize, String, usize)> {
         let context_lines = 50; // Show 50 lines before and after for better context
         let start_line = target_line.saturating_sub(context_lines);
         let end_line = target_line + context_lines;
@@ -62,33 +180,43 @@ impl FilePreview {
         let metadata = file.metadata()?;
         let file_size = metadata.len();
 
-        // For small files (< 10MB), just read the whole thing
-        if file_size < 10 * 1024 * 1024 {
-            let reader = BufReader::new(file);
-            let mut preview_line_idx = 0;
+        // For small files (< 10MB), read line by line up to the end of the window
+        if file_size < MMAP_THRESHOLD {
+            let mut reader = BufReader::new(file);
+            let mut lines = Vec::new();
             let mut target_preview_line = 0;
             let mut matched_line_text = String::new();
+            let mut target_offset = file_size as usize;
+            let mut offset = 0;
+            let mut buf = Vec::new();
+
+            for line_number in 1..=end_line + 1 {
+                buf.clear();
+                let read = reader.read_until(b'\n', &mut buf)?;
+                if read == 0 {
+                    break;
+                }
+                if line_number == target_line.max(1) {
+                    target_offset = offset;
+                }
+                offset += read;
+                if line_number <= start_line {
+                    continue;
+                }
+
+                let body = buf.strip_suffix(b"\n").unwrap_or(&buf);
+                let body = body.strip_suffix(b"\r").unwrap_or(body);
+                // Lines that aren't UTF-8 are left out, as the mmap path does
+                if let Ok(text) = std::str::from_utf8(body) {
+                    if line_number == target_line {
+                        target_preview_line = lines.len();
+                        matched_line_text = text.to_string();
+                    }
+                    lines.push(PreviewLine { number: line_number, text: text.to_string() });
+                }
+            }
 
-            let lines: Vec<String> = reader
-                .lines()
-                .enumerate()
-                .filter(|(idx, _)| *idx >= start_line && *idx <= end_line)
-                .filter_map(|(idx, line)| {
-                    line.ok().map(|l| {
-                        let formatted = if idx + 1 == target_line {
-                            target_preview_line = preview_line_idx;
-                            matched_line_text = l.clone();
-                            format!(">>> {:4} | {}", idx + 1, l)
-                        } else {
-                            format!("    {:4} | {}", idx + 1, l)
-                        };
-                        preview_line_idx += 1;
-                        formatted
-                    })
-                })
-                .collect();
-
-            return Ok((lines.join("\n"), target_preview_line, matched_line_text));
+            return Ok((lines, target_preview_line, matched_line_text, target_offset));
         }
 
         // For large files, use memory mapping
@@ -100,7 +228,7 @@ impl FilePreview {
         path: &Path,
         target_line: usize,
         context_lines: usize,
-    ) -> std::io::Result<(String, usize, String)> {
+    ) -> std::io::Result<(Vec<PreviewLine>, usize, String, usize)> {
         let file = File::open(path)?;
         let mmap = unsafe { Mmap::map(&file)? };
 
@@ -112,6 +240,7 @@ impl FilePreview {
         let mut line_start = 0;
         let mut target_preview_line = 0;
         let mut matched_line_text = String::new();
+        let mut target_offset = if target_line <= 1 { 0 } else { mmap.len() };
 
         for (pos, &byte) in mmap.iter().enumerate() {
             if byte == b'\n' {
@@ -121,15 +250,16 @@ impl FilePreview {
                         if current_line == target_line {
                             target_preview_line = result.len();
                             matched_line_text = line_str.to_string();
-                            result.push(format!(">>> {:4} | {}", current_line, line_str));
-                        } else {
-                            result.push(format!("    {:4} | {}", current_line, line_str));
                         }
+                        result.push(PreviewLine { number: current_line, text: line_str.to_string() });
                     }
                 }
 
                 current_line += 1;
                 line_start = pos + 1;
+                if current_line == target_line {
+                    target_offset = line_start;
+                }
 
                 if current_line > end_line {
                     break;
@@ -137,6 +267,164 @@ impl FilePreview {
             }
         }
 
-        Ok((result.join("\n"), target_preview_line, matched_line_text))
+        Ok((result, target_preview_line, matched_line_text, target_offset))
+    }
+
+    fn sniff_binary(path: &Path) -> std::io::Result<bool> {
+        let mut buf = vec![0u8; BINARY_SNIFF_LEN];
+        let mut file = File::open(path)?;
+        let len = file.read(&mut buf)?;
+        Ok(looks_binary(&buf[..len]))
+    }
+
+    /// Hex dump of the bytes around `target_offset`, where the target line starts
+    /// Returns (dump, row_of_target_line_start)
+    fn load_hex_window(path: &Path, target_offset: usize) -> std::io::Result<(String, usize)> {
+        let file = File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            return Ok((String::new(), 0));
+        }
+        let mmap = unsafe { Mmap::map(&file)? };
+        let target_offset = target_offset.min(mmap.len());
+
+        let start = target_offset.saturating_sub(HEX_BYTES_BEFORE) / HEX_BYTES_PER_ROW * HEX_BYTES_PER_ROW;
+        let end = (target_offset + HEX_BYTES_AFTER).min(mmap.len());
+        let target_row = (target_offset - start) / HEX_BYTES_PER_ROW;
+
+        Ok((hex_dump(&mmap[start..end], start, target_offset), target_row))
+    }
+}
+
+/// Draw tabs as `→` padded to the next 4-column stop and trailing spaces as `·`
+pub fn visualize_whitespace(line: &str) -> Cow<'_, str> {
+    let content_len = line.trim_end_matches(' ').len();
+    if !line.contains('\t') && content_len == line.len() {
+        return Cow::Borrowed(line);
+    }
+
+    let mut out = String::with_capacity(line.len() + 8);
+    let mut column = 0;
+    for ch in line[..content_len].chars() {
+        if ch == '\t' {
+            let width = 4 - column % 4;
+            out.push('→');
+            out.extend(std::iter::repeat_n(' ', width - 1));
+            column += width;
+        } else {
+            out.push(ch);
+            column += 1;
+        }
+    }
+    out.extend(std::iter::repeat_n('·', line.len() - content_len));
+    Cow::Owned(out)
+}
+
+/// True if the data has NUL bytes or is mostly control characters. Tabs, newlines,
+/// escapes and FIX SOH separators are normal in logs and don't count.
+pub fn looks_binary(data: &[u8]) -> bool {
+    if data.contains(&0) {
+        return true;
+    }
+    let control = data
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x1b | 0x01))
+        .count();
+    control * 10 > data.len() * 3
+}
+
+/// Classic `offset  hex bytes  |ascii|` dump of `data`, which starts at `base_offset`
+/// in the file. The row holding `marker_offset` is flagged with `>>>`.
+pub fn hex_dump(data: &[u8], base_offset: usize, marker_offset: usize) -> String {
+    let mut out = String::new();
+    for (row_idx, chunk) in data.chunks(HEX_BYTES_PER_ROW).enumerate() {
+        let offset = base_offset + row_idx * HEX_BYTES_PER_ROW;
+        let marker = if (offset..offset + HEX_BYTES_PER_ROW).contains(&marker_offset) { ">>>" } else { "   " };
+
+        let mut hex = String::new();
+        for i in 0..HEX_BYTES_PER_ROW {
+            match chunk.get(i) {
+                Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+                None => hex.push_str("   "),
+            }
+            if i == HEX_BYTES_PER_ROW / 2 - 1 {
+                hex.push(' ');
+            }
+        }
+
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+
+        if row_idx > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("{} {:08x}  {} |{}|", marker, offset, hex, ascii));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b"2024-01-15 INFO ok\n8=FIX.4.4\x019=12\x01\n"));
+        assert!(looks_binary(b"PK\x03\x04\x00\x00"));
+        assert!(looks_binary(&[0x02, 0x03, 0x04, b'a']));
+    }
+
+    #[test]
+    fn test_visualize_whitespace() {
+        assert!(matches!(visualize_whitespace("plain text"), Cow::Borrowed(_)));
+        assert_eq!(visualize_whitespace("a\tb  "), "a→  b··");
+        assert_eq!(visualize_whitespace("\tx"), "→   x");
+    }
+
+    #[test]
+    fn test_display_text_gutter() {
+        let mut preview = FilePreview::new();
+        preview.lines = vec![
+            PreviewLine { number: 9, text: "before".to_string() },
+            PreviewLine { number: 10, text: "match".to_string() },
+        ];
+        preview.target_line_in_preview = Some(1);
+
+        assert_eq!(
+            preview.display_text(true, false).unwrap(),
+            "       9 | before\n>>>   10 | match"
+        );
+        assert_eq!(preview.display_text(false, false).unwrap(), "before\nmatch");
+        assert_eq!(preview.plain_text().unwrap(), "before\nmatch");
+    }
+
+    #[test]
+    fn test_hex_window_built_only_when_shown() {
+        let path = std::env::temp_dir().join(format!("vis-grep-preview-{}.log", std::process::id()));
+        std::fs::write(&path, "a\r\nbb\nccc\n").unwrap();
+
+        let mut preview = FilePreview::new();
+        preview.load_file(&path, 3);
+        assert_eq!(preview.matched_line_text.as_deref(), Some("ccc"));
+        assert!(preview.hex_content.is_none());
+        assert_eq!(preview.hex_source.as_ref().map(|(_, offset)| *offset), Some(6));
+
+        preview.toggle_hex();
+        assert!(preview.hex_content.as_ref().unwrap().starts_with(">>> 00000000"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_hex_dump_rows() {
+        let dump = hex_dump(b"hello\nworld\x01\x02 tail!", 0x10, 0x16);
+        let rows: Vec<&str> = dump.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[0],
+            ">>> 00000010  68 65 6c 6c 6f 0a 77 6f  72 6c 64 01 02 20 74 61  |hello.world.. ta|"
+        );
+        assert!(rows[1].starts_with("    00000020  69 6c 21 "));
+        assert!(rows[1].ends_with("|il!|"));
     }
 }
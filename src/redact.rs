@@ -0,0 +1,77 @@
+use crate::config::RedactionConfig;
+use log::warn;
+use regex::Regex;
+use std::borrow::Cow;
+
+const MASK: &str = "****";
+
+/// Masks sensitive text (account numbers, tokens, emails) right before it is displayed,
+/// copied or exported. Buffers keep the original text so switching redaction off
+/// restores it.
+pub struct Redactor {
+    pub enabled: bool,
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    pub fn from_config(config: &RedactionConfig) -> Self {
+        let patterns = config
+            .patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    warn!("Ignoring invalid redaction pattern '{}': {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            enabled: config.enabled,
+            patterns,
+        }
+    }
+
+    /// Replace every match of every pattern with `****` (no-op while disabled)
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !self.enabled {
+            return Cow::Borrowed(text);
+        }
+
+        let mut result = Cow::Borrowed(text);
+        for pattern in &self.patterns {
+            if let Cow::Owned(replaced) = pattern.replace_all(&result, MASK) {
+                result = Cow::Owned(replaced);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(enabled: bool) -> Redactor {
+        Redactor::from_config(&RedactionConfig {
+            enabled,
+            ..RedactionConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_default_patterns_mask_emails_and_digits() {
+        let text = "account 12345678901 owner jane.doe@example.com qty=100";
+        assert_eq!(
+            redactor(true).redact(text),
+            "account **** owner **** qty=100"
+        );
+    }
+
+    #[test]
+    fn test_disabled_is_passthrough() {
+        let text = "account 12345678901";
+        assert!(matches!(redactor(false).redact(text), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How precisely the tail output shows how long ago each line arrived
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelativeTimeFormat {
+    /// One unit, rounded down: "42s", "1m", "3h"
+    #[default]
+    Compact,
+    /// The two largest units: "42s", "1m30s", "3h05m"
+    Precise,
+    /// Like precise, with milliseconds under a minute: "0.250s", "42.017s"
+    Millis,
+}
+
+impl RelativeTimeFormat {
+    pub const ALL: [RelativeTimeFormat; 3] =
+        [RelativeTimeFormat::Compact, RelativeTimeFormat::Precise, RelativeTimeFormat::Millis];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RelativeTimeFormat::Compact => "1m",
+            RelativeTimeFormat::Precise => "1m30s",
+            RelativeTimeFormat::Millis => "1.250s",
+        }
+    }
+
+    pub fn format(self, age: Duration) -> String {
+        let secs = age.as_secs();
+        match self {
+            RelativeTimeFormat::Compact => crate::format_age(age),
+            RelativeTimeFormat::Millis if secs < 60 => format!("{}.{:03}s", secs, age.subsec_millis()),
+            RelativeTimeFormat::Precise | RelativeTimeFormat::Millis => {
+                if secs < 60 {
+                    format!("{}s", secs)
+                } else if secs < 3600 {
+                    format!("{}m{:02}s", secs / 60, secs % 60)
+                } else if secs < 86400 {
+                    format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+                } else {
+                    format!("{}d{:02}h", secs / 86400, secs % 86400 / 3600)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats() {
+        let age = Duration::from_millis(90_250);
+        assert_eq!(RelativeTimeFormat::Compact.format(age), "1m");
+        assert_eq!(RelativeTimeFormat::Precise.format(age), "1m30s");
+        assert_eq!(RelativeTimeFormat::Millis.format(age), "1m30s");
+        assert_eq!(RelativeTimeFormat::Millis.format(Duration::from_millis(4_017)), "4.017s");
+        assert_eq!(RelativeTimeFormat::Precise.format(Duration::from_secs(3 * 3600 + 5 * 60)), "3h05m");
+    }
+}
@@ -0,0 +1,174 @@
+use crate::search::{LineMatcher, MatchInfo, SearchMode};
+use regex::{NoExpand, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A replacement for the matches of a search, applied line by line
+#[derive(Debug, Clone)]
+pub struct Replacement {
+    regex: Regex,
+    replacement: String,
+    /// Expand `$1`/`${name}` references; off for literal searches so `$` is just a dollar
+    expand: bool,
+}
+
+/// What writing a replacement to one file did
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FileEdit {
+    pub lines_changed: usize,
+    /// Matched lines whose text no longer matches what the search found, left alone
+    pub lines_skipped: usize,
+}
+
+impl Replacement {
+    /// None for fuzzy searches, which have no pattern to substitute
+    pub fn new(matcher: &LineMatcher, mode: SearchMode, replacement: &str) -> Option<Self> {
+        match matcher {
+            LineMatcher::Regex(regex) => Some(Self {
+                regex: regex.clone(),
+                replacement: replacement.to_string(),
+                expand: mode == SearchMode::Regex,
+            }),
+            LineMatcher::Fuzzy { .. } => None,
+        }
+    }
+
+    /// The line with every match replaced
+    pub fn apply(&self, line: &str) -> String {
+        if self.expand {
+            self.regex.replace_all(line, self.replacement.as_str()).into_owned()
+        } else {
+            self.regex.replace_all(line, NoExpand(&self.replacement)).into_owned()
+        }
+    }
+
+    /// Matched lines the replacement would change
+    pub fn changed_lines(&self, matches: &[MatchInfo]) -> usize {
+        matches.iter().filter(|m| self.apply(&m.line_text) != m.line_text).count()
+    }
+
+    /// Apply the replacement to the matched lines of `path` and write it back through a
+    /// temp file in the same directory, so the file is never left half written. Lines
+    /// that changed since the search are skipped; line endings are kept as they were.
+    pub fn write_file(&self, path: &Path, matches: &[MatchInfo]) -> Result<FileEdit, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let expected: HashMap<usize, &str> = matches.iter().map(|m| (m.line_number, m.line_text.as_str())).collect();
+
+        let mut edit = FileEdit::default();
+        let mut output = String::with_capacity(content.len());
+        for (idx, line) in content.split_inclusive('\n').enumerate() {
+            let body = line.strip_suffix('\n').unwrap_or(line);
+            let body = body.strip_suffix('\r').unwrap_or(body);
+            let ending = &line[body.len()..];
+            match expected.get(&(idx + 1)) {
+                Some(&text) if text == body => {
+                    let replaced = self.apply(body);
+                    if replaced != body {
+                        edit.lines_changed += 1;
+                    }
+                    output.push_str(&replaced);
+                    output.push_str(ending);
+                }
+                Some(_) => {
+                    edit.lines_skipped += 1;
+                    output.push_str(line);
+                }
+                None => output.push_str(line),
+            }
+        }
+
+        if edit.lines_changed > 0 {
+            write_atomically(path, &output)?;
+        }
+        Ok(edit)
+    }
+}
+
+/// Write through a temp file renamed over the target. Symlinks are followed so the link
+/// stays a link, and the temp file takes the original's permissions (and owner, where the
+/// platform allows) before it replaces it.
+fn write_atomically(path: &Path, content: &str) -> Result<(), String> {
+    let target = fs::canonicalize(path).map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?;
+    let file_name = target.file_name().ok_or_else(|| format!("Not a file: {}", path.display()))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".vis-grep-tmp");
+    let temp_path: PathBuf = target.with_file_name(temp_name);
+
+    let result = fs::metadata(&target).and_then(|metadata| {
+        fs::write(&temp_path, content)?;
+        fs::set_permissions(&temp_path, metadata.permissions())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            // Only root can give the file away; otherwise it stays owned by us
+            let _ = std::os::unix::fs::chown(&temp_path, Some(metadata.uid()), Some(metadata.gid()));
+        }
+        fs::rename(&temp_path, &target)
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to write {}: {}", path.display(), e));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchEngine;
+
+    fn replacement(query: &str, mode: SearchMode, with: &str) -> Replacement {
+        let matcher = SearchEngine::new().compile_query(query, true, mode).unwrap();
+        Replacement::new(&matcher, mode, with).unwrap()
+    }
+
+    #[test]
+    fn test_apply_expands_groups_for_regex_only() {
+        let regex = replacement(r"user=(\w+)", SearchMode::Regex, "user=<$1>");
+        assert_eq!(regex.apply("login user=bob ok"), "login user=<bob> ok");
+        let literal = replacement("cost", SearchMode::Literal, "$1");
+        assert_eq!(literal.apply("cost=3"), "$1=3");
+    }
+
+    #[test]
+    fn test_write_file_keeps_endings_and_skips_changed_lines() {
+        let path = std::env::temp_dir().join(format!("vis-grep-replace-{}.log", std::process::id()));
+        fs::write(&path, "a secret\r\nplain\nsecret again\n").unwrap();
+        let found = |line_number: usize, text: &str| MatchInfo {
+            line_number,
+            line_text: text.to_string(),
+            column_start: 0,
+            column_end: 0,
+            captures: Vec::new(),
+            occurrences: 1,
+        };
+        // Line 3 changed after the search, so it is left alone
+        let matches = [found(1, "a secret"), found(3, "secret")];
+
+        let edit = replacement("secret", SearchMode::Literal, "***").write_file(&path, &matches).unwrap();
+        assert_eq!(edit, FileEdit { lines_changed: 1, lines_skipped: 1 });
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a ***\r\nplain\nsecret again\n");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_file_through_symlink_keeps_link_and_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("vis-grep-replace-link-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("real.log");
+        let link = dir.join("link.log");
+        fs::write(&target, "token=abc\n").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o640)).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomically(&link, "token=***\n").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "token=***\n");
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o640);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,8 +1,15 @@
+use crate::fuzzy::fuzzy_match;
+use crate::preview::MMAP_THRESHOLD;
+use globset::{GlobBuilder, GlobMatcher};
+use memmap2::Mmap;
 use rayon::prelude::*;
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
 use std::time::{Duration, SystemTime};
 use walkdir::WalkDir;
 
@@ -12,95 +19,397 @@ pub struct MatchInfo {
     pub line_text: String,
     pub column_start: usize,
     pub column_end: usize,
+    /// Text of each capture group (empty for groups that didn't participate); empty
+    /// when the query has no groups
+    pub captures: Vec<String>,
+    /// How many times the query matched on the line (1 for fuzzy queries)
+    pub occurrences: usize,
+}
+
+/// Bounds for `SearchEngine::count_matches`
+#[derive(Debug, Clone, Copy)]
+pub struct CountLimits {
+    pub max_files: usize,
+    pub max_matches: usize,
+}
+
+/// Result of a bounded count; `truncated` means the real count may be higher
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchCount {
+    pub matches: usize,
+    pub truncated: bool,
 }
 
 #[derive(Debug)]
 pub struct SearchResult {
     pub file_path: PathBuf,
     pub matches: Vec<MatchInfo>,
+    /// Best fuzzy score among the matches (fuzzy mode only), used to rank files
+    pub best_score: Option<i64>,
+}
+
+impl SearchResult {
+    pub fn count(&self, unit: CountUnit) -> usize {
+        match unit {
+            CountUnit::Lines => self.matches.len(),
+            CountUnit::Occurrences => self.matches.iter().map(|m| m.occurrences).sum(),
+        }
+    }
+}
+
+/// What match counts count: lines with a match, or every match on them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CountUnit {
+    #[default]
+    Lines,
+    Occurrences,
+}
+
+impl CountUnit {
+    pub fn label(self) -> &'static str {
+        match self {
+            CountUnit::Lines => "lines",
+            CountUnit::Occurrences => "occurrences",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            CountUnit::Lines => CountUnit::Occurrences,
+            CountUnit::Occurrences => CountUnit::Lines,
+        }
+    }
+}
+
+/// How the query is matched against each line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SearchMode {
+    Literal,
+    #[default]
+    Regex,
+    /// Query characters in order with anything between, ranked by closeness
+    Fuzzy,
+}
+
+impl SearchMode {
+    pub const ALL: [SearchMode; 3] = [SearchMode::Literal, SearchMode::Regex, SearchMode::Fuzzy];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchMode::Literal => "Literal",
+            SearchMode::Regex => "Regex",
+            SearchMode::Fuzzy => "Fuzzy",
+        }
+    }
+}
+
+/// A compiled query, ready to run against lines
+#[derive(Debug, Clone)]
+pub enum LineMatcher {
+    Regex(Regex),
+    Fuzzy { query: String, case_sensitive: bool },
+}
+
+/// Where a line matched, as found by a LineMatcher
+struct LineMatch {
+    start: usize,
+    end: usize,
+    score: Option<i64>,
+    captures: Vec<String>,
+    occurrences: usize,
+}
+
+impl LineMatcher {
+    /// The pattern as it actually runs, with the options it was compiled with written as
+    /// inline flags, e.g. `(?i)35=8\|` for a case-insensitive literal `35=8|`
+    pub fn executed_pattern(&self, case_sensitive: bool) -> String {
+        match self {
+            LineMatcher::Regex(regex) => format!("{}{}", inline_flags(case_sensitive), regex.as_str()),
+            LineMatcher::Fuzzy { query, case_sensitive } => format!(
+                "fuzzy '{}'{}",
+                query,
+                if *case_sensitive { ", case sensitive" } else { "" }
+            ),
+        }
+    }
+
+    fn find(&self, line: &str) -> Option<LineMatch> {
+        match self {
+            // Capture groups are only worth the slower captures() call when there are any
+            LineMatcher::Regex(regex) if regex.captures_len() > 1 => {
+                let caps = regex.captures(line)?;
+                let whole = caps.get(0)?;
+                Some(LineMatch {
+                    start: whole.start(),
+                    end: whole.end(),
+                    score: None,
+                    occurrences: regex.find_iter(line).count(),
+                    captures: caps
+                        .iter()
+                        .skip(1)
+                        .map(|group| group.map_or(String::new(), |g| g.as_str().to_string()))
+                        .collect(),
+                })
+            }
+            LineMatcher::Regex(regex) => {
+                let mut found = regex.find_iter(line);
+                let first = found.next()?;
+                Some(LineMatch {
+                    start: first.start(),
+                    end: first.end(),
+                    score: None,
+                    captures: Vec::new(),
+                    occurrences: 1 + found.count(),
+                })
+            }
+            LineMatcher::Fuzzy { query, case_sensitive } => {
+                fuzzy_match(line, query, *case_sensitive).map(|m| LineMatch {
+                    start: m.start,
+                    end: m.end,
+                    score: Some(m.score),
+                    captures: Vec::new(),
+                    occurrences: 1,
+                })
+            }
+        }
+    }
 }
 
-pub struct SearchEngine;
+/// What a running search streams back to the UI
+#[derive(Debug)]
+pub enum SearchEvent {
+    /// Number of files that passed the name/age filters and will be searched
+    FilesScanned(usize),
+    Result(SearchResult),
+}
+
+/// The inputs that determine how a query compiles
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueryKey {
+    query: String,
+    case_sensitive: bool,
+    mode: SearchMode,
+}
+
+#[derive(Clone)]
+pub struct SearchEngine {
+    // Last compiled query, reused while the query and flags are unchanged
+    cached_regex: Option<(QueryKey, Regex)>,
+    /// Match the file name glob regardless of case, so `*.log` also finds `APP.LOG`
+    pub file_pattern_ignore_case: bool,
+    /// Descend into symlinked directories (and read symlinked files) when walking a
+    /// tree. walkdir skips a link back to one of its own ancestors, so loops end.
+    pub follow_symlinks: bool,
+}
 
 impl SearchEngine {
     pub fn new() -> Self {
-        Self
+        Self {
+            cached_regex: None,
+            file_pattern_ignore_case: false,
+            follow_symlinks: true,
+        }
     }
 
+    /// Compile the search query, reusing the previous regex compilation when the query
+    /// and flags haven't changed (e.g. debounced auto-search re-runs).
+    /// Returns the compile error if the query is not a valid regex.
+    pub fn compile_query(
+        &mut self,
+        query: &str,
+        case_sensitive: bool,
+        mode: SearchMode,
+    ) -> Result<LineMatcher, String> {
+        if mode == SearchMode::Fuzzy {
+            return Ok(LineMatcher::Fuzzy {
+                query: query.to_string(),
+                case_sensitive,
+            });
+        }
+
+        let key = QueryKey {
+            query: query.to_string(),
+            case_sensitive,
+            mode,
+        };
+
+        if let Some((cached_key, regex)) = &self.cached_regex {
+            if *cached_key == key {
+                return Ok(LineMatcher::Regex(regex.clone()));
+            }
+        }
+
+        let pattern = if mode == SearchMode::Regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|e| e.to_string())?;
+        self.cached_regex = Some((key, regex.clone()));
+        Ok(LineMatcher::Regex(regex))
+    }
+
+    /// Search files and send each file's result over `sender` as soon as it is done.
+    /// Returns once every file has been scanned, or early once the receiver has been
+    /// dropped; dropping the last sender tells the receiver the search is complete.
     pub fn search(
         &self,
         search_path: &str,
-        file_pattern: &str,
-        query: &str,
-        case_sensitive: bool,
-        use_regex: bool,
+        file_pattern: &FilePattern,
+        matcher: &LineMatcher,
         recursive: bool,
         file_age_hours: Option<u64>,
-    ) -> Vec<SearchResult> {
+        sender: Sender<SearchEvent>,
+    ) {
         let path = Path::new(search_path);
         if !path.exists() {
-            return Vec::new();
+            return;
         }
 
-        let age_cutoff =
-            file_age_hours.map(|hours| SystemTime::now() - Duration::from_secs(hours * 3600));
+        let files = self.collect_files(path, file_pattern, recursive, file_age_hours);
 
-        // Collect files matching the pattern
-        let files: Vec<PathBuf> = if path.is_file() {
-            vec![path.to_path_buf()]
-        } else if recursive {
-            WalkDir::new(path)
-                .follow_links(true)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().is_file())
-                .filter(|e| self.matches_pattern(e.path(), file_pattern))
-                .filter(|e| self.matches_age(e.path(), age_cutoff))
-                .map(|e| e.path().to_path_buf())
-                .collect()
-        } else {
-            std::fs::read_dir(path)
-                .ok()
-                .map(|entries| {
-                    entries
-                        .filter_map(|e| e.ok())
-                        .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
-                        .filter(|e| self.matches_pattern(&e.path(), file_pattern))
-                        .filter(|e| self.matches_age(&e.path(), age_cutoff))
-                        .map(|e| e.path())
-                        .collect()
-                })
-                .unwrap_or_default()
-        };
+        if sender.send(SearchEvent::FilesScanned(files.len())).is_err() {
+            return;
+        }
 
-        // Search in parallel
-        files
-            .par_iter()
-            .filter_map(|file| self.search_file(file, query, case_sensitive, use_regex))
-            .collect()
+        // Search in parallel, streaming results back as each file finishes. A failed send
+        // means the receiver is gone (the search was superseded), so the rest is skipped.
+        let _ = files.par_iter().try_for_each_with(sender, |sender, file| {
+            match self.search_file(file, matcher) {
+                Some(result) => sender.send(SearchEvent::Result(result)).map_err(|_| ()),
+                None => Ok(()),
+            }
+        });
     }
 
-    fn matches_pattern(&self, path: &Path, pattern: &str) -> bool {
-        let file_name = match path.file_name().and_then(|n| n.to_str()) {
-            Some(name) => name,
-            None => return false,
+    /// Like `search`, but the query is matched against each file's name instead of its
+    /// lines. Matching files are sent with no line matches and their contents aren't read.
+    pub fn search_file_names(
+        &self,
+        search_path: &str,
+        file_pattern: &FilePattern,
+        matcher: &LineMatcher,
+        recursive: bool,
+        file_age_hours: Option<u64>,
+        sender: Sender<SearchEvent>,
+    ) {
+        let path = Path::new(search_path);
+        if !path.exists() {
+            return;
+        }
+
+        let files = self.collect_files(path, file_pattern, recursive, file_age_hours);
+        if sender.send(SearchEvent::FilesScanned(files.len())).is_err() {
+            return;
+        }
+
+        for file in files {
+            let Some(found) = file.file_name().and_then(|name| matcher.find(&name.to_string_lossy())) else {
+                continue;
+            };
+            let result = SearchResult { file_path: file, matches: Vec::new(), best_score: found.score };
+            if sender.send(SearchEvent::Result(result)).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Count matching lines under `search_path`, looking at no more than `limits.max_files`
+    /// files and stopping at `limits.max_matches`, for a quick idea of whether a query is
+    /// worth a full search
+    pub fn count_matches(
+        &self,
+        search_path: &str,
+        file_pattern: &FilePattern,
+        matcher: &LineMatcher,
+        recursive: bool,
+        limits: CountLimits,
+    ) -> MatchCount {
+        let mut files = self.walk_files(Path::new(search_path), file_pattern, recursive, None);
+        let mut count = MatchCount {
+            matches: 0,
+            truncated: false,
         };
 
-        // Simple glob pattern matching
-        if pattern == "*" || pattern.is_empty() {
-            return true;
+        for file in files.by_ref().take(limits.max_files) {
+            let Ok(file) = File::open(file) else {
+                continue;
+            };
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if matcher.find(&line).is_some() {
+                    count.matches += 1;
+                    if count.matches >= limits.max_matches {
+                        count.truncated = true;
+                        return count;
+                    }
+                }
+            }
         }
+        // Files past the cap were left unread
+        count.truncated = files.next().is_some();
+        count
+    }
 
-        // Convert simple glob to regex
-        let pattern_regex = pattern
-            .replace(".", "\\.")
-            .replace("*", ".*")
-            .replace("?", ".");
+    /// Files under `path` whose names match the glob and that were modified within
+    /// `file_age_hours`. A file path is returned as-is.
+    pub fn collect_files(
+        &self,
+        path: &Path,
+        file_pattern: &FilePattern,
+        recursive: bool,
+        file_age_hours: Option<u64>,
+    ) -> Vec<PathBuf> {
+        self.walk_files(path, file_pattern, recursive, file_age_hours).collect()
+    }
 
-        Regex::new(&format!("^{}$", pattern_regex))
-            .ok()
-            .map(|re| re.is_match(file_name))
-            .unwrap_or(false)
+    /// Like `collect_files`, but yields the files as the directory walk finds them, so a
+    /// caller that only wants the first few doesn't walk the whole tree
+    fn walk_files<'a>(
+        &'a self,
+        path: &'a Path,
+        file_pattern: &'a FilePattern,
+        recursive: bool,
+        file_age_hours: Option<u64>,
+    ) -> Box<dyn Iterator<Item = PathBuf> + 'a> {
+        let age_cutoff =
+            file_age_hours.map(|hours| SystemTime::now() - Duration::from_secs(hours * 3600));
+
+        if path.is_file() {
+            Box::new(std::iter::once(path.to_path_buf()))
+        } else if recursive || file_pattern.path_glob {
+            // A path glob says how deep to look, so it always walks the tree
+            Box::new(
+                WalkDir::new(path)
+                    .follow_links(self.follow_symlinks)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_file())
+                    .filter(move |e| file_pattern.matches(e.path(), path))
+                    .filter(move |e| self.matches_age(e.path(), age_cutoff))
+                    .map(|e| e.path().to_path_buf()),
+            )
+        } else {
+            Box::new(
+                std::fs::read_dir(path)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+                    .filter(move |e| file_pattern.matches(&e.path(), path))
+                    .filter(move |e| self.matches_age(&e.path(), age_cutoff))
+                    .map(|e| e.path()),
+            )
+        }
+    }
+
+    /// Compile a file name glob for one search, matching regardless of case when
+    /// `file_pattern_ignore_case` is set
+    pub fn file_pattern(&self, pattern: &str) -> Result<FilePattern, String> {
+        FilePattern::new(pattern, self.file_pattern_ignore_case)
     }
 
     fn matches_age(&self, path: &Path, cutoff: Option<SystemTime>) -> bool {
@@ -118,55 +427,467 @@ impl SearchEngine {
         false // If we can't get metadata, exclude the file
     }
 
-    fn search_file(
-        &self,
-        file_path: &Path,
-        query: &str,
-        case_sensitive: bool,
-        use_regex: bool,
-    ) -> Option<SearchResult> {
-        let regex = if use_regex {
-            let pattern = if case_sensitive {
-                query.to_string()
-            } else {
-                format!("(?i){}", query)
-            };
-            Regex::new(&pattern).ok()?
-        } else {
-            let escaped = regex::escape(query);
-            let pattern = if case_sensitive {
-                escaped
-            } else {
-                format!("(?i){}", escaped)
-            };
-            Regex::new(&pattern).ok()?
-        };
-
+    fn search_file(&self, file_path: &Path, matcher: &LineMatcher) -> Option<SearchResult> {
         let file = File::open(file_path).ok()?;
-        let reader = BufReader::new(file);
+        let file_size = file.metadata().ok()?.len();
 
-        let mut matches = Vec::new();
+        let (matches, best_score) = if file_size >= MMAP_THRESHOLD {
+            // Large files: scan the mapped bytes directly instead of allocating per line
+            let mmap = unsafe { Mmap::map(&file).ok()? };
+            Self::search_bytes(&mmap, matcher)
+        } else {
+            let reader = BufReader::new(file);
+            let mut matches = Vec::new();
+            let mut best_score = None;
 
-        for (line_idx, line) in reader.lines().enumerate() {
-            if let Ok(line_text) = line {
-                if let Some(mat) = regex.find(&line_text) {
-                    matches.push(MatchInfo {
-                        line_number: line_idx + 1,
-                        line_text: line_text.clone(),
-                        column_start: mat.start(),
-                        column_end: mat.end(),
-                    });
+            for (line_idx, line) in reader.lines().enumerate() {
+                if let Ok(line_text) = line {
+                    if let Some(found) = matcher.find(&line_text) {
+                        best_score = best_score.max(found.score);
+                        matches.push(MatchInfo {
+                            line_number: line_idx + 1,
+                            line_text: line_text.clone(),
+                            column_start: found.start,
+                            column_end: found.end,
+                            captures: found.captures,
+                            occurrences: found.occurrences,
+                        });
+                    }
                 }
             }
-        }
+            (matches, best_score)
+        };
 
         if !matches.is_empty() {
             Some(SearchResult {
                 file_path: file_path.to_path_buf(),
                 matches,
+                best_score,
             })
         } else {
             None
         }
     }
+
+    /// Scan a byte buffer line by line, splitting on '\n' and dropping a trailing '\r'
+    /// the same way BufRead::lines does. Invalid UTF-8 is matched lossily.
+    fn search_bytes(data: &[u8], matcher: &LineMatcher) -> (Vec<MatchInfo>, Option<i64>) {
+        let mut matches = Vec::new();
+        let mut best_score = None;
+        let mut line_start = 0;
+        let mut line_number = 1;
+
+        while line_start < data.len() {
+            let line_end = data[line_start..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(data.len(), |offset| line_start + offset);
+
+            let mut line_bytes = &data[line_start..line_end];
+            if line_bytes.last() == Some(&b'\r') {
+                line_bytes = &line_bytes[..line_bytes.len() - 1];
+            }
+
+            let line_text = String::from_utf8_lossy(line_bytes);
+            if let Some(found) = matcher.find(&line_text) {
+                best_score = best_score.max(found.score);
+                matches.push(MatchInfo {
+                    line_number,
+                    line_text: line_text.to_string(),
+                    column_start: found.start,
+                    column_end: found.end,
+                    captures: found.captures,
+                    occurrences: found.occurrences,
+                });
+            }
+
+            line_start = line_end + 1;
+            line_number += 1;
+        }
+
+        (matches, best_score)
+    }
+}
+
+/// Why the query won't compile in this mode, checked as it is typed
+pub fn query_error(query: &str, mode: SearchMode) -> Option<String> {
+    if mode != SearchMode::Regex || query.is_empty() {
+        return None;
+    }
+    Regex::new(query).err().map(|e| e.to_string())
+}
+
+/// The inline flag group the case option amounts to: "(?i)", or empty when case
+/// sensitive. Search runs line by line, so multi-line flags would never apply.
+pub fn inline_flags(case_sensitive: bool) -> &'static str {
+    if case_sensitive {
+        ""
+    } else {
+        "(?i)"
+    }
+}
+
+/// The last line of a regex parse error (e.g. "error: unclosed group"), short enough to
+/// show inline; the full message points at the offending character
+pub fn error_summary(message: &str) -> &str {
+    message.lines().last().unwrap_or(message).trim()
+}
+
+/// A compiled file name glob. A plain glob like `*.log` matches the file name; one
+/// containing `/` (e.g. `src/**/*.rs`) matches the path relative to the search root.
+/// `**/` spans any number of directories, `*` and `?` stay within one path segment.
+#[derive(Debug, Clone)]
+pub struct FilePattern {
+    // None for `*` or an empty pattern, which match every file
+    glob: Option<GlobMatcher>,
+    path_glob: bool,
+}
+
+impl FilePattern {
+    pub fn new(pattern: &str, ignore_case: bool) -> Result<Self, String> {
+        if pattern == "*" || pattern.is_empty() {
+            return Ok(Self { glob: None, path_glob: false });
+        }
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            glob: Some(glob.compile_matcher()),
+            path_glob: pattern.contains('/'),
+        })
+    }
+
+    /// True if the file under `root` matches the glob
+    pub fn matches(&self, path: &Path, root: &Path) -> bool {
+        let Some(glob) = &self.glob else {
+            return true;
+        };
+
+        if self.path_glob {
+            match path.strip_prefix(root) {
+                Ok(relative) => glob.is_match(
+                    relative
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                ),
+                Err(_) => false,
+            }
+        } else {
+            path.file_name().is_some_and(|name| glob.is_match(name))
+        }
+    }
+}
+
+/// Group the results at `file_indices` by parent directory, keeping both the
+/// directories and the files within each in result order
+pub fn group_by_directory(results: &[SearchResult], file_indices: &[usize]) -> Vec<(PathBuf, Vec<usize>)> {
+    let mut groups: Vec<(PathBuf, Vec<usize>)> = Vec::new();
+    for &file_idx in file_indices {
+        let dir = results[file_idx]
+            .file_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        match groups.iter_mut().find(|(group_dir, _)| *group_dir == dir) {
+            Some((_, files)) => files.push(file_idx),
+            None => groups.push((dir, vec![file_idx])),
+        }
+    }
+    groups
+}
+
+/// A distinct matched line and every place it occurred
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniqueLine {
+    pub text: String,
+    /// (file index, match index) into the results
+    pub occurrences: Vec<(usize, usize)>,
+}
+
+/// Collapse the matches of the results at `file_indices` by trimmed line text, most
+/// frequent first and in order of first appearance among equals
+pub fn unique_lines(results: &[SearchResult], file_indices: &[usize]) -> Vec<UniqueLine> {
+    let mut lines: Vec<UniqueLine> = Vec::new();
+    let mut index_by_text: HashMap<&str, usize> = HashMap::new();
+    for &file_idx in file_indices {
+        for (match_idx, m) in results[file_idx].matches.iter().enumerate() {
+            let text = m.line_text.trim();
+            let idx = *index_by_text.entry(text).or_insert_with(|| {
+                lines.push(UniqueLine {
+                    text: text.to_string(),
+                    occurrences: Vec::new(),
+                });
+                lines.len() - 1
+            });
+            lines[idx].occurrences.push((file_idx, match_idx));
+        }
+    }
+    // Stable sort keeps first-appearance order among equal counts
+    lines.sort_by_key(|line| std::cmp::Reverse(line.occurrences.len()));
+    lines
+}
+
+/// Where a selected match sits among the matched lines, 1-based
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchPosition {
+    pub in_file: usize,
+    pub file_total: usize,
+    pub overall: usize,
+    pub total: usize,
+}
+
+/// Position of match `match_idx` of file `file_idx`; None if there is no such match
+pub fn match_position(results: &[SearchResult], file_idx: usize, match_idx: usize) -> Option<MatchPosition> {
+    let file_total = results.get(file_idx)?.matches.len();
+    if match_idx >= file_total {
+        return None;
+    }
+    let before: usize = results[..file_idx].iter().map(|r| r.matches.len()).sum();
+    Some(MatchPosition {
+        in_file: match_idx + 1,
+        file_total,
+        overall: before + match_idx + 1,
+        total: results.iter().map(|r| r.matches.len()).sum(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_by_directory_keeps_result_order() {
+        let result = |path: &str| SearchResult {
+            file_path: PathBuf::from(path),
+            matches: Vec::new(),
+            best_score: None,
+        };
+        let results = vec![result("/svc/b/app.log"), result("/svc/a/app.log"), result("/svc/b/err.log")];
+
+        let groups = group_by_directory(&results, &[0, 1, 2]);
+        assert_eq!(
+            groups,
+            vec![(PathBuf::from("/svc/b"), vec![0, 2]), (PathBuf::from("/svc/a"), vec![1])]
+        );
+        // Filtered-out files leave no empty directory behind
+        assert_eq!(group_by_directory(&results, &[1]), vec![(PathBuf::from("/svc/a"), vec![1])]);
+    }
+
+    #[test]
+    fn test_unique_lines_counts_trimmed_text() {
+        let matched = |line_text: &str| MatchInfo {
+            line_number: 1,
+            line_text: line_text.to_string(),
+            column_start: 0,
+            column_end: 0,
+            captures: Vec::new(),
+            occurrences: 1,
+        };
+        let results = vec![
+            SearchResult {
+                file_path: PathBuf::from("a.log"),
+                matches: vec![matched("ERROR disk full"), matched("ERROR timeout")],
+                best_score: None,
+            },
+            SearchResult {
+                file_path: PathBuf::from("b.log"),
+                matches: vec![matched("  ERROR timeout\n")],
+                best_score: None,
+            },
+        ];
+
+        assert_eq!(
+            match_position(&results, 1, 0),
+            Some(MatchPosition { in_file: 1, file_total: 1, overall: 3, total: 3 })
+        );
+        assert_eq!(match_position(&results, 0, 2), None);
+
+        let unique = unique_lines(&results, &[0, 1]);
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[0].text, "ERROR timeout");
+        assert_eq!(unique[0].occurrences, vec![(0, 1), (1, 0)]);
+        assert_eq!(unique[1].occurrences, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_file_pattern_case() {
+        let mut engine = SearchEngine::new();
+        let root = Path::new("/var/log");
+        let path = Path::new("/var/log/APP.LOG");
+        assert!(!engine.file_pattern("*.log").unwrap().matches(path, root));
+        engine.file_pattern_ignore_case = true;
+        assert!(engine.file_pattern("*.log").unwrap().matches(path, root));
+    }
+
+    #[test]
+    fn test_path_globs_match_relative_path() {
+        let root = Path::new("/repo");
+        let matches =
+            |path: &str, pattern: &str| FilePattern::new(pattern, false).unwrap().matches(Path::new(path), root);
+
+        assert!(matches("/repo/src/main.rs", "src/**/*.rs"));
+        assert!(matches("/repo/src/widgets/text_viewer.rs", "src/**/*.rs"));
+        assert!(!matches("/repo/tests/main.rs", "src/**/*.rs"));
+        assert!(matches("/repo/a/b/app.log", "**/app.log"));
+        assert!(!matches("/repo/src/widgets/mod.rs", "src/*.rs"));
+
+        // Plain globs still match the file name at any depth
+        assert!(matches("/repo/deep/dir/app.log", "*.log"));
+        assert!(matches("/repo/app.1.log", "app.?.log"));
+        assert!(matches("/repo/app2.log", "app[0-9].log"));
+        assert!(!matches("/repo/app2.log", "app[!0-9].log"));
+
+        // Class contents are literal characters, not regex syntax
+        assert!(matches("/repo/app\\.log", "app[\\d].log"));
+        assert!(!matches("/repo/app7.log", "app[\\d].log"));
+    }
+
+    #[test]
+    fn test_invalid_file_pattern() {
+        let error = FilePattern::new("app[.log", false).unwrap_err();
+        assert!(error.contains("app[.log"), "{}", error);
+    }
+
+    #[test]
+    fn test_compile_query_literal_is_escaped() {
+        let mut engine = SearchEngine::new();
+        let matcher = engine.compile_query("a.b", true, SearchMode::Literal).unwrap();
+        assert!(matcher.find("xa.by").is_some());
+        assert!(matcher.find("axb").is_none());
+    }
+
+    #[test]
+    fn test_compile_query_case_insensitive() {
+        let mut engine = SearchEngine::new();
+        let matcher = engine.compile_query("error", false, SearchMode::Regex).unwrap();
+        assert!(matcher.find("An ERROR occurred").is_some());
+    }
+
+    #[test]
+    fn test_compile_query_reuses_cache() {
+        let mut engine = SearchEngine::new();
+        engine.compile_query("35=8", false, SearchMode::Regex).unwrap();
+        assert!(!engine.cached_regex.as_ref().unwrap().0.case_sensitive);
+
+        // Changing a flag recompiles
+        engine.compile_query("35=8", true, SearchMode::Regex).unwrap();
+        assert!(engine.cached_regex.as_ref().unwrap().0.case_sensitive);
+    }
+
+    #[test]
+    fn test_executed_pattern() {
+        let mut engine = SearchEngine::new();
+        let matcher = engine.compile_query("35=8|", false, SearchMode::Literal).unwrap();
+        assert_eq!(matcher.executed_pattern(false), r"(?i)35=8\|");
+        assert_eq!(matcher.executed_pattern(true), r"35=8\|");
+    }
+
+    #[test]
+    fn test_count_matches_stops_at_limit() {
+        let dir = std::env::temp_dir().join(format!("vis-grep-count-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.log"), "ERROR one\nok\nERROR two\n").unwrap();
+
+        let mut engine = SearchEngine::new();
+        let matcher = engine.compile_query("error", false, SearchMode::Literal).unwrap();
+        let path = dir.to_string_lossy();
+        let logs = engine.file_pattern("*.log").unwrap();
+        let count = |max_files, max_matches| {
+            engine.count_matches(&path, &logs, &matcher, false, CountLimits { max_files, max_matches })
+        };
+        assert_eq!(count(10, 10), MatchCount { matches: 2, truncated: false });
+        assert_eq!(count(10, 1), MatchCount { matches: 1, truncated: true });
+
+        std::fs::write(dir.join("b.log"), "ERROR three\n").unwrap();
+        let capped = count(1, 10);
+        assert!(capped.truncated);
+        assert!(capped.matches == 1 || capped.matches == 2, "{:?}", capped);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_search_file_names_skips_contents() {
+        let dir = std::env::temp_dir().join(format!("vis-grep-names-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("gateway.log"), "nothing here\n").unwrap();
+        std::fs::write(dir.join("app.log"), "gateway down\n").unwrap();
+
+        let mut engine = SearchEngine::new();
+        let matcher = engine.compile_query("gateway", false, SearchMode::Literal).unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let logs = engine.file_pattern("*.log").unwrap();
+        engine.search_file_names(&dir.to_string_lossy(), &logs, &matcher, false, None, sender);
+
+        let found: Vec<_> = receiver.iter().filter_map(|event| match event {
+            SearchEvent::Result(result) => Some(result),
+            SearchEvent::FilesScanned(_) => None,
+        }).collect();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].file_path.ends_with("gateway.log"));
+        assert!(found[0].matches.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks_toggle() {
+        let dir = std::env::temp_dir().join(format!("vis-grep-links-{}", std::process::id()));
+        let target = dir.join("target");
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(target.join("a.log"), "x").unwrap();
+        let root = dir.join("root");
+        std::fs::create_dir_all(&root).unwrap();
+        std::os::unix::fs::symlink(&target, root.join("linked")).unwrap();
+        // A link back up the tree must not loop forever
+        std::os::unix::fs::symlink(&root, root.join("loop")).unwrap();
+
+        let mut engine = SearchEngine::new();
+        let logs = engine.file_pattern("*.log").unwrap();
+        assert_eq!(engine.collect_files(&root, &logs, true, None), vec![root.join("linked/a.log")]);
+        engine.follow_symlinks = false;
+        assert!(engine.collect_files(&root, &logs, true, None).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_search_bytes_line_numbers() {
+        let matcher = LineMatcher::Regex(Regex::new("ERROR").unwrap());
+        let data = b"INFO start\r\nERROR one\r\nINFO\n\nERROR \xff two";
+        let (matches, best_score) = SearchEngine::search_bytes(data, &matcher);
+        assert_eq!(best_score, None);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].line_text, "ERROR one");
+        assert_eq!(matches[1].line_number, 5);
+        assert_eq!(matches[1].column_start, 0);
+
+        let matcher = LineMatcher::Regex(Regex::new("^ERROR|two").unwrap());
+        let (matches, _) = SearchEngine::search_bytes(b"ERROR one ERROR two two", &matcher);
+        assert_eq!(matches[0].occurrences, 3);
+    }
+
+    #[test]
+    fn test_capture_groups() {
+        let matcher = LineMatcher::Regex(Regex::new(r"ClOrdID=(\w+).*Price=([\d.]+)|(never)").unwrap());
+        let found = matcher.find("35=D|ClOrdID=ABC1|44=1|Price=101.5|").unwrap();
+        assert_eq!(found.captures, vec!["ABC1", "101.5", ""]);
+        assert_eq!(found.start, 5);
+    }
+
+    #[test]
+    fn test_compile_query_invalid_regex() {
+        let mut engine = SearchEngine::new();
+        let error = engine.compile_query("(unclosed", false, SearchMode::Regex).unwrap_err();
+        assert_eq!(error_summary(&error), "error: unclosed group");
+        assert_eq!(error_summary(&query_error("(unclosed", SearchMode::Regex).unwrap()), "error: unclosed group");
+        assert_eq!(query_error("(unclosed", SearchMode::Literal), None);
+    }
 }
@@ -0,0 +1,131 @@
+use crate::config::Config;
+use crate::search::SearchMode;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Which mode the session was in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionMode {
+    #[default]
+    Grep,
+    Tail,
+}
+
+/// Grep mode inputs as they were left
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrepSession {
+    pub search_path: String,
+    pub file_pattern: String,
+    pub file_pattern_ignore_case: bool,
+    pub search_query: String,
+    pub case_sensitive: bool,
+    pub search_mode: SearchMode,
+    pub recursive: bool,
+    /// Missing from sessions saved before the option existed, which always followed links
+    #[serde(default = "default_follow_symlinks")]
+    pub follow_symlinks: bool,
+    pub file_age_hours: Option<u64>,
+    /// Match the query against file names rather than contents
+    pub file_names_only: bool,
+}
+
+fn default_follow_symlinks() -> bool {
+    true
+}
+
+impl GrepSession {
+    /// One line per parameter, for tooltips
+    pub fn describe(&self) -> String {
+        let mut lines = vec![
+            format!("Query: {}", self.search_query),
+            format!("Path: {}", self.search_path),
+            format!(
+                "Files: {}{}",
+                self.file_pattern,
+                if self.file_pattern_ignore_case { " (any case)" } else { "" }
+            ),
+            format!(
+                "{}{}{}",
+                self.search_mode.label(),
+                if self.case_sensitive { ", case sensitive" } else { "" },
+                match (self.recursive, self.follow_symlinks) {
+                    (true, true) => ", recursive",
+                    (true, false) => ", recursive without following links",
+                    (false, _) => "",
+                },
+            ),
+        ];
+        if let Some(hours) = self.file_age_hours {
+            lines.push(format!("Modified within {}h", hours));
+        }
+        if self.file_names_only {
+            lines.push("File names only".to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+/// What was open when the app last exited, restored on the next launch without
+/// arguments when `ui.restore_session` is on. Kept in session.yaml next to the config
+/// so the config file itself is only written by explicit changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub mode: SessionMode,
+    pub tail_layout: Option<PathBuf>,
+    /// Tailed files outside the layout (or all of them without one)
+    pub tail_files: Vec<PathBuf>,
+    pub grep: GrepSession,
+    pub font_size: Option<f32>,
+}
+
+impl Session {
+    pub fn session_path() -> Option<PathBuf> {
+        Config::config_path().and_then(|path| path.parent().map(|dir| dir.join("session.yaml")))
+    }
+
+    /// The saved session, if there is one that parses
+    pub fn load() -> Option<Self> {
+        let path = Self::session_path()?;
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_yaml::from_str(&content) {
+            Ok(session) => {
+                info!("Restoring session from {:?}", path);
+                Some(session)
+            }
+            Err(e) => {
+                warn!("Failed to parse session file {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::session_path().ok_or("Could not determine session path")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let yaml = serde_yaml::to_string(self).map_err(|e| format!("Failed to serialize session: {}", e))?;
+        fs::write(&path, yaml).map_err(|e| format!("Failed to write session file: {}", e))?;
+        info!("Saved session to {:?}", path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_session_uses_defaults() {
+        let session: Session = serde_yaml::from_str("mode: tail\ntail_files: [/var/log/app.log]\n").unwrap();
+        assert_eq!(session.mode, SessionMode::Tail);
+        assert_eq!(session.tail_files, vec![PathBuf::from("/var/log/app.log")]);
+        assert!(session.tail_layout.is_none());
+        assert_eq!(session.grep.search_mode, SearchMode::Regex);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::log_parser::LogLevel;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Ten-second buckets over the last five minutes
+pub const BUCKET_WIDTH: Duration = Duration::from_secs(10);
+pub const BUCKET_COUNT: usize = 30;
+
+/// Lines that arrived during one time bucket
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelBucket {
+    /// Bucket number counted from the histogram's origin
+    index: u64,
+    pub lines: usize,
+    pub warnings: usize,
+    pub errors: usize,
+    /// LogLine::seq of the first line in the bucket, for jumping the output there
+    pub first_seq: u64,
+}
+
+/// Rolling histogram of warning/error counts per arrival-time bucket, drawn as a
+/// sparkline above the tail output so a spike stands out before reading any lines
+pub struct LevelHistogram {
+    origin: Instant,
+    buckets: VecDeque<LevelBucket>,
+}
+
+impl LevelHistogram {
+    pub fn new(origin: Instant) -> Self {
+        Self {
+            origin,
+            buckets: VecDeque::new(),
+        }
+    }
+
+    fn bucket_index(&self, now: Instant) -> u64 {
+        (now.saturating_duration_since(self.origin).as_millis() / BUCKET_WIDTH.as_millis()) as u64
+    }
+
+    /// Count a line that arrived at `now`
+    pub fn record(&mut self, now: Instant, seq: u64, level: LogLevel) {
+        let index = self.bucket_index(now);
+        if self.buckets.back().is_none_or(|bucket| bucket.index != index) {
+            self.buckets.push_back(LevelBucket {
+                index,
+                lines: 0,
+                warnings: 0,
+                errors: 0,
+                first_seq: seq,
+            });
+        }
+        if let Some(bucket) = self.buckets.back_mut() {
+            bucket.lines += 1;
+            match level {
+                LogLevel::Warn => bucket.warnings += 1,
+                LogLevel::Error | LogLevel::Fatal => bucket.errors += 1,
+                _ => {}
+            }
+        }
+
+        while self.buckets.front().is_some_and(|bucket| index - bucket.index >= BUCKET_COUNT as u64) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// One slot per bucket in the window ending at `now`, oldest first; None where nothing arrived
+    pub fn slots(&self, now: Instant) -> Vec<Option<&LevelBucket>> {
+        let current = self.bucket_index(now);
+        let mut slots = vec![None; BUCKET_COUNT];
+        for bucket in &self.buckets {
+            let age = current.saturating_sub(bucket.index) as usize;
+            if age < BUCKET_COUNT {
+                slots[BUCKET_COUNT - 1 - age] = Some(bucket);
+            }
+        }
+        slots
+    }
+
+    /// Lines per second over the last `buckets` completed buckets (the one still filling
+    /// is left out so the rate doesn't dip at each bucket boundary)
+    pub fn lines_per_sec(&self, now: Instant, buckets: usize) -> f32 {
+        let current = self.bucket_index(now);
+        let lines: usize = self
+            .buckets
+            .iter()
+            .filter(|bucket| bucket.index < current && current - bucket.index <= buckets as u64)
+            .map(|bucket| bucket.lines)
+            .sum();
+        lines as f32 / (BUCKET_WIDTH.as_secs_f32() * buckets as f32)
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_land_in_time_buckets() {
+        let origin = Instant::now();
+        let mut histogram = LevelHistogram::new(origin);
+        histogram.record(origin, 0, LogLevel::Error);
+        histogram.record(origin + Duration::from_secs(1), 1, LogLevel::Info);
+        histogram.record(origin + Duration::from_secs(25), 2, LogLevel::Warn);
+
+        let slots = histogram.slots(origin + Duration::from_secs(25));
+        let newest = slots[BUCKET_COUNT - 1].unwrap();
+        assert_eq!((newest.lines, newest.warnings, newest.errors, newest.first_seq), (1, 1, 0, 2));
+        assert!(slots[BUCKET_COUNT - 2].is_none());
+        let oldest = slots[BUCKET_COUNT - 3].unwrap();
+        assert_eq!((oldest.lines, oldest.errors, oldest.first_seq), (2, 1, 0));
+    }
+
+    #[test]
+    fn test_lines_per_sec_skips_current_bucket() {
+        let origin = Instant::now();
+        let mut histogram = LevelHistogram::new(origin);
+        for seq in 0..20 {
+            histogram.record(origin, seq, LogLevel::Info);
+        }
+        histogram.record(origin + BUCKET_WIDTH, 20, LogLevel::Info);
+
+        assert_eq!(histogram.lines_per_sec(origin + BUCKET_WIDTH, 1), 2.0);
+        assert_eq!(histogram.lines_per_sec(origin + BUCKET_WIDTH * 2, 2), 1.05);
+    }
+
+    #[test]
+    fn test_old_buckets_roll_off() {
+        let origin = Instant::now();
+        let mut histogram = LevelHistogram::new(origin);
+        histogram.record(origin, 0, LogLevel::Error);
+        histogram.record(origin + BUCKET_WIDTH * BUCKET_COUNT as u32, 1, LogLevel::Info);
+
+        assert_eq!(histogram.buckets.len(), 1);
+        let now = origin + BUCKET_WIDTH * BUCKET_COUNT as u32;
+        assert_eq!(histogram.slots(now).iter().flatten().map(|b| b.errors).sum::<usize>(), 0);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::format_bytes;
+use crate::log_parser::LogLevel;
+use crate::util::UtcDateTime;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Level columns in the order they're reported, most severe first
+const LEVELS: [(LogLevel, &str); 7] = [
+    (LogLevel::Fatal, "FATAL"),
+    (LogLevel::Error, "ERROR"),
+    (LogLevel::Warn, "WARN"),
+    (LogLevel::Info, "INFO"),
+    (LogLevel::Debug, "DEBUG"),
+    (LogLevel::Trace, "TRACE"),
+    (LogLevel::Unknown, "other"),
+];
+
+/// One tailed file as it stood when the report was taken
+#[derive(Debug, Clone)]
+pub struct FileStatus {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    /// "active", "idle", "paused", "muted" or "missing"
+    pub state: &'static str,
+    pub lines_read: usize,
+    pub bytes_read: u64,
+    /// Lines seen at each level since tailing started
+    pub level_totals: HashMap<LogLevel, usize>,
+}
+
+/// A point-in-time snapshot of tail mode, written out as markdown to paste into an
+/// incident channel or ticket
+#[derive(Debug, Clone)]
+pub struct StatusReport {
+    pub title: String,
+    pub generated: SystemTime,
+    pub files: Vec<FileStatus>,
+    pub lines_received: usize,
+    pub lines_in_buffer: usize,
+    pub lines_dropped: usize,
+}
+
+impl StatusReport {
+    pub fn to_markdown(&self) -> String {
+        // Only the levels some file actually logged get a column
+        let levels: Vec<(LogLevel, &str)> = LEVELS
+            .into_iter()
+            .filter(|(level, _)| self.files.iter().any(|file| file.level_totals.get(level).is_some_and(|&n| n > 0)))
+            .collect();
+
+        let mut out = format!("# {}\n\nGenerated {}\n\n", self.title, format_utc(self.generated));
+
+        out.push_str("| File | Status | Size | Lines read | Bytes read |");
+        for (_, name) in &levels {
+            out.push_str(&format!(" {} |", name));
+        }
+        out.push_str(" Path |\n|---|---|---:|---:|---:|");
+        out.push_str(&"---:|".repeat(levels.len()));
+        out.push_str("---|\n");
+
+        for file in &self.files {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |",
+                escape_cell(&file.name),
+                file.state,
+                format_bytes(file.size),
+                file.lines_read,
+                format_bytes(file.bytes_read)
+            ));
+            for (level, _) in &levels {
+                out.push_str(&format!(" {} |", file.level_totals.get(level).copied().unwrap_or(0)));
+            }
+            out.push_str(&format!(" `{}` |\n", escape_cell(&file.path)));
+        }
+
+        let active = self.files.iter().filter(|file| file.state == "active").count();
+        out.push_str(&format!(
+            "\n{} files ({} active). {} lines received, {} in the output buffer, {} dropped from it.\n",
+            self.files.len(),
+            active,
+            self.lines_received,
+            self.lines_in_buffer,
+            self.lines_dropped
+        ));
+        out
+    }
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// "YYYY-MM-DD HH:MM:SS UTC"
+fn format_utc(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let time = UtcDateTime::from_unix_secs(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        time.year, time.month, time.day, time.hour, time.minute, time.second
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_markdown_report() {
+        let report = StatusReport {
+            title: "Trading".to_string(),
+            generated: UNIX_EPOCH + Duration::from_secs(1_709_210_096),
+            files: vec![FileStatus {
+                name: "app.log".to_string(),
+                path: "/var/log/app.log".to_string(),
+                size: 2048,
+                state: "active",
+                lines_read: 10,
+                bytes_read: 512,
+                level_totals: HashMap::from([(LogLevel::Error, 2), (LogLevel::Info, 8)]),
+            }],
+            lines_received: 10,
+            lines_in_buffer: 10,
+            lines_dropped: 0,
+        };
+
+        let markdown = report.to_markdown();
+        assert!(markdown.starts_with("# Trading\n\nGenerated 2024-02-29 12:34:56 UTC\n"));
+        assert!(markdown.contains("| File | Status | Size | Lines read | Bytes read | ERROR | INFO | Path |\n"));
+        assert!(markdown.contains("| app.log | active | 2.0 KB | 10 | 512 B | 2 | 8 | `/var/log/app.log` |\n"));
+        assert!(markdown.contains("1 files (1 active)"));
+    }
+}
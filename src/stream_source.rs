@@ -0,0 +1,590 @@
+use crate::log_parser::LogLevel;
+use crate::util::UtcDateTime;
+use log::{info, warn};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Wait this long after a stream closes before starting it again
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Lines kept per stream for the preview, which has no file to read back from
+const HISTORY_LINES: usize = 10_000;
+
+/// How long a FIFO reader sleeps when no writer is connected or nothing was written
+const FIFO_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A tailed entry that isn't a local file, named in the files list by a URL
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamKind {
+    /// `ssh://[user@]host[:port]/path`: `tail -f` of a file on another machine
+    Ssh {
+        destination: String,
+        port: Option<u16>,
+        path: String,
+    },
+    /// `journal://unit`: a systemd unit's journal through `journalctl`
+    Journal { unit: String },
+    /// A named pipe, which has no size to poll; read as writers send lines
+    Fifo { path: PathBuf },
+}
+
+impl StreamKind {
+    /// The stream a files-list entry names, or None for a plain path
+    pub fn parse(spec: &str) -> Option<Self> {
+        if let Some(unit) = spec.strip_prefix("journal://") {
+            let unit = unit.trim_matches('/');
+            return (!unit.is_empty()).then(|| StreamKind::Journal { unit: unit.to_string() });
+        }
+
+        let rest = spec.strip_prefix("ssh://")?;
+        let (authority, path) = rest.split_once('/')?;
+        if path.is_empty() {
+            return None;
+        }
+        let (destination, port) = match authority.rsplit_once(':') {
+            Some((destination, port)) => (destination, Some(port.parse().ok()?)),
+            None => (authority, None),
+        };
+        let host = destination.rsplit('@').next().unwrap_or(destination);
+        // A leading '-' would reach ssh as an option such as -oProxyCommand=...
+        if host.is_empty() || destination.starts_with('-') {
+            return None;
+        }
+        Some(StreamKind::Ssh {
+            destination: destination.to_string(),
+            port,
+            path: format!("/{}", path),
+        })
+    }
+
+    /// Name for the file list, e.g. `app.log@web1`
+    pub fn display_name(&self) -> String {
+        match self {
+            StreamKind::Ssh { destination, path, .. } => {
+                let file = path.rsplit('/').next().unwrap_or(path);
+                let host = destination.rsplit('@').next().unwrap_or(destination);
+                format!("{}@{}", file, host)
+            }
+            StreamKind::Journal { unit } => format!("journal:{}", unit),
+            StreamKind::Fifo { path } => path
+                .file_name()
+                .map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string()),
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            StreamKind::Ssh { .. } => "SSH",
+            StreamKind::Journal { .. } => "JOURNAL",
+            StreamKind::Fifo { .. } => "FIFO",
+        }
+    }
+
+    /// The command whose stdout is the stream. The first connection also shows what is
+    /// already there (the whole remote file, the journal's last few entries); a reconnect
+    /// only picks up new lines so nothing is shown twice. None for a FIFO, which is
+    /// opened directly.
+    fn command(&self, reconnect: bool) -> Option<Command> {
+        let command = match self {
+            StreamKind::Ssh { destination, port, path } => {
+                let mut command = Command::new("ssh");
+                command.args(["-o", "BatchMode=yes", "-o", "ServerAliveInterval=15"]);
+                if let Some(port) = port {
+                    command.arg("-p").arg(port.to_string());
+                }
+                let start = if reconnect { "0" } else { "+0" };
+                command
+                    .arg("--")
+                    .arg(destination)
+                    .arg(format!("tail -f -n {} {}", start, shell_quote(path)));
+                command
+            }
+            StreamKind::Journal { unit } => {
+                // JSON carries each entry's priority, which the short formats leave out
+                let mut command = Command::new("journalctl");
+                command.args(["-u", unit, "-f", "-o", "json"]);
+                if reconnect {
+                    command.args(["-n", "0"]);
+                }
+                command
+            }
+            StreamKind::Fifo { .. } => return None,
+        };
+        Some(command)
+    }
+
+    /// Turn a line of the command's output into the line to show
+    fn format_line(&self, raw: String) -> String {
+        match self {
+            StreamKind::Ssh { .. } | StreamKind::Fifo { .. } => raw,
+            StreamKind::Journal { .. } => format_journal_entry(&raw).unwrap_or(raw),
+        }
+    }
+}
+
+/// A `journalctl -o json` entry laid out like `-o short-iso`, with the entry's priority
+/// written as a `[LEVEL]` tag so level detection and coloring pick it up:
+/// `2024-05-01T10:00:00Z web1 nginx[812]: [ERROR] upstream timed out`
+fn format_journal_entry(json: &str) -> Option<String> {
+    let entry: serde_json::Value = serde_json::from_str(json).ok()?;
+    let field = |name: &str| entry.get(name).and_then(|value| value.as_str());
+
+    let message = match entry.get("MESSAGE") {
+        Some(serde_json::Value::String(message)) => message.clone(),
+        // Journald stores non-UTF-8 messages as byte arrays
+        Some(serde_json::Value::Array(bytes)) => {
+            let bytes: Vec<u8> = bytes.iter().filter_map(|b| b.as_u64()).map(|b| b as u8).collect();
+            String::from_utf8_lossy(&bytes).to_string()
+        }
+        _ => String::new(),
+    };
+
+    let mut line = String::new();
+    if let Some(micros) = field("__REALTIME_TIMESTAMP").and_then(|ts| ts.parse::<u64>().ok()) {
+        line.push_str(&iso_timestamp(micros / 1_000_000));
+        line.push(' ');
+    }
+    if let Some(host) = field("_HOSTNAME") {
+        line.push_str(host);
+        line.push(' ');
+    }
+    line.push_str(field("SYSLOG_IDENTIFIER").or(field("_COMM")).unwrap_or("unknown"));
+    if let Some(pid) = field("_PID") {
+        line.push_str(&format!("[{}]", pid));
+    }
+    line.push_str(": ");
+    if let Some(level) = field("PRIORITY").and_then(|p| p.parse().ok()).map(priority_level) {
+        line.push_str(&format!("[{}] ", level_tag(level)));
+    }
+    line.push_str(&message);
+    Some(line)
+}
+
+/// Syslog priority (0 = emerg ... 7 = debug) as a log level
+fn priority_level(priority: u8) -> LogLevel {
+    match priority {
+        0..=2 => LogLevel::Fatal,
+        3 => LogLevel::Error,
+        4 => LogLevel::Warn,
+        5 | 6 => LogLevel::Info,
+        _ => LogLevel::Debug,
+    }
+}
+
+fn level_tag(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Trace => "TRACE",
+        LogLevel::Debug => "DEBUG",
+        LogLevel::Info => "INFO",
+        LogLevel::Warn => "WARN",
+        LogLevel::Error => "ERROR",
+        LogLevel::Fatal => "FATAL",
+        LogLevel::Unknown => "UNKNOWN",
+    }
+}
+
+/// Seconds since the epoch as a UTC `YYYY-MM-DDTHH:MM:SSZ`
+fn iso_timestamp(secs: u64) -> String {
+    let time = UtcDateTime::from_unix_secs(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        time.year, time.month, time.day, time.hour, time.minute, time.second
+    )
+}
+
+/// True for a named pipe
+#[cfg(unix)]
+pub fn is_fifo(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    metadata.file_type().is_fifo()
+}
+
+#[cfg(not(unix))]
+pub fn is_fifo(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Quote for a POSIX shell, since ssh hands the remote command to one
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+enum StreamEvent {
+    Line(String),
+    /// Ended; started again after RECONNECT_DELAY
+    Closed(String),
+    /// Can't run here at all (e.g. the command isn't installed); not retried
+    Unavailable(String),
+}
+
+/// Reads a stream on a background thread and hands its lines to the tail loop,
+/// starting it again a few seconds after it closes
+pub struct StreamSource {
+    kind: StreamKind,
+    receiver: Option<Receiver<StreamEvent>>,
+    /// The running process, shared with the reader thread so dropping the source can stop it
+    child: Arc<Mutex<Option<Child>>>,
+    /// Set when the source is dropped, for reader threads that have no process to kill
+    stop: Arc<AtomicBool>,
+    reconnect_at: Option<Instant>,
+    connected_once: bool,
+    /// Gave up because the command can't run on this machine
+    unavailable: bool,
+    history: VecDeque<String>,
+}
+
+impl StreamSource {
+    pub fn new(kind: StreamKind) -> Self {
+        Self {
+            kind,
+            receiver: None,
+            child: Arc::new(Mutex::new(None)),
+            stop: Arc::new(AtomicBool::new(false)),
+            reconnect_at: None,
+            connected_once: false,
+            unavailable: false,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Lines that arrived since the last poll, starting the stream if it isn't running.
+    /// A closed stream shows up as a marker line, except a FIFO whose writer left.
+    pub fn poll(&mut self) -> Vec<String> {
+        if self.receiver.is_none() {
+            if self.unavailable || self.reconnect_at.is_some_and(|at| Instant::now() < at) {
+                return Vec::new();
+            }
+            self.start();
+        }
+
+        let mut lines = Vec::new();
+        let mut closed = None;
+        if let Some(receiver) = &self.receiver {
+            loop {
+                match receiver.try_recv() {
+                    Ok(StreamEvent::Line(line)) => lines.push(line),
+                    Ok(StreamEvent::Closed(reason)) => {
+                        closed = Some(reason);
+                        break;
+                    }
+                    Ok(StreamEvent::Unavailable(reason)) => {
+                        warn!("{} stream {} unavailable: {}", self.kind.label(), self.kind.display_name(), reason);
+                        self.unavailable = true;
+                        lines.push(format!("[{} UNAVAILABLE: {}]", self.kind.label(), reason));
+                        break;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        closed = Some("reader stopped".to_string());
+                        break;
+                    }
+                }
+            }
+        }
+
+        if self.unavailable {
+            self.receiver = None;
+        } else if let (Some(reason), StreamKind::Fifo { .. }) = (&closed, &self.kind) {
+            // Writers come and go; reopening waits for the next one
+            info!("FIFO {}: {}", self.kind.display_name(), reason);
+            self.receiver = None;
+        } else if let Some(reason) = closed {
+            warn!("{} stream {} closed: {}", self.kind.label(), self.kind.display_name(), reason);
+            self.receiver = None;
+            self.reconnect_at = Some(Instant::now() + RECONNECT_DELAY);
+            lines.push(format!(
+                "[{} DISCONNECTED: {} - reconnecting in {}s]",
+                self.kind.label(),
+                reason,
+                RECONNECT_DELAY.as_secs()
+            ));
+        }
+
+        for line in &lines {
+            if self.history.len() >= HISTORY_LINES {
+                self.history.pop_front();
+            }
+            self.history.push_back(line.clone());
+        }
+        lines
+    }
+
+    /// The last `count` lines received, oldest first
+    pub fn recent_lines(&self, count: usize) -> Vec<String> {
+        self.history
+            .iter()
+            .skip(self.history.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+
+    fn start(&mut self) {
+        let command = self.kind.command(self.connected_once);
+        info!("Starting {} stream {}", self.kind.label(), self.kind.display_name());
+        self.connected_once = true;
+        self.reconnect_at = None;
+
+        let (sender, receiver) = mpsc::channel();
+        let child = Arc::clone(&self.child);
+        let stop = Arc::clone(&self.stop);
+        let kind = self.kind.clone();
+        std::thread::spawn(move || {
+            let end = match (command, &kind) {
+                (Some(command), _) => run_command(&kind, command, &child, &sender),
+                (None, StreamKind::Fifo { path }) => read_fifo(path, &sender, &stop),
+                (None, _) => StreamEvent::Unavailable("nothing to run".to_string()),
+            };
+            let _ = sender.send(end);
+        });
+        self.receiver = Some(receiver);
+    }
+}
+
+impl Drop for StreamSource {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Ok(mut child) = self.child.lock() {
+            if let Some(child) = child.as_mut() {
+                let _ = child.kill();
+            }
+        }
+    }
+}
+
+/// Send the command's stdout line by line until it ends. Returns the event saying why
+/// it ended: the last thing it wrote to stderr, or its exit status.
+fn run_command(
+    kind: &StreamKind,
+    mut command: Command,
+    child_slot: &Mutex<Option<Child>>,
+    sender: &Sender<StreamEvent>,
+) -> StreamEvent {
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return StreamEvent::Unavailable(format!("{:?} not found", command.get_program()));
+        }
+        Err(e) => return StreamEvent::Closed(format!("failed to start: {}", e)),
+    };
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    if let Ok(mut slot) = child_slot.lock() {
+        *slot = Some(child);
+    }
+
+    // Drained on its own thread so a chatty stderr can't fill its pipe and stall the
+    // child while stdout is being read
+    let errors = stderr.map(|mut stderr| {
+        std::thread::spawn(move || {
+            let mut errors = String::new();
+            let _ = stderr.read_to_string(&mut errors);
+            errors
+        })
+    });
+
+    if let Some(stdout) = stdout {
+        send_lines(kind, stdout, sender);
+    }
+
+    let errors = errors.and_then(|reader| reader.join().ok()).unwrap_or_default();
+    let status = child_slot
+        .lock()
+        .ok()
+        .and_then(|mut slot| slot.take())
+        .and_then(|mut child| child.wait().ok());
+
+    StreamEvent::Closed(match errors.lines().rev().find(|line| !line.trim().is_empty()) {
+        Some(error) => error.trim().to_string(),
+        None => status.map_or("closed".to_string(), |status| format!("exited with {}", status)),
+    })
+}
+
+/// Read a FIFO until its writers close it. It's opened non-blocking, so a FIFO nobody
+/// writes to can't hold this thread in open() forever; instead it polls, and gives up
+/// once `stop` is set by the source being dropped.
+#[cfg(unix)]
+fn read_fifo(path: &Path, sender: &Sender<StreamEvent>, stop: &AtomicBool) -> StreamEvent {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut fifo = match std::fs::OpenOptions::new().read(true).custom_flags(libc::O_NONBLOCK).open(path) {
+        Ok(fifo) => fifo,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return StreamEvent::Unavailable(format!("{} was removed", path.display()));
+        }
+        Err(e) => return StreamEvent::Closed(format!("failed to open: {}", e)),
+    };
+
+    let kind = StreamKind::Fifo { path: path.to_path_buf() };
+    let mut pending = Vec::new();
+    let mut chunk = [0u8; 8192];
+    // With no writer connected a read finds end of input, both before the first writer
+    // arrives and after the last one leaves; only the second ends the stream
+    let mut writer_seen = false;
+    while !stop.load(Ordering::Relaxed) {
+        match fifo.read(&mut chunk) {
+            Ok(0) if writer_seen => {
+                if !pending.is_empty() {
+                    send_line(&kind, &pending, sender);
+                }
+                return StreamEvent::Closed("writer closed".to_string());
+            }
+            Ok(0) => std::thread::sleep(FIFO_POLL_INTERVAL),
+            Ok(read) => {
+                writer_seen = true;
+                pending.extend_from_slice(&chunk[..read]);
+                while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+                    let line: Vec<u8> = pending.drain(..=end).collect();
+                    if !send_line(&kind, &line, sender) {
+                        return StreamEvent::Closed("nobody listening".to_string());
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                // A writer is connected but hasn't sent anything yet
+                writer_seen = true;
+                std::thread::sleep(FIFO_POLL_INTERVAL);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return StreamEvent::Closed(format!("read failed: {}", e)),
+        }
+    }
+    StreamEvent::Closed("source removed".to_string())
+}
+
+#[cfg(not(unix))]
+fn read_fifo(_path: &Path, _sender: &Sender<StreamEvent>, _stop: &AtomicBool) -> StreamEvent {
+    StreamEvent::Unavailable("FIFOs are only supported on Unix".to_string())
+}
+
+/// Send each line read until end of input, or until nobody is listening
+fn send_lines(kind: &StreamKind, input: impl Read, sender: &Sender<StreamEvent>) {
+    let mut reader = BufReader::new(input);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                if !send_line(kind, &buffer, sender) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Send one line, without its line ending. False once nobody is listening.
+fn send_line(kind: &StreamKind, line: &[u8], sender: &Sender<StreamEvent>) -> bool {
+    let line = String::from_utf8_lossy(line).trim_end_matches(['\r', '\n']).to_string();
+    sender.send(StreamEvent::Line(kind.format_line(line))).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ssh_url() {
+        assert_eq!(
+            StreamKind::parse("ssh://deploy@web1:2222/var/log/app.log"),
+            Some(StreamKind::Ssh {
+                destination: "deploy@web1".to_string(),
+                port: Some(2222),
+                path: "/var/log/app.log".to_string(),
+            })
+        );
+        let kind = StreamKind::parse("ssh://web1/var/log/app.log").unwrap();
+        assert_eq!(kind.display_name(), "app.log@web1");
+
+        assert_eq!(StreamKind::parse("/var/log/app.log"), None);
+        assert_eq!(StreamKind::parse("ssh://web1/"), None);
+        assert_eq!(StreamKind::parse("ssh://web1:port/x.log"), None);
+        assert_eq!(StreamKind::parse("ssh://-oProxyCommand=touch%20x/x.log"), None);
+    }
+
+    #[test]
+    fn test_journal_entry_gets_level_tag() {
+        assert_eq!(
+            StreamKind::parse("journal://nginx.service"),
+            Some(StreamKind::Journal { unit: "nginx.service".to_string() })
+        );
+
+        let json = r#"{"__REALTIME_TIMESTAMP":"1714557600000000","_HOSTNAME":"web1","SYSLOG_IDENTIFIER":"nginx","_PID":"812","PRIORITY":"3","MESSAGE":"upstream timed out"}"#;
+        assert_eq!(
+            format_journal_entry(json).unwrap(),
+            "2024-05-01T10:00:00Z web1 nginx[812]: [ERROR] upstream timed out"
+        );
+        assert_eq!(priority_level(2), LogLevel::Fatal);
+        assert_eq!(priority_level(6), LogLevel::Info);
+        assert!(format_journal_entry("-- No entries --").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fifo_lines_across_writers() {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("vis-grep-fifo-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(Command::new("mkfifo").arg(&path).status().unwrap().success());
+        assert!(is_fifo(&std::fs::metadata(&path).unwrap()));
+
+        let mut source = StreamSource::new(StreamKind::Fifo { path: path.clone() });
+        // Each open blocks until the source's reader has the FIFO open, so write from a
+        // thread while this one keeps polling
+        let writer_path = path.clone();
+        let writer = std::thread::spawn(move || {
+            for text in ["first\n", "second\n"] {
+                let mut writer = std::fs::OpenOptions::new().write(true).open(&writer_path).unwrap();
+                writer.write_all(text.as_bytes()).unwrap();
+            }
+        });
+
+        let mut received = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while received.len() < 2 && Instant::now() < deadline {
+            received.extend(source.poll());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        writer.join().unwrap();
+
+        assert_eq!(received, vec!["first", "second"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fifo_reader_ends_when_stopped_without_a_writer() {
+        let path = std::env::temp_dir().join(format!("vis-grep-fifo-stop-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(Command::new("mkfifo").arg(&path).status().unwrap().success());
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, _receiver) = mpsc::channel();
+        let (done_sender, done) = mpsc::channel();
+        let reader_path = path.clone();
+        let reader_stop = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            let end = read_fifo(&reader_path, &sender, &reader_stop);
+            let _ = done_sender.send(matches!(end, StreamEvent::Closed(_)));
+        });
+
+        std::thread::sleep(FIFO_POLL_INTERVAL);
+        stop.store(true, Ordering::Relaxed);
+        assert_eq!(done.recv_timeout(Duration::from_secs(5)), Ok(true));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/var/log/my app.log"), "'/var/log/my app.log'");
+        assert_eq!(shell_quote("it's.log"), r"'it'\''s.log'");
+    }
+}
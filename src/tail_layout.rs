@@ -1,11 +1,16 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// The main layout configuration for tail mode
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TailLayout {
     pub name: String,
     pub version: u32,
+    /// Other layout files whose groups are merged in ahead of this file's own,
+    /// relative to this file's directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
     #[serde(default)]
     pub settings: LayoutSettings,
     #[serde(rename = "groups")]
@@ -15,7 +20,11 @@ pub struct TailLayout {
 /// Settings that apply to the entire layout
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LayoutSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub poll_interval_ms: Option<u64>,
+    /// Overrides `ui.idle_timeout_secs` while this layout is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_secs: Option<u64>,
     #[serde(default = "default_true")]
     pub auto_expand_active: bool,
 }
@@ -24,6 +33,7 @@ impl Default for LayoutSettings {
     fn default() -> Self {
         Self {
             poll_interval_ms: None,
+            idle_timeout_secs: None,
             auto_expand_active: true,
         }
     }
@@ -39,6 +49,7 @@ pub struct FileGroup {
     #[serde(skip)]
     pub id: String, // Will be generated after loading
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<String>,
     #[serde(skip)]
     pub parent_id: Option<String>,
@@ -46,9 +57,9 @@ pub struct FileGroup {
     pub collapsed: bool,
 
     // Either files or subgroups (or both)
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub files: Vec<FileEntry>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub groups: Vec<FileGroup>,
 
     // Runtime state (not serialized)
@@ -66,18 +77,120 @@ pub struct FileGroup {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
     pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>, // Display name override
     #[serde(default)]
     pub pattern: bool, // If true, path is a glob pattern
     #[serde(default)]
     pub paused: bool, // If true, file starts paused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>, // "#RRGGBB" overriding the color picked from the file name
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "serde_yaml::with::singleton_map")]
+    pub start_at: Option<StartAt>, // Where reading starts; unset follows --lines
 
     // Reference to actual TailedFile (set at runtime)
     #[serde(skip)]
     pub tailed_file_idx: Option<usize>,
 }
 
+/// Where a layout file starts being read when the layout is loaded, written as `end`,
+/// `beginning` or `last_lines: N`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartAt {
+    /// Only lines written from now on, like `tail -f`
+    End,
+    /// The whole file, then new lines
+    Beginning,
+    /// The last N lines already in the file, then new lines
+    LastLines(usize),
+}
+
+impl FileGroup {
+    fn new(name: String, files: Vec<FileEntry>) -> Self {
+        Self {
+            id: String::new(),
+            name,
+            icon: None,
+            parent_id: None,
+            collapsed: false,
+            files,
+            groups: Vec::new(),
+            has_activity: false,
+            active_file_count: 0,
+            total_file_count: 0,
+            user_collapsed: None,
+        }
+    }
+}
+
 impl TailLayout {
+    /// Build a layout from (path, display name, paused, color) of files being tailed, either
+    /// as a single group or one group per parent directory. Display names matching the file
+    /// name are left out of the entries.
+    pub fn from_files(name: &str, files: &[(PathBuf, String, bool, Option<String>)], group_by_directory: bool) -> Self {
+        let mut groups: Vec<FileGroup> = Vec::new();
+        for (path, display_name, paused, color) in files {
+            let file_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+            let entry = FileEntry {
+                path: path.clone(),
+                name: (file_name.as_deref() != Some(display_name.as_str())).then(|| display_name.clone()),
+                pattern: false,
+                paused: *paused,
+                color: color.clone(),
+                start_at: None,
+                tailed_file_idx: None,
+            };
+
+            let group_name = if group_by_directory {
+                path.parent()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_else(|| "Files".to_string())
+            } else {
+                "Files".to_string()
+            };
+
+            match groups.iter_mut().find(|g| g.name == group_name) {
+                Some(group) => group.files.push(entry),
+                None => groups.push(FileGroup::new(group_name, vec![entry])),
+            }
+        }
+
+        let mut layout = Self {
+            name: name.to_string(),
+            version: 1,
+            include: Vec::new(),
+            settings: LayoutSettings::default(),
+            root_groups: groups,
+        };
+        layout.assign_ids();
+        layout
+    }
+
+    /// Append a root group holding the given files and return its id
+    pub fn add_group(&mut self, name: &str, paths: &[PathBuf]) -> String {
+        let files = paths
+            .iter()
+            .map(|path| FileEntry {
+                path: path.clone(),
+                name: None,
+                pattern: false,
+                paused: false,
+                color: None,
+                start_at: None,
+                tailed_file_idx: None,
+            })
+            .collect();
+        self.root_groups.push(FileGroup::new(name.to_string(), files));
+        // New groups go last, so the ids of existing groups are unchanged
+        self.assign_ids();
+        self.root_groups.last().map(|group| group.id.clone()).unwrap_or_default()
+    }
+
+    pub fn to_yaml_string(&self) -> Result<String, String> {
+        serde_yaml::to_string(self).map_err(|e| format!("Failed to serialize layout: {}", e))
+    }
+
     /// Link a file entry to its index in the TailedFile vector
     pub fn link_file_to_index(&mut self, file_path: &PathBuf, group_id: &str, file_idx: usize) {
         // Find the file entry in the appropriate group and update its index
@@ -91,18 +204,25 @@ impl TailLayout {
         }
     }
     
-    /// Load a layout from a YAML file
-    pub fn from_yaml_file(path: &PathBuf) -> Result<Self, String> {
-        let contents = std::fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read layout file: {}", e))?;
-        
-        Self::from_yaml_str(&contents)
+    /// Load a layout from a YAML or JSON file (by extension), merging in any included layouts
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let mut layout = Self::load_with_includes(path, &mut Vec::new())?;
+        // IDs were assigned per file; renumber across the merged tree
+        layout.assign_ids();
+        Ok(layout)
     }
 
     /// Parse layout from YAML string
     pub fn from_yaml_str(yaml: &str) -> Result<Self, String> {
-        let mut layout: TailLayout = serde_yaml::from_str(yaml)
-            .map_err(|e| format!("Failed to parse YAML: {}", e))?;
+        let mut layout: TailLayout = serde_yaml::from_str(yaml).map_err(|e| match e.location() {
+            Some(location) => format!(
+                "Failed to parse YAML at line {}, column {}: {}",
+                location.line(),
+                location.column(),
+                e
+            ),
+            None => format!("Failed to parse YAML: {}", e),
+        })?;
         
         // Assign IDs and parent references
         layout.assign_ids();
@@ -110,6 +230,82 @@ impl TailLayout {
         Ok(layout)
     }
 
+    /// Parse layout from JSON string
+    pub fn from_json_str(json: &str) -> Result<Self, String> {
+        let mut layout: TailLayout = serde_json::from_str(json).map_err(|e| {
+            format!("Failed to parse JSON at line {}, column {}: {}", e.line(), e.column(), e)
+        })?;
+        layout.assign_ids();
+        Ok(layout)
+    }
+
+    pub fn to_json_string(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize layout: {}", e))
+    }
+
+    /// True for `.json` layout files; anything else is read as YAML
+    pub fn is_json_path(path: &Path) -> bool {
+        path.extension()
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or(false)
+    }
+
+    /// Load one layout file and, recursively, its includes. Included groups come first and
+    /// their relative file paths are made absolute against the included file's directory.
+    /// `stack` holds the files currently being loaded, to catch include cycles.
+    fn load_with_includes(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Self, String> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if stack.contains(&canonical) {
+            let chain: Vec<String> = stack
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(format!("Include cycle: {}", chain.join(" -> ")));
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read layout file {}: {}", path.display(), e))?;
+        let parsed = if Self::is_json_path(path) {
+            Self::from_json_str(&contents)
+        } else {
+            Self::from_yaml_str(&contents)
+        };
+        let mut layout = parsed
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        let layout_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        if layout.include.is_empty() {
+            return Ok(layout);
+        }
+
+        stack.push(canonical);
+        let mut groups = Vec::new();
+        for include in &layout.include {
+            let include_path = resolve_entry_path(include, &layout_dir);
+            let mut included = Self::load_with_includes(&include_path, stack)?;
+            let include_dir = include_path.parent().map(Path::to_path_buf).unwrap_or_default();
+            for group in &mut included.root_groups {
+                Self::resolve_group_paths(group, &include_dir);
+            }
+            groups.append(&mut included.root_groups);
+        }
+        stack.pop();
+
+        groups.append(&mut layout.root_groups);
+        layout.root_groups = groups;
+        Ok(layout)
+    }
+
+    fn resolve_group_paths(group: &mut FileGroup, layout_dir: &Path) {
+        for entry in &mut group.files {
+            entry.path = resolve_entry_path(&entry.path, layout_dir);
+        }
+        for subgroup in &mut group.groups {
+            Self::resolve_group_paths(subgroup, layout_dir);
+        }
+    }
+
     /// Assign unique IDs to all groups and set up parent references
     fn assign_ids(&mut self) {
         let mut counter = 0;
@@ -171,24 +367,95 @@ impl TailLayout {
         None
     }
 
+    /// Move the entry for tailed file `file_idx` to just before (or after) the entry for
+    /// `target_idx` within the same group. Entries keep their tailed_file_idx, so the links
+    /// to the tailed files survive the move. Returns false if either isn't in the group.
+    pub fn move_file_entry(&mut self, group_id: &str, file_idx: usize, target_idx: usize, after: bool) -> bool {
+        let Some(group) = self.find_group_mut(group_id) else {
+            return false;
+        };
+        let position = |files: &[FileEntry], idx: usize| files.iter().position(|e| e.tailed_file_idx == Some(idx));
+        let Some(from) = position(&group.files, file_idx) else {
+            return false;
+        };
+        if position(&group.files, target_idx).is_none() || file_idx == target_idx {
+            return false;
+        }
+
+        let entry = group.files.remove(from);
+        let target = position(&group.files, target_idx).unwrap_or(group.files.len());
+        group.files.insert(if after { target + 1 } else { target }, entry);
+        true
+    }
+
+    /// Apply `f` to every file entry in the tree
+    pub fn for_each_file_entry_mut(&mut self, mut f: impl FnMut(&mut FileEntry)) {
+        fn visit(group: &mut FileGroup, f: &mut impl FnMut(&mut FileEntry)) {
+            group.files.iter_mut().for_each(&mut *f);
+            for subgroup in &mut group.groups {
+                visit(subgroup, f);
+            }
+        }
+        for group in &mut self.root_groups {
+            visit(group, &mut f);
+        }
+    }
+
+    /// Expand/collapse choices the user made, keyed by group name path (`App/Core`) since
+    /// group ids are renumbered whenever a layout is loaded
+    pub fn collapse_states(&self) -> HashMap<String, bool> {
+        fn visit(group: &FileGroup, prefix: &str, states: &mut HashMap<String, bool>) {
+            let path = format!("{}{}", prefix, group.name);
+            if group.user_collapsed.is_some() {
+                states.insert(path.clone(), group.collapsed);
+            }
+            for subgroup in &group.groups {
+                visit(subgroup, &format!("{}/", path), states);
+            }
+        }
+        let mut states = HashMap::new();
+        for group in &self.root_groups {
+            visit(group, "", &mut states);
+        }
+        states
+    }
+
+    /// Re-apply choices from collapse_states to groups with the same name path
+    pub fn restore_collapse_states(&mut self, states: &HashMap<String, bool>) {
+        fn visit(group: &mut FileGroup, prefix: &str, states: &HashMap<String, bool>) {
+            let path = format!("{}{}", prefix, group.name);
+            if let Some(&collapsed) = states.get(&path) {
+                group.collapsed = collapsed;
+                group.user_collapsed = Some(collapsed);
+            }
+            for subgroup in &mut group.groups {
+                visit(subgroup, &format!("{}/", path), states);
+            }
+        }
+        for group in &mut self.root_groups {
+            visit(group, "", states);
+        }
+    }
+
     /// Get all file paths from the layout (flattened) with paused status
-    pub fn get_all_file_paths(&self) -> Vec<(PathBuf, Option<String>, String, bool)> {
-        let mut paths = Vec::new();
+    /// Every file entry in the layout, with the id of the group it belongs to
+    pub fn get_all_file_entries(&self) -> Vec<(FileEntry, String)> {
+        let mut entries = Vec::new();
         for group in &self.root_groups {
-            Self::collect_file_paths(group, &mut paths);
+            Self::collect_file_entries(group, &mut entries);
         }
-        paths
+        entries
     }
 
-    fn collect_file_paths(group: &FileGroup, paths: &mut Vec<(PathBuf, Option<String>, String, bool)>) {
+    fn collect_file_entries(group: &FileGroup, entries: &mut Vec<(FileEntry, String)>) {
         // Add files from this group
         for file in &group.files {
-            paths.push((file.path.clone(), file.name.clone(), group.id.clone(), file.paused));
+            entries.push((file.clone(), group.id.clone()));
         }
         
         // Recursively add files from subgroups
         for subgroup in &group.groups {
-            Self::collect_file_paths(subgroup, paths);
+            Self::collect_file_entries(subgroup, entries);
         }
     }
 
@@ -242,10 +509,155 @@ impl TailLayout {
     }
 }
 
+/// Resolve a layout file entry: expand `~`, `$VAR` and `${VAR}`, then resolve relative
+/// paths against the layout file's own directory so layouts work from any CWD
+pub fn resolve_entry_path(path: &Path, layout_dir: &Path) -> PathBuf {
+    if crate::stream_source::StreamKind::parse(&path.to_string_lossy()).is_some() {
+        return path.to_path_buf();
+    }
+    let expanded = crate::VisGrepApp::expand_tilde(&path.to_string_lossy());
+    let expanded = PathBuf::from(expand_env_vars(&expanded, |name| std::env::var(name).ok()));
+    if expanded.is_absolute() {
+        expanded
+    } else {
+        layout_dir.join(expanded)
+    }
+}
+
+/// Substitute `$VAR` and `${VAR}`; unknown variables are left as written so the
+/// resulting "file not found" names the variable
+fn expand_env_vars(text: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(dollar) = rest.find('$') {
+        out.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        match (name.is_empty(), lookup(name)) {
+            (false, Some(value)) => out.push_str(&value),
+            _ => out.push_str(&rest[dollar..dollar + 1 + consumed]),
+        }
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_move_file_entry_keeps_links() {
+        let yaml = r#"
+name: "Test Layout"
+version: 1
+groups:
+  - name: "Apps"
+    files:
+      - path: "a.log"
+      - path: "b.log"
+      - path: "c.log"
+"#;
+        let mut layout = TailLayout::from_yaml_str(yaml).unwrap();
+        for (idx, name) in ["a.log", "b.log", "c.log"].iter().enumerate() {
+            layout.link_file_to_index(&PathBuf::from(name), "group_0", idx);
+        }
+
+        assert!(layout.move_file_entry("group_0", 2, 0, false));
+        let order: Vec<_> = layout.root_groups[0].files.iter()
+            .map(|e| (e.path.to_string_lossy().to_string(), e.tailed_file_idx))
+            .collect();
+        assert_eq!(order, vec![
+            ("c.log".to_string(), Some(2)),
+            ("a.log".to_string(), Some(0)),
+            ("b.log".to_string(), Some(1)),
+        ]);
+
+        assert!(layout.move_file_entry("group_0", 2, 1, true));
+        assert_eq!(layout.root_groups[0].files[2].tailed_file_idx, Some(2));
+        assert!(!layout.move_file_entry("group_0", 0, 7, false));
+    }
+
+    #[test]
+    fn test_start_at_round_trip() {
+        let yaml = r#"
+name: "Test"
+version: 1
+groups:
+  - name: "Apps"
+    files:
+      - path: "audit.log"
+        start_at: beginning
+      - path: "app.log"
+        start_at:
+          last_lines: 50
+      - path: "access.log"
+"#;
+        let layout = TailLayout::from_yaml_str(yaml).unwrap();
+        let starts: Vec<_> = layout.root_groups[0].files.iter().map(|e| e.start_at).collect();
+        assert_eq!(starts, vec![Some(StartAt::Beginning), Some(StartAt::LastLines(50)), None]);
+
+        let saved = layout.to_yaml_string().unwrap();
+        assert!(saved.contains("start_at: beginning"));
+        assert!(saved.contains("last_lines: 50"));
+        let json = layout.to_json_string().unwrap();
+        assert_eq!(TailLayout::from_json_str(&json).unwrap().root_groups[0].files[1].start_at, Some(StartAt::LastLines(50)));
+    }
+
+    #[test]
+    fn test_add_group_keeps_existing_ids() {
+        let mut layout = TailLayout::from_files("Tail", &[(PathBuf::from("/tmp/a.log"), "a.log".to_string(), false, None)], false);
+        let id = layout.add_group("logs", &[PathBuf::from("/var/log/x.log")]);
+        assert_eq!(layout.root_groups[0].id, "group_0");
+        assert_eq!(id, "group_1");
+        assert_eq!(layout.find_group(&id).unwrap().total_file_count, 1);
+    }
+
+    #[test]
+    fn test_collapse_states_survive_reload_by_name() {
+        let yaml = r#"
+version: 1
+name: "Test"
+groups:
+  - name: "App"
+    groups:
+      - name: "Core"
+        files:
+          - path: "/tmp/core.log"
+  - name: "Web"
+    collapsed: true
+    files:
+      - path: "/tmp/web.log"
+"#;
+        let mut layout = TailLayout::from_yaml_str(yaml).unwrap();
+        let core_id = layout.root_groups[0].groups[0].id.clone();
+        let core = layout.find_group_mut(&core_id).unwrap();
+        core.collapsed = true;
+        core.user_collapsed = Some(true);
+        let states = layout.collapse_states();
+        assert_eq!(states, HashMap::from([("App/Core".to_string(), true)]));
+
+        let mut reloaded = TailLayout::from_yaml_str(yaml).unwrap();
+        reloaded.restore_collapse_states(&states);
+        assert!(reloaded.root_groups[0].groups[0].collapsed);
+        assert!(!reloaded.root_groups[0].collapsed);
+        // Groups the user never touched keep what the file says
+        assert!(reloaded.root_groups[1].collapsed);
+    }
+
     #[test]
     fn test_parse_simple_layout() {
         let yaml = r#"
@@ -286,4 +698,131 @@ groups:
         assert_eq!(layout.root_groups[0].groups.len(), 2);
         assert_eq!(layout.root_groups[0].total_file_count, 2);
     }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let lookup = |name: &str| (name == "LOGDIR").then(|| "/var/log/app".to_string());
+        assert_eq!(expand_env_vars("$LOGDIR/a.log", lookup), "/var/log/app/a.log");
+        assert_eq!(expand_env_vars("${LOGDIR}_old/a.log", lookup), "/var/log/app_old/a.log");
+        assert_eq!(expand_env_vars("$NOPE/a.log", lookup), "$NOPE/a.log");
+        assert_eq!(expand_env_vars("cost$", lookup), "cost$");
+    }
+
+    #[test]
+    fn test_relative_entry_resolves_against_layout_dir() {
+        let resolved = resolve_entry_path(Path::new("logs/app.log"), Path::new("/etc/layouts"));
+        assert_eq!(resolved, PathBuf::from("/etc/layouts/logs/app.log"));
+    }
+
+    fn write_layout(dir: &Path, name: &str, yaml: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, yaml).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_includes_merge_groups() {
+        let dir = std::env::temp_dir().join(format!("vis-grep-include-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("services")).unwrap();
+        write_layout(&dir.join("services"), "auth.yaml", r#"
+name: "Auth"
+version: 1
+groups:
+  - name: "Auth"
+    files:
+      - path: "logs/auth.log"
+"#);
+        let main = write_layout(&dir, "main.yaml", r#"
+name: "Dashboard"
+version: 1
+include:
+  - "services/auth.yaml"
+groups:
+  - name: "Mine"
+    files:
+      - path: "/tmp/mine.log"
+"#);
+
+        let layout = TailLayout::from_file(&main).unwrap();
+        let names: Vec<&str> = layout.root_groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["Auth", "Mine"]);
+        assert_eq!(layout.root_groups[0].files[0].path, dir.join("services/logs/auth.log"));
+        assert_ne!(layout.root_groups[0].id, layout.root_groups[1].id);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_include_cycle_detected() {
+        let dir = std::env::temp_dir().join(format!("vis-grep-cycle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let a = write_layout(&dir, "a.yaml", "name: a\nversion: 1\ninclude: [b.yaml]\ngroups: []\n");
+        write_layout(&dir, "b.yaml", "name: b\nversion: 1\ninclude: [a.yaml]\ngroups: []\n");
+
+        let err = TailLayout::from_file(&a).unwrap_err();
+        assert!(err.contains("Include cycle"), "{}", err);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_from_files_round_trips() {
+        let files = vec![
+            (PathBuf::from("/var/log/app/a.log"), "a.log".to_string(), false, None),
+            (PathBuf::from("/var/log/app/b.log"), "Backend".to_string(), true, Some("#FF8800".to_string())),
+            (PathBuf::from("/tmp/c.log"), "c.log".to_string(), false, None),
+        ];
+
+        let layout = TailLayout::from_files("Session", &files, true);
+        assert_eq!(layout.root_groups.len(), 2);
+        assert_eq!(layout.root_groups[0].name, "/var/log/app");
+
+        let reloaded = TailLayout::from_yaml_str(&layout.to_yaml_string().unwrap()).unwrap();
+        let entries = reloaded.get_all_file_entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].0.name, None);
+        assert_eq!(entries[1].0.name.as_deref(), Some("Backend"));
+        assert!(entries[1].0.paused);
+        assert_eq!(entries[1].0.color.as_deref(), Some("#FF8800"));
+
+        let flat = TailLayout::from_files("Session", &files, false);
+        assert_eq!(flat.root_groups.len(), 1);
+    }
+
+    #[test]
+    fn test_json_layout() {
+        let json = r#"{
+            "name": "Json Layout",
+            "version": 1,
+            "groups": [
+                { "name": "App", "files": [ { "path": "/app/core.log", "paused": true } ] }
+            ]
+        }"#;
+
+        let layout = TailLayout::from_json_str(json).unwrap();
+        assert_eq!(layout.name, "Json Layout");
+        assert!(layout.root_groups[0].files[0].paused);
+
+        let round_trip = TailLayout::from_json_str(&layout.to_json_string().unwrap()).unwrap();
+        assert_eq!(round_trip.get_all_file_entries().len(), 1);
+
+        let err = TailLayout::from_json_str("{\"name\": \"x\",\n \"version\": }").unwrap_err();
+        assert!(err.starts_with("Failed to parse JSON at line 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_error_has_location() {
+        let yaml = r#"
+name: "Broken"
+version: 1
+groups:
+  - name: "App"
+    files:
+      - path: "/app/core.log"
+        paused: maybe
+"#;
+
+        let err = TailLayout::from_yaml_str(yaml).unwrap_err();
+        assert!(err.starts_with("Failed to parse YAML at line 8, column"), "{}", err);
+    }
 }
\ No newline at end of file
@@ -1,8 +1,79 @@
-use crate::{PreviewMode, VisGrepApp, get_color_for_file, filter, log_parser, widgets};
+use crate::{LogLine, PreviewMode, TreeItem, VisGrepApp, format_age, get_color_for_file, columns, config, filter, highlight, links, log_parser, marks, sparkline, truncate, widgets};
 use eframe::egui;
+use std::collections::VecDeque;
+use std::time::Instant;
 use log::info;
+use crate::relative_time::RelativeTimeFormat;
+
+/// (text, background) for live grep matches in the output
+const GREP_MATCH_COLORS: (egui::Color32, egui::Color32) =
+    (egui::Color32::BLACK, egui::Color32::from_rgb(255, 200, 100));
+
+/// (text, background) for watch keywords such as the hostname
+const KEYWORD_COLORS: (egui::Color32, egui::Color32) =
+    (egui::Color32::WHITE, egui::Color32::from_rgb(30, 110, 130));
+
+/// Color for the marker lines the tailer inserts when a file is rotated, truncated or
+/// deleted, so they stand out from the log's own lines
+fn file_event_color(content: &str) -> Option<egui::Color32> {
+    if content.starts_with("[FILE ROTATED") {
+        Some(egui::Color32::from_rgb(120, 200, 255))
+    } else if content.starts_with("[FILE TRUNCATED") {
+        Some(egui::Color32::from_rgb(255, 180, 80))
+    } else if content.starts_with("[FILE DELETED") {
+        Some(egui::Color32::from_rgb(255, 110, 110))
+    } else {
+        None
+    }
+}
+
+/// Drag-and-drop payload for reordering files within a layout group
+struct DraggedTailFile {
+    group_id: String,
+    file_idx: usize,
+}
+
+/// Something clicked on a row of the combined output
+enum OutputRowAction {
+    ShowJson(String),
+    ToggleBookmark(u64),
+    ToggleExpanded(u64),
+    CopyLine(u64),
+}
 
 impl VisGrepApp {
+    /// Layout parse errors and files that couldn't be opened, so a typo'd path doesn't just vanish
+    fn render_layout_problems(&mut self, ui: &mut egui::Ui) {
+        if self.tail_state.layout_problems.is_empty() {
+            return;
+        }
+
+        let mut dismiss = false;
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgb(70, 50, 20))
+            .inner_margin(egui::Margin::same(6.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ Layout problems ({})", self.tail_state.layout_problems.len()))
+                            .color(egui::Color32::from_rgb(255, 200, 100))
+                            .strong(),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        dismiss = ui.small_button("Dismiss").clicked();
+                    });
+                });
+                for problem in &self.tail_state.layout_problems {
+                    ui.label(egui::RichText::new(problem).small());
+                }
+            });
+        ui.separator();
+
+        if dismiss {
+            self.tail_state.layout_problems.clear();
+        }
+    }
+
     pub fn render_tail_mode_controls(&mut self, ui: &mut egui::Ui) {
         
         // Tree filter
@@ -11,11 +82,29 @@ impl VisGrepApp {
         }
         
         ui.separator();
+
+        self.render_layout_problems(ui);
         
         // File list header
         ui.horizontal(|ui| {
             ui.label("Files Being Monitored:");
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.menu_button("📂 Add directory", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Files:");
+                        ui.add(egui::TextEdit::singleline(&mut self.tail_state.dir_pattern).desired_width(100.0));
+                    });
+                    ui.checkbox(&mut self.tail_state.dir_group, "Group under the directory name");
+                    if ui.button("Choose folder...").clicked() {
+                        ui.close_menu();
+                        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                            let pattern = self.tail_state.dir_pattern.clone();
+                            if let Err(e) = self.tail_state.add_directory(&dir, &pattern, self.tail_state.dir_group) {
+                                log::error!("{}", e);
+                            }
+                        }
+                    }
+                });
                 if ui
                     .button(if self.tail_state.paused_all {
                         "▶ Resume All"
@@ -87,7 +176,7 @@ impl VisGrepApp {
             
             // Slider for fine control
             ui.add(
-                egui::Slider::new(&mut self.tail_state.font_size, 8.0..=24.0)
+                egui::Slider::new(&mut self.tail_state.font_size, crate::FONT_SIZE_RANGE)
                     .suffix(" px")
                     .show_value(true)
             );
@@ -225,6 +314,134 @@ impl VisGrepApp {
         false
     }
     
+    fn is_file_visible_in_tree(&self, file_idx: usize) -> bool {
+        let file = &self.tail_state.files[file_idx];
+        filter::tree::is_file_visible(&self.tail_state.tree_filter, &file.path.to_string_lossy(), &file.display_name)
+    }
+
+    /// The rows of the file tree in the order they're drawn, leaving out collapsed
+    /// children and whatever the tree filter hides
+    fn visible_tree_items(&self) -> Vec<TreeItem> {
+        let mut items = Vec::new();
+        let Some(layout) = &self.tail_state.layout else {
+            items.extend((0..self.tail_state.files.len()).filter(|&idx| self.is_file_visible_in_tree(idx)).map(TreeItem::File));
+            return items;
+        };
+        for group in &layout.root_groups {
+            self.collect_tree_items(&group.id, &mut items);
+        }
+        items.extend(
+            (0..self.tail_state.files.len())
+                .filter(|&idx| self.tail_state.files[idx].group_id.is_none() && self.is_file_visible_in_tree(idx))
+                .map(TreeItem::File),
+        );
+        items
+    }
+
+    fn collect_tree_items(&self, group_id: &str, items: &mut Vec<TreeItem>) {
+        let Some(group) = self.tail_state.layout.as_ref().and_then(|layout| layout.find_group(group_id)) else {
+            return;
+        };
+        if self.tail_state.tree_filter.active && !self.group_has_visible_content(group_id) {
+            return;
+        }
+        items.push(TreeItem::Group(group_id.to_string()));
+        if group.collapsed {
+            return;
+        }
+        for child in &group.groups {
+            self.collect_tree_items(&child.id, items);
+        }
+        for entry in &group.files {
+            if let Some(idx) = self.tail_state.files.iter().position(|f| f.path == entry.path) {
+                if self.is_file_visible_in_tree(idx) {
+                    items.push(TreeItem::File(idx));
+                }
+            }
+        }
+    }
+
+    /// Keys go to the tree when it was given focus with F6, or when there's no preview
+    /// for them to go to instead
+    fn tree_has_keyboard_focus(&self) -> bool {
+        self.tail_state.tree_focused || self.tail_state.preview_selected_file.is_none()
+    }
+
+    /// j/k or the arrow keys move through the tree, Enter previews the file or expands /
+    /// collapses the group, Left / Right collapse / expand it, i isolates the file in the
+    /// output, and Escape or F6 hands the keys back to the preview
+    fn handle_tree_navigation(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.key_pressed(egui::Key::F6)) {
+            self.tail_state.tree_focused = !self.tail_state.tree_focused;
+        }
+        if !self.tree_has_keyboard_focus() || ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (down, up, enter, left, right, escape, isolate) = ctx.input(|i| {
+            let plain = !i.modifiers.ctrl && !i.modifiers.alt && !i.modifiers.command;
+            (
+                i.key_pressed(egui::Key::ArrowDown) || (plain && i.key_pressed(egui::Key::J)),
+                i.key_pressed(egui::Key::ArrowUp) || (plain && i.key_pressed(egui::Key::K)),
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::ArrowLeft),
+                i.key_pressed(egui::Key::ArrowRight),
+                i.key_pressed(egui::Key::Escape),
+                plain && i.key_pressed(egui::Key::I),
+            )
+        });
+        if escape && self.tail_state.preview_selected_file.is_some() {
+            self.tail_state.tree_focused = false;
+            return;
+        }
+
+        let items = self.visible_tree_items();
+        if items.is_empty() {
+            return;
+        }
+        if down || up {
+            let current = self.tail_state.tree_cursor.as_ref().and_then(|cursor| items.iter().position(|item| item == cursor));
+            let next = match current {
+                None => 0,
+                Some(pos) if down => (pos + 1).min(items.len() - 1),
+                Some(pos) => pos.saturating_sub(1),
+            };
+            self.tail_state.tree_cursor = Some(items[next].clone());
+            self.tail_state.tree_scroll_to_cursor = true;
+        }
+
+        match self.tail_state.tree_cursor.clone() {
+            Some(TreeItem::File(idx)) if enter && idx < self.tail_state.files.len() => self.select_preview_file(idx),
+            Some(TreeItem::File(idx)) if isolate && idx < self.tail_state.files.len() => self.toggle_isolated_file(idx),
+            Some(TreeItem::Group(id)) if enter || left || right => {
+                if let Some(group) = self.tail_state.layout.as_mut().and_then(|layout| layout.find_group_mut(&id)) {
+                    group.collapsed = if enter { !group.collapsed } else { left };
+                    group.user_collapsed = Some(group.collapsed);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Show only `file_idx`'s lines in the output, or everything again if it already was
+    fn toggle_isolated_file(&mut self, file_idx: usize) {
+        let path = &self.tail_state.files[file_idx].path;
+        self.tail_state.isolated_file =
+            if self.tail_state.isolated_file.as_ref() == Some(path) { None } else { Some(path.clone()) };
+    }
+
+    /// Outline the tree cursor's row, scrolling it into view after it moved
+    fn paint_tree_cursor(&mut self, ui: &egui::Ui, item: TreeItem, rect: egui::Rect) {
+        if self.tail_state.tree_cursor.as_ref() != Some(&item) || !self.tree_has_keyboard_focus() {
+            return;
+        }
+        ui.painter().rect_stroke(rect, 2.0, ui.visuals().selection.stroke);
+        if self.tail_state.tree_scroll_to_cursor {
+            ui.scroll_to_rect(rect, None);
+            self.tail_state.tree_scroll_to_cursor = false;
+        }
+    }
+
     fn render_file_group_by_id(&mut self, ui: &mut egui::Ui, group_id: &str, depth: usize) {
         // Get group info (cloned to avoid borrow issues)
         let group_info = if let Some(layout) = &self.tail_state.layout {
@@ -276,7 +493,7 @@ impl VisGrepApp {
             
             // Scale row height with font size
             let row_height = self.tail_state.font_size + 2.0; // Minimal padding
-            ui.allocate_ui_with_layout(
+            let row = ui.allocate_ui_with_layout(
                 egui::Vec2::new(ui.available_width(), row_height),
                 egui::Layout::left_to_right(egui::Align::Center),
                 |ui| {
@@ -285,6 +502,7 @@ impl VisGrepApp {
                 // Expand/collapse arrow
                 let arrow = if collapsed { "▶" } else { "▼" };
                 if ui.small_button(arrow).clicked() {
+                    self.tail_state.tree_cursor = Some(TreeItem::Group(group_id.to_string()));
                     // Toggle collapsed state
                     if let Some(layout) = &mut self.tail_state.layout {
                         if let Some(group) = layout.find_group_mut(group_id) {
@@ -322,6 +540,7 @@ impl VisGrepApp {
                     }
                 });
             });
+            self.paint_tree_cursor(ui, TreeItem::Group(group_id.to_string()), row.response.rect);
             
             // Add minimal spacing between rows
             ui.add_space(1.0);
@@ -359,6 +578,14 @@ impl VisGrepApp {
         // Capture the file path before the closure to avoid borrowing issues
         let file_path = file.path.clone();
         let mut open_in_editor_clicked = false;
+        let mut preview_clicked = false;
+        let mut clear_output_clicked = false;
+        let mut isolate_clicked = false;
+        let isolated = self.tail_state.isolated_file.as_ref() == Some(&file.path);
+        // (dragged file, drop after this one rather than before)
+        let mut dropped: Option<(usize, bool)> = None;
+        let mut entry_rect = None;
+        let group_id = file.group_id.clone();
         
         // Scale indent based on font size
         let indent = depth as f32 * (self.tail_state.font_size * 1.0);
@@ -372,13 +599,18 @@ impl VisGrepApp {
             ui.add_space(indent);
             
             // Activity indicator
-            let indicator = if file.is_active { "●" } else { "○" };
-            let color = if file.is_active {
-                egui::Color32::from_rgb(0, 255, 0)
+            if file.missing {
+                ui.colored_label(egui::Color32::from_rgb(255, 80, 80), "✖")
+                    .on_hover_text("File not found - it will be picked up again if it reappears");
             } else {
-                egui::Color32::GRAY
-            };
-            ui.colored_label(color, indicator);
+                let indicator = if file.is_active { "●" } else { "○" };
+                let color = if file.is_active {
+                    egui::Color32::from_rgb(0, 255, 0)
+                } else {
+                    egui::Color32::GRAY
+                };
+                ui.colored_label(color, indicator);
+            }
 
             // Filename (selectable) - use calculated max width for alignment
             let selected = self.tail_state.preview_selected_file == Some(file_idx);
@@ -389,11 +621,31 @@ impl VisGrepApp {
                 .and_then(|p| p.to_str())
                 .unwrap_or("");
 
-            // Use horizontal with fixed width and clip content
+            // Use horizontal with fixed width and clip content. Files in a layout group
+            // can be dragged onto a sibling to reorder the group.
+            let sense = if group_id.is_some() { egui::Sense::click_and_drag() } else { egui::Sense::click() };
             let (rect, response) = ui.allocate_exact_size(
                 egui::vec2(entry_width, self.tail_state.font_size + 4.0),
-                egui::Sense::click()
+                sense
             );
+            entry_rect = Some(rect);
+
+            if let Some(group_id) = &group_id {
+                response.dnd_set_drag_payload(DraggedTailFile { group_id: group_id.clone(), file_idx });
+
+                let sibling_payload = |payload: std::sync::Arc<DraggedTailFile>| {
+                    (payload.group_id == *group_id && payload.file_idx != file_idx).then_some(payload.file_idx)
+                };
+                if let (Some(_), Some(pointer)) = (response.dnd_hover_payload().and_then(sibling_payload), ui.ctx().pointer_interact_pos()) {
+                    // Insertion marker on the half of the row the file would land on
+                    let after = pointer.y > rect.center().y;
+                    let y = if after { rect.bottom() } else { rect.top() };
+                    ui.painter().hline(rect.x_range(), y, egui::Stroke::new(2.0, ui.visuals().selection.stroke.color));
+                    if let Some(dragged) = response.dnd_release_payload().and_then(sibling_payload) {
+                        dropped = Some((dragged, after));
+                    }
+                }
+            }
 
             if ui.is_rect_visible(rect) {
                 // Save current clip rect
@@ -426,7 +678,7 @@ impl VisGrepApp {
                     egui::Align2::LEFT_CENTER,
                     &file.display_name,
                     egui::FontId::proportional(self.tail_state.font_size),
-                    visuals.text_color(),
+                    file.color.unwrap_or(visuals.text_color()),
                 );
 
                 // Restore original clip rect
@@ -434,11 +686,28 @@ impl VisGrepApp {
             }
 
             if response.clicked() {
-                self.tail_state.preview_selected_file = Some(file_idx);
-                self.tail_state.preview_needs_reload = true;
-                self.tail_state.preview_mode = PreviewMode::Following;
+                preview_clicked = true;
             }
 
+            response.context_menu(|ui| {
+                ui.horizontal(|ui| {
+                    let mut color = file.color.unwrap_or_else(|| get_color_for_file(&file.display_name));
+                    if ui.color_edit_button_srgba(&mut color).changed() {
+                        file.color = Some(color);
+                    }
+                    ui.label("Color");
+                });
+                if ui.add_enabled(file.color.is_some(), egui::Button::new("Reset color")).clicked() {
+                    file.color = None;
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui.button("Clear its lines from output").clicked() {
+                    clear_output_clicked = true;
+                    ui.close_menu();
+                }
+            });
+
             // Show tooltip with full path and parent directory
             response.on_hover_text(format!(
                 "Full path: {}\nDirectory: {}",
@@ -452,8 +721,20 @@ impl VisGrepApp {
                 egui::Label::new(format!("{:.1} KB", file.last_size as f64 / 1024.0))
             );
 
+            // Time since the file was last written, to spot logs that went quiet
+            let age = file.modified.and_then(|modified| modified.elapsed().ok());
+            ui.add_sized(
+                egui::vec2(60.0, 20.0),
+                egui::Label::new(
+                    egui::RichText::new(age.map(|age| format!("{} ago", format_age(age))).unwrap_or_default())
+                        .color(egui::Color32::GRAY),
+                ),
+            ).on_hover_text("Last modified");
+
             // Activity info - show log level counts if available, otherwise line count
-            let (status_text, status_color) = if file.is_active && file.lines_since_last_read > 0 {
+            let (status_text, status_color) = if file.missing {
+                ("(missing)".to_string(), egui::Color32::from_rgb(255, 80, 80))
+            } else if file.is_active && file.lines_since_last_read > 0 {
                 // Check if we have level counts to display
                 if !file.level_counts_since_last_read.is_empty() {
                     // Build a compact display of significant log levels
@@ -504,6 +785,18 @@ impl VisGrepApp {
             if ui.small_button(if file.paused { "▶" } else { "⏸" }).clicked() {
                 file.paused = !file.paused;
             }
+
+            // Mute button - keeps reading the file but hides its lines from the output
+            let mute_hint = if file.muted { "Show this file's lines in the output" } else { "Hide this file's lines from the output (keeps tailing)" };
+            if ui.small_button(if file.muted { "🔇" } else { "🔊" }).on_hover_text(mute_hint).clicked() {
+                file.muted = !file.muted;
+            }
+
+            // Isolate button - show only this file's lines in the output
+            let isolate_hint = if isolated { "Show all files in the output again" } else { "Show only this file's lines in the output (i in the tree)" };
+            if ui.add(egui::Button::new("🎯").small().selected(isolated)).on_hover_text(isolate_hint).clicked() {
+                isolate_clicked = true;
+            }
             
             // Copy path button (small)
             if ui.small_button("📋").on_hover_text("Copy full path").clicked() {
@@ -528,7 +821,26 @@ impl VisGrepApp {
         
         // Handle open in editor outside closure to avoid borrowing issues
         if open_in_editor_clicked {
-            self.open_file_in_editor(&file_path);
+            self.open_file_in_editor(&file_path, None);
+        }
+        if let Some(rect) = entry_rect {
+            self.paint_tree_cursor(ui, TreeItem::File(file_idx), rect);
+        }
+        if isolate_clicked {
+            self.toggle_isolated_file(file_idx);
+        }
+        if preview_clicked {
+            self.tail_state.tree_cursor = Some(TreeItem::File(file_idx));
+            self.select_preview_file(file_idx);
+        }
+        if clear_output_clicked {
+            let path = self.tail_state.files[file_idx].path.clone();
+            self.tail_state.clear_file_output(&path);
+        }
+        if let (Some((dragged_idx, after)), Some(group_id), Some(layout)) = (dropped, &group_id, &mut self.tail_state.layout) {
+            if layout.move_file_entry(group_id, dragged_idx, file_idx, after) {
+                info!("Moved {} within its group", self.tail_state.files[dragged_idx].display_name);
+            }
         }
         
         // Add minimal spacing between rows
@@ -575,12 +887,61 @@ impl VisGrepApp {
                 }
                 if ui.button("Clear").clicked() {
                     self.tail_state.output_buffer.clear();
+                    self.tail_state.output_rows = None;
+                    self.tail_state.buffer_level_counts.clear();
+                    self.tail_state.level_histogram.clear();
+                    self.tail_state.expanded_lines.clear();
                     self.tail_state.total_lines_received = 0;
                     self.tail_state.lines_dropped = 0;
                 }
+                self.render_output_bookmarks_menu(ui);
             });
         });
 
+        let isolated_path = self.tail_state.isolated_file.clone();
+        let isolated_name = isolated_path.as_ref().map(|path| {
+            self.tail_state.files.iter()
+                .find(|file| file.path == *path)
+                .map(|file| file.display_name.clone())
+                .unwrap_or_else(|| path.display().to_string())
+        });
+        if let Some(name) = &isolated_name {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(format!("🎯 Showing only {}", name))
+                        .strong()
+                        .color(egui::Color32::from_rgb(255, 200, 100)),
+                );
+                if ui.small_button("✖ Show all").clicked() {
+                    self.tail_state.isolated_file = None;
+                }
+            });
+        }
+
+        // Live grep across all files; the match count is filled in once the rows are filtered
+        let grep_row = ui.horizontal(|ui| {
+            ui.label("Grep:");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.tail_state.output_grep.query)
+                    .desired_width(250.0)
+                    .hint_text("regex across all files"),
+            );
+            let case_changed = ui.checkbox(&mut self.tail_state.output_grep.case_sensitive, "Aa")
+                .on_hover_text("Case sensitive")
+                .changed();
+            if response.changed() || case_changed {
+                self.tail_state.output_grep.recompile();
+            }
+            if !self.tail_state.output_grep.query.is_empty() && ui.small_button("✖").clicked() {
+                self.tail_state.output_grep.query.clear();
+                self.tail_state.output_grep.recompile();
+            }
+            if let Some(error) = &self.tail_state.output_grep.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), "invalid regex")
+                    .on_hover_text(error);
+            }
+        });
+
         // Log level filter controls
         ui.horizontal(|ui| {
             ui.label("Level:");
@@ -616,128 +977,203 @@ impl VisGrepApp {
             {
                 // Checkbox state updated automatically
             }
+
+            ui.separator();
+            self.render_source_selection(ui);
+
+            ui.separator();
+            self.render_column_view_controls(ui);
         });
 
+        self.render_level_sparkline(ui);
+        if self.tail_state.show_source_legend && self.tail_state.files.len() > 1 {
+            self.render_source_legend(ui);
+        }
+
         ui.separator();
 
         // Output area - use all available space
-        let scroll_output = egui::ScrollArea::vertical()
-            .id_salt("tail_output_scroll")
-            .auto_shrink([false, false])
-            .stick_to_bottom(self.tail_state.auto_scroll);
+        let is_filtered = self.tail_state.tree_filter.active &&
+                         self.tail_state.tree_filter.apply_to_output;
+
+        // Rows surviving the isolation, mute, tree, level and grep filters; only rebuilt
+        // over the whole buffer when one of those changed
+        self.tail_state.refresh_output_rows();
+        let jump_to_seq = self.tail_state.output_scroll_to_seq.take();
+        let empty_rows = VecDeque::new();
+        let visible_lines = self.tail_state.output_rows.as_ref().map_or(&empty_rows, |rows| &rows.rows);
+
+        if self.tail_state.output_grep.is_active() {
+            let match_count: usize = visible_lines.iter().map(|(_, count)| count).sum();
+            ui.painter().text(
+                grep_row.response.rect.right_center(),
+                egui::Align2::RIGHT_CENTER,
+                format!("{} matching lines", match_count),
+                egui::TextStyle::Body.resolve(ui.style()),
+                egui::Color32::from_rgb(255, 200, 100),
+            );
+        }
 
-        scroll_output.show(ui, |ui| {
-            // Add horizontal scrolling for long lines
-            egui::ScrollArea::horizontal()
-                .id_salt("tail_output_h_scroll")
+        // Rows are laid out with ui.horizontal, which is never shorter than interact_size.y
+        let font_id = egui::FontId::new(self.tail_state.font_size, egui::FontFamily::Monospace);
+        let row_height = ui.fonts(|f| f.row_height(&font_id))
+            .max(ui.spacing().interact_size.y);
+
+        if visible_lines.is_empty() {
+            egui::ScrollArea::vertical()
+                .id_salt("tail_output_scroll")
+                .auto_shrink([false, false])
                 .show(ui, |ui| {
-                    ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
-                    
-                    // Apply custom font size
-                    let font_id = egui::FontId::new(self.tail_state.font_size, egui::FontFamily::Monospace);
-                    ui.style_mut().text_styles.insert(egui::TextStyle::Monospace, font_id);
+                    if self.tail_state.output_grep.is_active() && !self.tail_state.output_buffer.is_empty() {
+                        ui.label(
+                            egui::RichText::new("No lines match the grep yet")
+                                .italics()
+                                .color(egui::Color32::from_rgb(255, 200, 100)),
+                        );
+                    } else if is_filtered && !self.tail_state.output_buffer.is_empty() {
+                        ui.label(
+                            egui::RichText::new("No output from filtered files")
+                                .italics()
+                                .color(egui::Color32::from_rgb(255, 200, 100)),
+                        );
+                    } else {
+                        ui.label(
+                            egui::RichText::new("Waiting for log output...")
+                                .italics()
+                                .color(egui::Color32::GRAY),
+                        );
+                    }
+                });
+        } else {
+            let mut row_action = None;
+            let mut column_count = 0;
+            let mut bottom_seq = None;
+
+            // Bookmark jumps: rows outside the viewport aren't laid out, so go via the row index
+            let row_pitch = row_height + ui.spacing().item_spacing.y;
+            let jump_offset = jump_to_seq.map(|seq| {
+                let row = visible_lines.partition_point(|(row_seq, _)| *row_seq < seq);
+                (row as f32 * row_pitch - ui.available_height() / 2.0).max(0.0)
+            });
+            let scroll_area = egui::ScrollArea::both();
+            let scroll_area = match jump_offset {
+                Some(offset) => scroll_area.vertical_scroll_offset(offset),
+                None => scroll_area,
+            };
 
-                    let is_filtered = self.tail_state.tree_filter.active && 
-                                     self.tail_state.tree_filter.apply_to_output;
-                    
-                    for log_line in &self.tail_state.output_buffer {
-                        // Check if this line should be visible based on tree filter
-                        if is_filtered {
-                            // Find the file that generated this log line
-                            let should_show = self.tail_state.files.iter().any(|file| {
-                                file.display_name == log_line.source_file &&
-                                filter::tree::is_file_visible(
-                                    &self.tail_state.tree_filter,
-                                    &file.path.to_string_lossy(),
-                                    &file.display_name
-                                )
-                            });
+            // Only the rows inside the viewport are laid out each frame
+            scroll_area
+                .id_salt("tail_output_scroll")
+                .auto_shrink([false, false])
+                .stick_to_bottom(self.tail_state.auto_scroll)
+                .show_rows(ui, row_height, visible_lines.len(), |ui, row_range| {
+                    ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
 
-                            if !should_show {
-                                continue;
-                            }
-                        }
+                    // Apply custom font size
+                    ui.style_mut().text_styles.insert(egui::TextStyle::Monospace, font_id.clone());
+
+                    let lines: Vec<_> = row_range
+                        .filter_map(|row| {
+                            let (seq, repeat_count) = visible_lines[row];
+                            self.tail_state.find_output_line(seq).map(|line| (line, repeat_count))
+                        })
+                        .collect();
+
+                    // Redact first so masked values never leak through column widths
+                    let contents: Vec<_> = lines.iter()
+                        .map(|(line, _)| self.redactor.redact(&line.content))
+                        .collect();
+
+                    // Column view: widths are computed over the visible window only
+                    let column_view = &self.tail_state.column_view;
+                    let widths = column_view.enabled.then(|| {
+                        columns::column_widths(contents.iter().map(|c| c.as_ref()), &column_view.delimiter)
+                    });
+                    column_count = widths.as_ref().map_or(0, |w| w.len());
 
-                        // Check if this line should be visible based on log level filter
-                        if !self.tail_state.log_level_filter.should_show_line(
-                            &log_line.content,
-                            &self.log_detector
-                        ) {
-                            continue;
+                    for (&(log_line, repeat_count), content) in lines.iter().zip(&contents) {
+                        let display = match &widths {
+                            Some(widths) => columns::format_columns(content, &column_view.delimiter, widths, &column_view.hidden),
+                            None => content.to_string(),
+                        };
+                        if let Some(action) = self.render_output_line(ui, log_line, &display, repeat_count) {
+                            row_action = Some(action);
                         }
+                        bottom_seq = Some(log_line.seq);
+                    }
+                });
 
-                        ui.horizontal(|ui| {
-                            ui.spacing_mut().item_spacing.x = 4.0;
-
-                            // Timestamp (relative)
-                            let elapsed = log_line.timestamp.elapsed();
-                            let secs = elapsed.as_secs();
-                            let time_str = if secs < 60 {
-                                format!("{}s", secs)
-                            } else if secs < 3600 {
-                                format!("{}m", secs / 60)
-                            } else {
-                                format!("{}h", secs / 3600)
-                            };
-                            ui.label(egui::RichText::new(time_str).color(egui::Color32::GRAY));
-
-                            // Source file with color
-                            let color = get_color_for_file(&log_line.source_file);
-                            ui.colored_label(color, format!("[{}]", log_line.source_file));
+            self.tail_state.output_bottom_seq = bottom_seq;
 
-                            // Content with log level coloring
-                            let detected_level = self.log_detector.detect(&log_line.content);
-                            let level_color = self.config.log_format.get_color_scheme().get_color(detected_level);
-                            ui.colored_label(level_color, &log_line.content);
-                        });
-                    }
+            if self.tail_state.column_view.enabled {
+                self.tail_state.column_view.column_count = column_count;
+            }
 
-                    // Check if we're showing nothing due to filtering
-                    let visible_count = self.tail_state.output_buffer.iter().filter(|log_line| {
-                        // Check tree filter
-                        if is_filtered {
-                            let tree_visible = self.tail_state.files.iter().any(|file| {
-                                file.display_name == log_line.source_file &&
-                                filter::tree::is_file_visible(
-                                    &self.tail_state.tree_filter,
-                                    &file.path.to_string_lossy(),
-                                    &file.display_name
-                                )
-                            });
-                            if !tree_visible {
-                                return false;
-                            }
-                        }
+            match row_action {
+                Some(OutputRowAction::ShowJson(json)) => self.tail_state.output_json_view = Some(json),
+                Some(OutputRowAction::ToggleBookmark(seq)) => self.tail_state.toggle_output_bookmark(seq),
+                Some(OutputRowAction::ToggleExpanded(seq)) => self.tail_state.toggle_expanded_line(seq),
+                Some(OutputRowAction::CopyLine(seq)) => self.copy_output_line(seq),
+                None => {}
+            }
+        }
 
-                        // Check log level filter
-                        self.tail_state.log_level_filter.should_show_line(
-                            &log_line.content,
-                            &self.log_detector
-                        )
-                    }).count();
-                    
-                    if visible_count == 0 {
-                        if is_filtered && !self.tail_state.output_buffer.is_empty() {
-                            ui.label(
-                                egui::RichText::new("No output from filtered files")
-                                    .italics()
-                                    .color(egui::Color32::from_rgb(255, 200, 100)),
-                            );
-                        } else {
-                            ui.label(
-                                egui::RichText::new("Waiting for log output...")
-                                    .italics()
-                                    .color(egui::Color32::GRAY),
-                            );
-                        }
-                    }
-                });
-        });
+        widgets::json_view::show_json_window(ui.ctx(), "JSON (output)", &mut self.tail_state.output_json_view, &self.redactor);
 
         // Status bar
         ui.separator();
         ui.horizontal(|ui| {
             ui.checkbox(&mut self.tail_state.auto_scroll, "Auto-scroll");
+            ui.checkbox(&mut self.tail_state.collapse_repeats, "Collapse repeats")
+                .on_hover_text("Show runs of identical consecutive lines as a single row");
+            ui.checkbox(&mut self.tail_state.show_source_legend, "Legend")
+                .on_hover_text("Show each source's color and activity above the output");
+            if ui.checkbox(&mut self.config.ui.highlight_level_token, "Level tags")
+                .on_hover_text("Mark the token each line's level was detected from")
+                .changed()
+            {
+                if let Err(e) = self.config.save() {
+                    log::error!("Failed to save config: {}", e);
+                }
+            }
+
+            ui.toggle_value(&mut self.sound_alert.enabled, "🔔 Sound")
+                .on_hover_text("Play a sound when a line hits the alert level or patterns (config: sound_alert)");
+
+            // Compact output: give the log content the width the time/source columns take
+            let compact = self.config.ui.output_hide_time || self.config.ui.output_hide_source;
+            ui.menu_button(if compact { "Compact ✔" } else { "Compact" }, |ui| {
+                let time_changed = ui.checkbox(&mut self.config.ui.output_hide_time, "Hide time").changed();
+                let source_changed = ui.checkbox(&mut self.config.ui.output_hide_source, "Hide source").changed();
+                let mut format_changed = false;
+                ui.add_enabled_ui(!self.config.ui.output_hide_time, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Time:");
+                        for format in RelativeTimeFormat::ALL {
+                            format_changed |= ui
+                                .radio_value(&mut self.config.ui.relative_time_format, format, format.label())
+                                .changed();
+                        }
+                    });
+                });
+                if time_changed || source_changed || format_changed {
+                    if let Err(e) = self.config.save() {
+                        log::error!("Failed to save config: {}", e);
+                    }
+                }
+            });
+
+            // Highlight rules: the toggle switches them all, the button opens the editor
+            let rule_count = self.config.highlight_rules.rules.len();
+            if ui.toggle_value(&mut self.config.highlight_rules.enabled, format!("🎨 Rules ({})", rule_count))
+                .on_hover_text("Highlight rule matches in the output and preview (config: highlight_rules)")
+                .changed()
+            {
+                self.apply_highlight_rules(true);
+            }
+            if ui.small_button("✏").on_hover_text("Edit highlight rules").clicked() {
+                self.tail_state.show_highlight_rules = !self.tail_state.show_highlight_rules;
+            }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 let buffer_pct = if self.tail_state.max_buffer_lines > 0 {
@@ -768,6 +1204,679 @@ impl VisGrepApp {
                 }
             });
         });
+        self.render_aggregate_stats(ui);
+    }
+
+    /// Totals across every tailed file: lines and bytes read, the recent line rate, and
+    /// how the buffered lines break down by level
+    fn render_aggregate_stats(&mut self, ui: &mut egui::Ui) {
+        use log_parser::LogLevel;
+
+        let total_lines: usize = self.tail_state.files.iter().map(|f| f.total_lines_read).sum();
+        let total_bytes: u64 = self.tail_state.files.iter().map(|f| f.total_bytes_read).sum();
+        // Over the last minute of completed histogram buckets
+        let rate = self.tail_state.level_histogram.lines_per_sec(Instant::now(), 6);
+
+        let scheme = self.config.log_format.get_color_scheme();
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "Σ Read: {} lines, {:.1} MB  Rate: {:.1} lines/s",
+                total_lines,
+                total_bytes as f64 / (1024.0 * 1024.0),
+                rate
+            ))
+            .on_hover_text("Totals across all tailed files; the rate is averaged over the last minute");
+
+            ui.separator();
+            ui.label("Buffer:");
+            let levels = [
+                (LogLevel::Fatal, "FATAL"),
+                (LogLevel::Error, "ERROR"),
+                (LogLevel::Warn, "WARN"),
+                (LogLevel::Info, "INFO"),
+                (LogLevel::Debug, "DEBUG"),
+                (LogLevel::Trace, "TRACE"),
+                (LogLevel::Unknown, "other"),
+            ];
+            for (level, name) in levels {
+                let count = self.tail_state.buffer_level_counts.get(&level).copied().unwrap_or(0);
+                if count > 0 {
+                    ui.colored_label(scheme.get_color(level), format!("{} {}", count, name));
+                }
+            }
+        });
+    }
+
+    /// Warnings and errors per time bucket over the last few minutes, stacked as bars.
+    /// Clicking a bucket scrolls the output to the first line that arrived in it.
+    fn render_level_sparkline(&mut self, ui: &mut egui::Ui) {
+        let now = Instant::now();
+        let slots = self.tail_state.level_histogram.slots(now);
+        let peak = slots.iter().flatten().map(|b| b.warnings + b.errors).max().unwrap_or(0).max(1);
+
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), 24.0),
+            egui::Sense::click(),
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+        let scheme = self.config.log_format.get_color_scheme();
+        let error_color = scheme.get_color(log_parser::LogLevel::Error);
+        let warn_color = scheme.get_color(log_parser::LogLevel::Warn);
+        let slot_width = rect.width() / slots.len() as f32;
+        let height_for = |count: usize| (rect.height() - 2.0) * count as f32 / peak as f32;
+
+        for (slot, bucket) in slots.iter().enumerate() {
+            let Some(bucket) = bucket else { continue };
+            let left = rect.left() + slot as f32 * slot_width + 1.0;
+            let right = left + slot_width - 2.0;
+            let error_top = rect.bottom() - height_for(bucket.errors);
+            let warn_top = error_top - height_for(bucket.warnings);
+            if bucket.errors > 0 {
+                painter.rect_filled(egui::Rect::from_x_y_ranges(left..=right, error_top..=rect.bottom()), 0.0, error_color);
+            }
+            if bucket.warnings > 0 {
+                painter.rect_filled(egui::Rect::from_x_y_ranges(left..=right, warn_top..=error_top), 0.0, warn_color);
+            }
+            if bucket.errors == 0 && bucket.warnings == 0 {
+                // A tick so quiet-but-active buckets are distinguishable from no output at all
+                painter.hline(left..=right, rect.bottom() - 1.0, egui::Stroke::new(1.0, egui::Color32::DARK_GRAY));
+            }
+        }
+
+        let hovered_slot = response.hover_pos().map(|pos| {
+            (((pos.x - rect.left()) / slot_width) as usize).min(slots.len() - 1)
+        });
+        let Some(slot) = hovered_slot else { return };
+        let age = sparkline::BUCKET_WIDTH * (slots.len() - 1 - slot) as u32;
+        let bucket = slots[slot];
+        let tooltip = match bucket {
+            Some(bucket) => format!(
+                "{} ago: {} errors, {} warnings ({} lines)\nClick to jump to this window",
+                format_age(age),
+                bucket.errors,
+                bucket.warnings,
+                bucket.lines
+            ),
+            None => format!("{} ago: no output", format_age(age)),
+        };
+        let clicked = response.clicked();
+        response.on_hover_text(tooltip);
+
+        if let (true, Some(bucket)) = (clicked, bucket) {
+            self.tail_state.output_scroll_to_seq = Some(bucket.first_seq);
+            self.tail_state.auto_scroll = false;
+        }
+    }
+
+    /// Checkbox per file for which sources appear in the combined output. Unchecking a
+    /// file mutes it, the same as its 🔊 button in the tree.
+    fn render_source_selection(&mut self, ui: &mut egui::Ui) {
+        let total = self.tail_state.files.len();
+        let shown = self.tail_state.files.iter().filter(|f| !f.muted).count();
+        let label = if shown == total {
+            "Sources: all".to_string()
+        } else {
+            format!("Sources: {}/{}", shown, total)
+        };
+
+        ui.menu_button(label, |ui| {
+            ui.horizontal(|ui| {
+                if ui.small_button("All").clicked() {
+                    self.tail_state.files.iter_mut().for_each(|f| f.muted = false);
+                }
+                if ui.small_button("None").clicked() {
+                    self.tail_state.files.iter_mut().for_each(|f| f.muted = true);
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for file in &mut self.tail_state.files {
+                    let mut show = !file.muted;
+                    let color = file.color.unwrap_or_else(|| get_color_for_file(&file.display_name));
+                    if ui.checkbox(&mut show, egui::RichText::new(&file.display_name).color(color))
+                        .on_hover_text(file.path.display().to_string())
+                        .changed()
+                    {
+                        file.muted = !show;
+                    }
+                }
+            });
+        });
+    }
+
+    /// One entry per source: its activity dot and name in the color of its [source] tag.
+    /// Muted sources are dimmed; clicking an entry previews that file.
+    fn render_source_legend(&mut self, ui: &mut egui::Ui) {
+        let mut clicked = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 10.0;
+            for (file_idx, file) in self.tail_state.files.iter().enumerate() {
+                let (dot, dot_color) = if file.missing {
+                    ("✖", egui::Color32::from_rgb(255, 80, 80))
+                } else if file.is_active {
+                    ("●", egui::Color32::from_rgb(0, 255, 0))
+                } else {
+                    ("○", egui::Color32::GRAY)
+                };
+                let color = file.color.unwrap_or_else(|| get_color_for_file(&file.display_name));
+                let mut name = egui::RichText::new(&file.display_name).color(color);
+                if file.muted {
+                    name = name.weak().strikethrough();
+                }
+
+                let mut job = egui::text::LayoutJob::default();
+                egui::RichText::new(format!("{} ", dot)).color(dot_color).append_to(
+                    &mut job,
+                    ui.style(),
+                    egui::FontSelection::Default,
+                    egui::Align::Center,
+                );
+                name.append_to(&mut job, ui.style(), egui::FontSelection::Default, egui::Align::Center);
+
+                let selected = self.tail_state.preview_selected_file == Some(file_idx);
+                let last_line = if file.total_lines_read > 0 {
+                    format!(", last {} ago", format_age(file.last_activity.elapsed()))
+                } else {
+                    String::new()
+                };
+                let response = ui
+                    .selectable_label(selected, job)
+                    .on_hover_text(format!(
+                        "{}\n{} lines read{}{}",
+                        file.path.display(),
+                        file.total_lines_read,
+                        last_line,
+                        if file.muted { "\nMuted in the output" } else { "" }
+                    ));
+                if response.clicked() {
+                    clicked = Some(file_idx);
+                }
+            }
+        });
+
+        if let Some(file_idx) = clicked {
+            self.select_preview_file(file_idx);
+        }
+    }
+
+    /// Column view toggle, delimiter choice and per-column visibility
+    fn render_column_view_controls(&mut self, ui: &mut egui::Ui) {
+        let column_view = &mut self.tail_state.column_view;
+        ui.checkbox(&mut column_view.enabled, "Columns")
+            .on_hover_text("Split lines on a delimiter and align them as columns");
+
+        if !column_view.enabled {
+            return;
+        }
+
+        egui::ComboBox::from_id_salt("column_delimiter")
+            .selected_text(column_view.delimiter_label())
+            .width(90.0)
+            .show_ui(ui, |ui| {
+                for (label, delimiter) in columns::DELIMITER_PRESETS {
+                    if ui.selectable_label(column_view.delimiter == *delimiter, *label).clicked() {
+                        column_view.delimiter = delimiter.to_string();
+                        column_view.hidden.clear();
+                    }
+                }
+            });
+
+        ui.menu_button("Show/Hide", |ui| {
+            if column_view.column_count == 0 {
+                ui.label("No columns in view");
+            }
+            for idx in 0..column_view.column_count {
+                let mut visible = !column_view.hidden.contains(&idx);
+                if ui.checkbox(&mut visible, format!("Column {}", idx + 1)).changed() {
+                    if visible {
+                        column_view.hidden.remove(&idx);
+                    } else {
+                        column_view.hidden.insert(idx);
+                    }
+                }
+            }
+            if !column_view.hidden.is_empty() && ui.button("Show all").clicked() {
+                column_view.hidden.clear();
+            }
+        });
+    }
+
+    /// Bookmark list for the output: live bookmarks jump to their line, dropped ones are greyed
+    fn render_output_bookmarks_menu(&mut self, ui: &mut egui::Ui) {
+        let count = self.tail_state.output_bookmarks.len();
+        let mut jump_to = None;
+        let mut prune = false;
+        let mut clear = false;
+
+        ui.menu_button(format!("★ {}", count), |ui| {
+            if count == 0 {
+                ui.label("Click ☆ in the gutter or press Ctrl+B to bookmark a line");
+                return;
+            }
+
+            for (seq, bookmark) in &self.tail_state.output_bookmarks {
+                let age = self.config.ui.relative_time_format.format(bookmark.timestamp.elapsed());
+                let label = format!("{} [{}] {}", age, bookmark.source_file, self.redactor.redact(&bookmark.content));
+                if self.tail_state.find_output_line(*seq).is_some() {
+                    if ui.selectable_label(false, label).clicked() {
+                        jump_to = Some(*seq);
+                        ui.close_menu();
+                    }
+                } else {
+                    ui.label(egui::RichText::new(format!("{} (dropped)", label)).color(egui::Color32::DARK_GRAY));
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                prune = ui.button("Prune dropped").clicked();
+                clear = ui.button("Clear all").clicked();
+            });
+            ui.label(egui::RichText::new("F2 / Shift+F2: next / previous").small().color(egui::Color32::GRAY));
+        });
+
+        if let Some(seq) = jump_to {
+            self.tail_state.last_bookmark_jump = Some(seq);
+            self.tail_state.output_scroll_to_seq = Some(seq);
+            self.tail_state.auto_scroll = false;
+        }
+        if prune {
+            self.tail_state.prune_output_bookmarks();
+        }
+        if clear {
+            self.tail_state.output_bookmarks.clear();
+            self.tail_state.last_bookmark_jump = None;
+        }
+    }
+
+    /// Render a single row of the combined output; `display` is the (redacted, possibly
+    /// column-formatted) text shown for the line and repeat_count > 1 marks a collapsed run.
+    /// Returns what the user clicked on the row, if anything.
+    fn render_output_line(&self, ui: &mut egui::Ui, log_line: &LogLine, display: &str, repeat_count: usize) -> Option<OutputRowAction> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 4.0;
+
+            // Bookmark gutter
+            let bookmarked = self.tail_state.output_bookmarks.contains_key(&log_line.seq);
+            let star = if bookmarked {
+                egui::RichText::new("★").color(egui::Color32::YELLOW)
+            } else {
+                egui::RichText::new("☆").color(egui::Color32::from_gray(70))
+            };
+            if ui.add(egui::Label::new(star).sense(egui::Sense::click()))
+                .on_hover_text("Toggle bookmark")
+                .clicked()
+            {
+                action = Some(OutputRowAction::ToggleBookmark(log_line.seq));
+            }
+
+            // Timestamp (relative)
+            if !self.config.ui.output_hide_time {
+                let time_str = self.config.ui.relative_time_format.format(log_line.timestamp.elapsed());
+                ui.label(egui::RichText::new(time_str).color(egui::Color32::GRAY));
+            }
+
+            // Source file with color
+            if !self.config.ui.output_hide_source {
+                let color = self.tail_state.file_color(log_line);
+                ui.colored_label(color, format!("[{}]", log_line.source_file));
+            }
+
+            // Over-long lines are cut short until expanded
+            let truncated = truncate::truncate_line(display, self.config.ui.max_display_line_len);
+            let expanded = self.tail_state.expanded_lines.contains(&log_line.seq);
+            let display = match truncated {
+                Some((shown, _)) if !expanded => shown,
+                _ => display,
+            };
+
+            // Content with log level coloring
+            let level_color = self.config.log_format.get_color_scheme().get_color(log_line.level);
+            let grep_matches = self.tail_state.output_grep.find_matches(display);
+            let rule_spans = self.tail_state.rule_highlighter.find_spans(display);
+            let keyword_matches = self.tail_state.keyword_highlighter.find_matches(display);
+            if let Some(color) = file_event_color(&log_line.content) {
+                ui.label(
+                    egui::RichText::new(display)
+                        .strong()
+                        .color(color)
+                        .background_color(color.gamma_multiply(0.15)),
+                );
+            } else if !grep_matches.is_empty() {
+                ui.label(Self::highlight_job(ui.style(), display, &grep_matches, level_color, GREP_MATCH_COLORS));
+            } else if !rule_spans.is_empty() {
+                let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+                ui.label(highlight::rule_spans_job(font_id, display, &rule_spans, level_color));
+            } else if !keyword_matches.is_empty() {
+                // Keywords are highlighted in the text around any file:line links
+                let style = ui.style().clone();
+                let keyword_job = |start: usize, end: usize| {
+                    let in_gap: Vec<(usize, usize)> = keyword_matches
+                        .iter()
+                        .filter(|&&(match_start, match_end)| match_start < end && match_end > start)
+                        .map(|&(match_start, match_end)| (match_start.max(start) - start, match_end.min(end) - start))
+                        .collect();
+                    Self::highlight_job(&style, &display[start..end], &in_gap, level_color, KEYWORD_COLORS)
+                };
+                if let Some(link) = links::render_linkified_job(ui, display, keyword_job) {
+                    self.open_output_link(&log_line.source_path, &link);
+                }
+            } else if let Some((start, end)) = self.level_token(display) {
+                ui.label(filter::preview::level_token_job(ui, display, start, end, level_color));
+            } else if let Some(link) = links::render_linkified_text(ui, display, level_color) {
+                self.open_output_link(&log_line.source_path, &link);
+            }
+
+            if let Some((_, hidden)) = truncated {
+                let marker = if expanded { "▲ collapse".to_string() } else { truncate::hidden_marker(hidden) };
+                if ui.add(egui::Label::new(egui::RichText::new(marker).italics().color(egui::Color32::GRAY)).sense(egui::Sense::click()))
+                    .clicked()
+                {
+                    action = Some(OutputRowAction::ToggleExpanded(log_line.seq));
+                }
+                if ui.small_button("📋").on_hover_text("Copy the full line").clicked() {
+                    action = Some(OutputRowAction::CopyLine(log_line.seq));
+                }
+            }
+
+            if repeat_count > 1 {
+                ui.label(
+                    egui::RichText::new(format!("(repeated {} times)", repeat_count))
+                        .italics()
+                        .color(egui::Color32::GRAY),
+                );
+            }
+
+            if log_line.is_json
+                && ui.small_button("{ }").on_hover_text("Pretty-print JSON").clicked()
+            {
+                action = widgets::json_view::pretty_print_json(&log_line.content).map(OutputRowAction::ShowJson);
+            }
+        });
+        action
+    }
+
+    /// Copy an output line in full (redacted), however it's shown
+    fn copy_output_line(&self, seq: u64) {
+        let Some(line) = self.tail_state.find_output_line(seq) else {
+            return;
+        };
+        let content = self.redactor.redact(&line.content);
+        match arboard::Clipboard::new() {
+            Ok(mut clipboard) => match clipboard.set_text(content.to_string()) {
+                Ok(_) => info!("Copied output line ({} chars) to clipboard", content.len()),
+                Err(e) => log::error!("Failed to copy line: {}", e),
+            },
+            Err(e) => log::error!("Failed to access clipboard: {}", e),
+        }
+    }
+
+    /// Where the level token is in an output line, when level tags are on. Lines with
+    /// file links are left alone so the links stay clickable.
+    fn level_token(&self, display: &str) -> Option<(usize, usize)> {
+        if !self.config.ui.highlight_level_token || !links::find_file_links(display).is_empty() {
+            return None;
+        }
+        self.log_detector.detect_with_range(display).1
+    }
+
+    /// Line text with the given ranges (live grep matches or watch keywords) highlighted
+    /// in the (text, background) colors
+    fn highlight_job(
+        style: &egui::Style,
+        text: &str,
+        matches: &[(usize, usize)],
+        color: egui::Color32,
+        (match_color, match_background): (egui::Color32, egui::Color32),
+    ) -> egui::text::LayoutJob {
+        let font_id = egui::TextStyle::Monospace.resolve(style);
+        let plain = egui::TextFormat::simple(font_id.clone(), color);
+        let highlighted = egui::TextFormat {
+            color: match_color,
+            background: match_background,
+            ..egui::TextFormat::simple(font_id, color)
+        };
+
+        let mut job = egui::text::LayoutJob::default();
+        let mut pos = 0;
+        for &(start, end) in matches {
+            job.append(&text[pos..start], 0.0, plain.clone());
+            job.append(&text[start..end], 0.0, highlighted.clone());
+            pos = end;
+        }
+        job.append(&text[pos..], 0.0, plain);
+        job
+    }
+
+    /// Open a file:line reference clicked in the output, resolving relative paths
+    /// against the directory of the file that logged it
+    fn open_output_link(&self, source_path: &std::path::Path, link: &links::FileLink) {
+        let path = links::resolve_link_path(&link.path, source_path);
+        if path.exists() {
+            self.open_file_in_editor(&path, Some(link.line));
+        } else {
+            info!("Linked file not found: {:?}", path);
+        }
+    }
+
+    /// Switch the preview to another file, remembering where the current one was being
+    /// read. A file left in Paused mode comes back at the same scroll offset; anything
+    /// else opens following the end. Each file keeps its own follow-line count.
+    fn select_preview_file(&mut self, file_idx: usize) {
+        let state = &mut self.tail_state;
+        if state.preview_selected_file == Some(file_idx) {
+            state.preview_needs_reload = true;
+            return;
+        }
+
+        if let Some(current) = state.preview_selected_file.and_then(|idx| state.files.get(idx)) {
+            let position = (state.preview_mode == PreviewMode::Paused).then_some(state.preview_scroll_offset);
+            match position {
+                Some(offset) => state.preview_positions.insert(current.path.clone(), offset),
+                None => state.preview_positions.remove(&current.path),
+            };
+        }
+
+        let saved = state.files.get(file_idx).and_then(|f| state.preview_positions.get(&f.path)).copied();
+        state.preview_selected_file = Some(file_idx);
+        state.preview_follow_lines = state
+            .files
+            .get(file_idx)
+            .and_then(|f| state.preview_follow_lines_by_file.get(&f.path))
+            .copied()
+            .unwrap_or(crate::DEFAULT_PREVIEW_FOLLOW_LINES);
+        state.text_viewer_state.pinned_lines.clear();
+        state.text_viewer_state.folds.clear();
+        state.text_viewer_state.marks.clear();
+        state.pending_marks = state
+            .files
+            .get(file_idx)
+            .filter(|f| f.stream.is_none())
+            .and_then(|f| marks::MarksFile::load(&f.path));
+        state.preview_needs_reload = true;
+        match saved {
+            Some(offset) => {
+                state.preview_mode = PreviewMode::Paused;
+                state.preview_scroll_offset = offset;
+            }
+            None => {
+                state.preview_mode = PreviewMode::Following;
+                state.preview_scroll_offset = 0.0;
+            }
+        }
+    }
+
+    /// Place marks loaded from the selected file's .marks file onto the preview lines they
+    /// were set on
+    pub(crate) fn apply_pending_marks(&mut self) {
+        let Some(saved) = self.tail_state.pending_marks.take() else {
+            return;
+        };
+        let state = &mut self.tail_state;
+        for (mark_char, mark) in saved.marks {
+            match mark.locate(&state.preview_content, state.preview_first_line) {
+                Some(idx) => {
+                    state.text_viewer_state.marks.insert(mark_char, idx);
+                }
+                None => info!("Mark '{}' (line {}) is not in the preview", mark_char, mark.line),
+            }
+        }
+    }
+
+    /// Write the preview's marks to `<file>.marks` next to the selected file. Marks already
+    /// saved for lines outside the preview window are kept; the line text is redacted.
+    fn save_preview_marks(&self) -> Result<std::path::PathBuf, String> {
+        let state = &self.tail_state;
+        let file = state
+            .preview_selected_file
+            .and_then(|idx| state.files.get(idx))
+            .ok_or_else(|| "No file selected".to_string())?;
+        let mut saved = marks::MarksFile::load(&file.path).unwrap_or_default();
+        for (&mark_char, &idx) in &state.text_viewer_state.marks {
+            if let Some(text) = state.preview_content.get(idx) {
+                saved.marks.insert(
+                    mark_char,
+                    marks::SavedMark {
+                        line: state.preview_first_line + idx + 1,
+                        text: self.redactor.redact(text).into_owned(),
+                    },
+                );
+            }
+        }
+        saved.save(&file.path)
+    }
+
+    /// Recompile the highlight rules after an edit, and save them to the config if asked
+    fn apply_highlight_rules(&mut self, save: bool) {
+        self.tail_state.rebuild_highlight_rules(&self.config.highlight_rules);
+        if save {
+            if let Err(e) = self.config.save() {
+                log::error!("Failed to save config: {}", e);
+            }
+        }
+    }
+
+    /// Window for editing the highlight rules: each rule's name, regex, colors and
+    /// switch, in the order they apply
+    pub fn render_highlight_rules_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.tail_state.show_highlight_rules;
+        let mut changed = false;
+        let mut save = false;
+        let mut remove = None;
+        let mut move_up = None;
+        egui::Window::new("🎨 Highlight rules")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(620.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new("Rules apply in order; where matches overlap, the earlier rule wins.").small().color(egui::Color32::GRAY));
+                save |= ui.checkbox(&mut self.config.highlight_rules.enabled, "All rules enabled").changed();
+                ui.separator();
+
+                let rules = &mut self.config.highlight_rules.rules;
+                let rule_count = rules.len();
+                egui::Grid::new("highlight_rules_grid").striped(true).show(ui, |ui| {
+                    for (idx, rule) in rules.iter_mut().enumerate() {
+                        save |= ui.checkbox(&mut rule.enabled, "").changed();
+                        let name = ui.add(egui::TextEdit::singleline(&mut rule.name).desired_width(100.0).hint_text("name"));
+                        let pattern = ui.add(
+                            egui::TextEdit::singleline(&mut rule.pattern)
+                                .desired_width(220.0)
+                                .font(egui::TextStyle::Monospace)
+                                .hint_text("regex"),
+                        );
+                        changed |= name.changed() || pattern.changed();
+                        save |= name.lost_focus() || pattern.lost_focus();
+
+                        for (color, label) in [(&mut rule.foreground, "text"), (&mut rule.background, "bg")] {
+                            match color.as_deref().and_then(crate::log_parser::LogColorScheme::parse_hex_color) {
+                                Some(mut picked) => {
+                                    ui.horizontal(|ui| {
+                                        if ui.color_edit_button_srgba(&mut picked).on_hover_text(label).changed() {
+                                            *color = Some(crate::color_to_hex(picked));
+                                            save = true;
+                                        }
+                                        if ui.small_button("×").on_hover_text(format!("No {} color", label)).clicked() {
+                                            *color = None;
+                                            save = true;
+                                        }
+                                    });
+                                }
+                                None => {
+                                    if ui.small_button(format!("+ {}", label)).clicked() {
+                                        let (text, background) = highlight::pattern_colors(idx);
+                                        *color = Some(crate::color_to_hex(if label == "bg" { background } else { text }));
+                                        save = true;
+                                    }
+                                }
+                            }
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(idx > 0, egui::Button::new("⬆").small()).on_hover_text("Apply earlier").clicked() {
+                                move_up = Some(idx);
+                            }
+                            if ui.add_enabled(idx + 1 < rule_count, egui::Button::new("⬇").small()).on_hover_text("Apply later").clicked() {
+                                move_up = Some(idx + 1);
+                            }
+                            if ui.small_button("🗑").on_hover_text("Delete rule").clicked() {
+                                remove = Some(idx);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+
+                for (name, error) in &self.tail_state.highlight_rule_errors {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 100, 100),
+                        format!("⚠ {}: {}", name, crate::search::error_summary(error)),
+                    )
+                    .on_hover_text(egui::RichText::new(error).monospace());
+                }
+
+                if ui.button("➕ Add rule").clicked() {
+                    let (text, _) = highlight::pattern_colors(self.config.highlight_rules.rules.len());
+                    self.config.highlight_rules.rules.push(config::HighlightRule {
+                        name: format!("Rule {}", self.config.highlight_rules.rules.len() + 1),
+                        pattern: String::new(),
+                        foreground: Some(crate::color_to_hex(text)),
+                        background: None,
+                        enabled: true,
+                    });
+                    save = true;
+                }
+            });
+
+        let rules = &mut self.config.highlight_rules.rules;
+        if let Some(idx) = move_up {
+            rules.swap(idx - 1, idx);
+            save = true;
+        }
+        if let Some(idx) = remove {
+            rules.remove(idx);
+            save = true;
+        }
+        if changed || save {
+            self.apply_highlight_rules(save);
+        }
+        self.tail_state.show_highlight_rules = open;
+    }
+
+    /// Store a named preview filter in the config, replacing any with the same name
+    fn save_preview_filter(&mut self, saved: config::SavedFilter) {
+        info!("Saved preview filter: {} -> {}", saved.name, saved.query);
+        match self.config.saved_filters.iter_mut().find(|existing| existing.name == saved.name) {
+            Some(existing) => *existing = saved,
+            None => self.config.saved_filters.push(saved),
+        }
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save config: {}", e);
+        }
     }
 
     pub fn render_tail_preview(&mut self, ui: &mut egui::Ui) {
@@ -824,6 +1933,92 @@ impl VisGrepApp {
                                 PreviewMode::Paused => PreviewMode::Following,
                             };
                         }
+
+                        if ui.button("⤓ Bottom").on_hover_text("Scroll to the end and follow (G)").clicked() {
+                            self.tail_state.preview_mode = PreviewMode::Following;
+                            self.tail_state.text_viewer_state.scroll_to_bottom = true;
+                        }
+                        let filtering = self.tail_state.preview_filter.active;
+                        let copy_hint = if filtering {
+                            "Copy the lines matching the filter (Shift+click: every line)"
+                        } else {
+                            "Copy every line in the preview"
+                        };
+                        if ui.button("📋 Copy all").on_hover_text(copy_hint).clicked() {
+                            let everything = !filtering || ui.input(|i| i.modifiers.shift);
+                            let content = &self.tail_state.preview_content;
+                            let lines: Vec<&str> = if everything {
+                                content.iter().map(String::as_str).collect()
+                            } else {
+                                self.tail_state
+                                    .preview_filter
+                                    .match_lines
+                                    .iter()
+                                    .filter_map(|&idx| content.get(idx).map(String::as_str))
+                                    .collect()
+                            };
+                            VisGrepApp::copy_to_clipboard(&self.redactor.redact(&lines.join("\n")), "preview lines");
+                        }
+                        let is_stream = self.tail_state.preview_selected_file
+                            .and_then(|idx| self.tail_state.files.get(idx))
+                            .is_some_and(|f| f.stream.is_some());
+                        if !is_stream {
+                            ui.menu_button(format!("🔖 Marks ({})", self.tail_state.text_viewer_state.marks.len()), |ui| {
+                                let state = &mut self.tail_state;
+                                let mut marks: Vec<(char, usize)> =
+                                    state.text_viewer_state.marks.iter().map(|(&c, &idx)| (c, idx)).collect();
+                                marks.sort();
+                                if marks.is_empty() {
+                                    ui.label("No marks set (m<letter> sets one)");
+                                }
+                                for (mark_char, idx) in marks {
+                                    let text = state.preview_content.get(idx).map(String::as_str).unwrap_or("");
+                                    let text = truncate::truncate_line(text, 60).map_or(text, |(shown, _)| shown);
+                                    let label = format!("'{}  {}: {}", mark_char, state.preview_first_line + idx + 1, text);
+                                    if ui.button(label).clicked() {
+                                        let viewer = &mut state.text_viewer_state;
+                                        viewer.goto_line_target = Some(idx);
+                                        viewer.last_navigated_line = Some(idx);
+                                        viewer.view_mode = widgets::ViewMode::Paused;
+                                        ui.close_menu();
+                                    }
+                                }
+                                ui.separator();
+                                if ui.button("💾 Save to .marks file")
+                                    .on_hover_text("Write these marks next to the log so they come back when it is opened again")
+                                    .clicked()
+                                {
+                                    match self.save_preview_marks() {
+                                        Ok(path) => info!("Marks written to {:?}", path),
+                                        Err(e) => log::error!("Failed to save marks: {}", e),
+                                    }
+                                    ui.close_menu();
+                                }
+                                if ui.button("📂 Reload from .marks file").clicked() {
+                                    let state = &mut self.tail_state;
+                                    state.pending_marks = state
+                                        .preview_selected_file
+                                        .and_then(|idx| state.files.get(idx))
+                                        .and_then(|f| marks::MarksFile::load(&f.path));
+                                    if state.pending_marks.is_some() {
+                                        state.text_viewer_state.marks.clear();
+                                    }
+                                    self.apply_pending_marks();
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+                        if ui.button("Clear")
+                            .on_hover_text("Empty the preview; it refills from the file on the next reload")
+                            .clicked()
+                        {
+                            self.tail_state.preview_content.clear();
+                            self.tail_state.text_viewer_state.line_formats.clear();
+                            self.tail_state.text_viewer_state.expanded_lines.clear();
+                            self.tail_state.text_viewer_state.folds.clear();
+                            self.tail_state.preview_filter.match_lines.clear();
+                            self.tail_state.preview_filter.current_match = None;
+                        }
                         
                         ui.separator();
                         
@@ -854,32 +2049,26 @@ impl VisGrepApp {
                         
                         ui.separator();
                         
-                        // Buffer size control
+                        // Buffer size control, kept per file
                         ui.label("Lines:");
+                        let mut follow_lines = self.tail_state.preview_follow_lines;
                         let response = ui.add(
-                            egui::DragValue::new(&mut self.tail_state.preview_follow_lines)
+                            egui::DragValue::new(&mut follow_lines)
                                 .speed(50.0)
                                 .range(100..=10000)
                                 .prefix("📜 ")
                         );
-                        
-                        if response.changed() {
-                            self.tail_state.preview_needs_reload = true;
-                        }
-                        
-                        response.on_hover_text("Number of lines to keep in buffer (100-10000)");
-                        
-                        if ui.small_button("500").on_hover_text("500 lines").clicked() {
-                            self.tail_state.preview_follow_lines = 500;
-                            self.tail_state.preview_needs_reload = true;
-                        }
-                        if ui.small_button("1K").on_hover_text("1000 lines").clicked() {
-                            self.tail_state.preview_follow_lines = 1000;
-                            self.tail_state.preview_needs_reload = true;
+                        let mut changed = response.changed();
+                        response.on_hover_text("Number of lines to keep in buffer for this file (100-10000)");
+
+                        for (label, lines) in [("500", 500), ("1K", 1000), ("5K", 5000)] {
+                            if ui.small_button(label).on_hover_text(format!("{} lines", lines)).clicked() {
+                                follow_lines = lines;
+                                changed = true;
+                            }
                         }
-                        if ui.small_button("5K").on_hover_text("5000 lines").clicked() {
-                            self.tail_state.preview_follow_lines = 5000;
-                            self.tail_state.preview_needs_reload = true;
+                        if changed {
+                            self.tail_state.set_preview_follow_lines(follow_lines);
                         }
                     });
                 });
@@ -894,6 +2083,11 @@ impl VisGrepApp {
                 self.tail_state.text_viewer_state.scroll_offset = self.tail_state.preview_scroll_offset;
                 self.tail_state.text_viewer_state.filter = self.tail_state.preview_filter.clone();
                 self.tail_state.text_viewer_state.font_size = self.tail_state.font_size;
+                self.tail_state.text_viewer_state.max_line_len = self.config.ui.max_display_line_len;
+                self.tail_state.text_viewer_state.highlight_level_token = self.config.ui.highlight_level_token;
+                self.tail_state.text_viewer_state.scroll_lines = self.config.ui.scroll_lines.max(1);
+                self.tail_state.text_viewer_state.page_lines = self.config.ui.page_lines.max(1);
+                self.tail_state.text_viewer_state.smooth_scroll = self.config.ui.smooth_scroll;
 
                 // Render the text viewer widget (it handles filter UI internally)
                 let color_scheme = self.config.log_format.get_color_scheme();
@@ -902,6 +2096,9 @@ impl VisGrepApp {
                     &self.tail_state.preview_content,
                     &self.log_detector,
                     &color_scheme,
+                    &self.redactor,
+                    &self.config.saved_filters,
+                    &self.tail_state.rule_highlighter,
                 );
                 viewer.show(ui);
 
@@ -912,10 +2109,14 @@ impl VisGrepApp {
                 };
                 self.tail_state.preview_scroll_offset = self.tail_state.text_viewer_state.scroll_offset;
                 self.tail_state.preview_filter = self.tail_state.text_viewer_state.filter.clone();
+
+                if let Some(saved) = self.tail_state.preview_filter.pending_save.take() {
+                    self.save_preview_filter(saved);
+                }
                 
                 // Handle editor opening outside of closures
                 if open_editor {
-                    self.open_file_in_editor(&file_path);
+                    self.open_file_in_editor(&file_path, None);
                 }
             } else {
                 // Invalid file index
@@ -940,6 +2141,8 @@ impl VisGrepApp {
     }
 
     pub fn handle_tail_mode_navigation(&mut self, ctx: &egui::Context) {
+        let typing = ctx.wants_keyboard_input();
+
         // Handle global tail mode shortcuts
         ctx.input(|i| {
             // + or = - increase update rate (decrease interval)
@@ -966,8 +2169,9 @@ impl VisGrepApp {
                 };
             }
 
-            // L - cycle log level filter (ALL -> INFO+ -> WARN+ -> ERROR -> ALL)
-            if i.key_pressed(egui::Key::L) && !i.modifiers.shift {
+            // L - cycle log level filter (ALL -> INFO+ -> WARN+ -> ERROR -> ALL).
+            // While a preview is open, plain l scrolls the preview sideways instead.
+            if i.key_pressed(egui::Key::L) && !i.modifiers.shift && self.tail_state.preview_selected_file.is_none() {
                 self.tail_state.log_level_filter.cycle_mode();
             }
 
@@ -975,10 +2179,39 @@ impl VisGrepApp {
             if i.key_pressed(egui::Key::L) && i.modifiers.shift {
                 self.tail_state.log_level_filter.cycle_mode_backwards();
             }
+
+            // Ctrl+B - bookmark the line at the bottom of the output view
+            if i.key_pressed(egui::Key::B) && i.modifiers.command {
+                if let Some(seq) = self.tail_state.output_bottom_seq {
+                    self.tail_state.toggle_output_bookmark(seq);
+                }
+            }
+
+            // F2 / Shift+F2 - next / previous output bookmark
+            if i.key_pressed(egui::Key::F2) {
+                self.tail_state.jump_to_output_bookmark(!i.modifiers.shift);
+            }
+
+            // ]e / [e - next / previous ERROR or FATAL line in the output.
+            // Read as typed text so the brackets work on any keyboard layout.
+            if !typing {
+                for event in &i.events {
+                    if let egui::Event::Text(text) = event {
+                        match (self.tail_state.pending_error_jump.take(), text.as_str()) {
+                            (Some(forward), "e") => self.tail_state.jump_to_error_line(forward),
+                            (_, "]") => self.tail_state.pending_error_jump = Some(true),
+                            (_, "[") => self.tail_state.pending_error_jump = Some(false),
+                            _ => {}
+                        }
+                    }
+                }
+            }
         });
         
-        // Handle preview navigation (if a file is selected)
-        if self.tail_state.preview_selected_file.is_some() {
+        self.handle_tree_navigation(ctx);
+
+        // Handle preview navigation (if a file is selected and the tree doesn't have the keys)
+        if self.tail_state.preview_selected_file.is_some() && !self.tail_state.tree_focused {
             // Use TextViewer's input handler for all navigation
             widgets::TextViewer::handle_input(
                 &mut self.tail_state.text_viewer_state,
@@ -1007,15 +2240,17 @@ impl VisGrepApp {
                         self.tail_state.text_viewer_state.goto_line_target = None;
                     }
                 }
-                // Ctrl+D - page down
-                if i.key_pressed(egui::Key::D) && i.modifiers.ctrl {
-                    self.tail_state.preview_scroll_offset += 400.0;
-                    self.tail_state.preview_mode = PreviewMode::Paused;
-                }
-                // Ctrl+U - page up
-                if i.key_pressed(egui::Key::U) && i.modifiers.ctrl {
-                    self.tail_state.preview_scroll_offset =
-                        (self.tail_state.preview_scroll_offset - 400.0).max(0.0);
+                // Ctrl+D / Ctrl+U - page down / up
+                let pages = if i.key_pressed(egui::Key::D) && i.modifiers.ctrl {
+                    1
+                } else if i.key_pressed(egui::Key::U) && i.modifiers.ctrl {
+                    -1
+                } else {
+                    0
+                };
+                if pages != 0 {
+                    self.tail_state.text_viewer_state.scroll_pages_by(pages);
+                    self.tail_state.preview_scroll_offset = self.tail_state.text_viewer_state.scroll_offset;
                     self.tail_state.preview_mode = PreviewMode::Paused;
                 }
             });
@@ -0,0 +1,62 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Bytes read per step while scanning back from the end
+const SCAN_CHUNK: u64 = 8192;
+
+/// Offset where the last `lines` lines of the first `size` bytes start, found by scanning
+/// back for newlines from `size`. A newline ending the final line doesn't count as the
+/// start of another. 0 when there are fewer lines than asked for.
+pub fn last_lines_offset<R: Read + Seek>(reader: &mut R, size: u64, lines: usize) -> io::Result<u64> {
+    if lines == 0 {
+        return Ok(size);
+    }
+
+    let mut newlines = 0;
+    let mut end = size;
+    let mut buffer = vec![0u8; SCAN_CHUNK as usize];
+    while end > 0 {
+        let start = end.saturating_sub(SCAN_CHUNK);
+        let chunk = &mut buffer[..(end - start) as usize];
+        reader.seek(SeekFrom::Start(start))?;
+        reader.read_exact(chunk)?;
+
+        for (idx, &byte) in chunk.iter().enumerate().rev() {
+            let offset = start + idx as u64;
+            if byte != b'\n' || offset + 1 == size {
+                continue;
+            }
+            newlines += 1;
+            if newlines == lines {
+                return Ok(offset + 1);
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_last_lines_offset() {
+        let data = b"one\ntwo\nthree\n";
+        let size = data.len() as u64;
+        let offset = |lines| last_lines_offset(&mut Cursor::new(&data[..]), size, lines).unwrap();
+        assert_eq!(offset(0), size);
+        assert_eq!(offset(1), 8);
+        assert_eq!(offset(2), 4);
+        assert_eq!(offset(10), 0);
+
+        // An unterminated last line counts as a line
+        let data = b"one\ntwo";
+        assert_eq!(last_lines_offset(&mut Cursor::new(&data[..]), 7, 1).unwrap(), 4);
+
+        // Newlines further back than one chunk
+        let long: Vec<u8> = [&b"start\n"[..], &vec![b'x'; 20_000], b"\nend\n"].concat();
+        let size = long.len() as u64;
+        assert_eq!(last_lines_offset(&mut Cursor::new(&long), size, 2).unwrap(), 6);
+    }
+}
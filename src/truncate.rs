@@ -0,0 +1,32 @@
+/// Cut a line down to `max_chars` characters for display. Returns the part to show and
+/// how many characters were left off, or None when the line fits (or `max_chars` is 0,
+/// meaning no limit).
+pub fn truncate_line(line: &str, max_chars: usize) -> Option<(&str, usize)> {
+    // A line can't have more characters than bytes, so short lines skip the char walk
+    if max_chars == 0 || line.len() <= max_chars {
+        return None;
+    }
+    let (cut, _) = line.char_indices().nth(max_chars)?;
+    Some((&line[..cut], line[cut..].chars().count()))
+}
+
+/// The marker shown after a truncated line
+pub fn hidden_marker(hidden_chars: usize) -> String {
+    format!("…(+{} chars, click to expand)", hidden_chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_line() {
+        assert_eq!(truncate_line("short", 10), None);
+        assert_eq!(truncate_line("0123456789abc", 10), Some(("0123456789", 3)));
+        assert_eq!(truncate_line("0123456789abc", 0), None);
+
+        // Cuts on character boundaries, counting characters rather than bytes
+        assert_eq!(truncate_line("ééééé", 2), Some(("éé", 3)));
+        assert_eq!(truncate_line("éé", 2), None);
+    }
+}
@@ -0,0 +1,55 @@
+/// A UTC calendar date and time of day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcDateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl UtcDateTime {
+    /// The date and time `secs` seconds after the Unix epoch
+    pub fn from_unix_secs(secs: u64) -> Self {
+        let (days, secs_of_day) = ((secs / 86_400) as i64, (secs % 86_400) as u32);
+
+        // Days since the epoch to a civil date (Howard Hinnant's civil_from_days)
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: secs_of_day / 3_600,
+            minute: secs_of_day % 3_600 / 60,
+            second: secs_of_day % 60,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_unix_secs() {
+        let epoch = UtcDateTime::from_unix_secs(0);
+        assert_eq!((epoch.year, epoch.month, epoch.day, epoch.hour), (1970, 1, 1, 0));
+
+        // A leap day
+        let time = UtcDateTime::from_unix_secs(1_709_210_096);
+        assert_eq!(
+            time,
+            UtcDateTime { year: 2024, month: 2, day: 29, hour: 12, minute: 34, second: 56 }
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeSet;
+
+/// Leading whitespace width of a line, tabs counting as 4; None for blank lines, which
+/// don't end a block
+fn indent(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(line[..line.len() - trimmed.len()].chars().map(|c| if c == '\t' { 4 } else { 1 }).sum())
+}
+
+/// Whether the line starts a block that can be folded: the next non-blank line is more
+/// indented than it
+pub fn is_foldable(content: &[String], header: usize) -> bool {
+    let Some(header_indent) = content.get(header).and_then(|line| indent(line)) else {
+        return false;
+    };
+    content[header + 1..]
+        .iter()
+        .find_map(|line| indent(line))
+        .is_some_and(|next| next > header_indent)
+}
+
+/// End (exclusive) of the block under `header`: the run of following lines more indented
+/// than it, not counting blank lines at the end. Equal to `header + 1` when there is none.
+pub fn fold_end(content: &[String], header: usize) -> usize {
+    let Some(header_indent) = content.get(header).and_then(|line| indent(line)) else {
+        return header + 1;
+    };
+    let mut end = header + 1;
+    for (idx, line) in content.iter().enumerate().skip(header + 1) {
+        match indent(line) {
+            Some(line_indent) if line_indent > header_indent => end = idx + 1,
+            Some(_) => break,
+            None => {}
+        }
+    }
+    end
+}
+
+/// Line indices left showing with the given fold headers collapsed, in order. Folds
+/// inside a collapsed block are hidden along with it.
+pub fn visible_lines(content: &[String], folds: &BTreeSet<usize>) -> Vec<usize> {
+    let mut lines = Vec::with_capacity(content.len());
+    let mut idx = 0;
+    while idx < content.len() {
+        lines.push(idx);
+        idx = if folds.contains(&idx) { fold_end(content, idx) } else { idx + 1 };
+    }
+    lines
+}
+
+/// Row showing `line` in `visible` (from visible_lines): its own, or the header of the
+/// fold hiding it
+pub fn row_of_line(visible: &[usize], line: usize) -> usize {
+    visible.partition_point(|&shown| shown <= line).saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folding_indented_blocks() {
+        let content: Vec<String> = [
+            "Exception in thread main",
+            "    at a.b(C.java:1)",
+            "",
+            "    at d.e(F.java:2)",
+            "",
+            "next entry",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert!(is_foldable(&content, 0));
+        assert!(!is_foldable(&content, 1));
+        assert_eq!(fold_end(&content, 0), 4);
+
+        let folds = BTreeSet::from([0]);
+        let visible = visible_lines(&content, &folds);
+        assert_eq!(visible, vec![0, 4, 5]);
+        assert_eq!(row_of_line(&visible, 2), 0);
+        assert_eq!(row_of_line(&visible, 5), 2);
+    }
+}
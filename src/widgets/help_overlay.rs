@@ -0,0 +1,57 @@
+use eframe::egui;
+use crate::input_handler::{BindingMode, KEY_BINDINGS};
+
+const MODES: [BindingMode; 4] = [
+    BindingMode::Global,
+    BindingMode::Grep,
+    BindingMode::Tail,
+    BindingMode::TailPreview,
+];
+
+/// Show the keyboard help overlay while `open` is true
+pub fn show_help_overlay(ctx: &egui::Context, open: &mut bool) {
+    if !*open {
+        return;
+    }
+
+    // Dim the app behind the overlay; the backdrop sits above panels and other windows
+    let screen_rect = ctx.screen_rect();
+    egui::Area::new(egui::Id::new("help_overlay_backdrop"))
+        .order(egui::Order::Middle)
+        .fixed_pos(screen_rect.min)
+        .show(ctx, |ui| {
+            ui.painter().rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(160));
+        });
+
+    egui::Window::new("Keyboard Shortcuts")
+        .open(open)
+        .collapsible(false)
+        .resizable(false)
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(screen_rect.height() * 0.7)
+                .show(ui, |ui| {
+                    for mode in MODES {
+                        ui.heading(mode.label());
+                        egui::Grid::new(("help_grid", mode.label()))
+                            .num_columns(2)
+                            .spacing([24.0, 4.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for binding in KEY_BINDINGS.iter().filter(|b| b.mode == mode) {
+                                    ui.label(
+                                        egui::RichText::new(binding.keys)
+                                            .monospace()
+                                            .color(egui::Color32::from_rgb(255, 200, 100)),
+                                    );
+                                    ui.label(binding.description);
+                                    ui.end_row();
+                                }
+                            });
+                        ui.add_space(8.0);
+                    }
+                });
+        });
+}
@@ -0,0 +1,99 @@
+use crate::redact::Redactor;
+use eframe::egui;
+
+/// The JSON part of a log line: the whole line if it starts with `{`/`[`, otherwise a
+/// trailing `{...}` object after a log prefix such as a timestamp and level
+fn json_payload(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return Some(trimmed);
+    }
+
+    let start = trimmed.find('{')?;
+    if trimmed.ends_with('}') {
+        Some(&trimmed[start..])
+    } else {
+        None
+    }
+}
+
+/// True if the line carries a JSON object/array that parses
+pub fn is_json_line(line: &str) -> bool {
+    json_payload(line)
+        .map(|payload| serde_json::from_str::<serde::de::IgnoredAny>(payload).is_ok())
+        .unwrap_or(false)
+}
+
+/// Reformat the JSON part of a line with indentation, keeping any log prefix on its
+/// own line above it. Returns None if the line holds no valid JSON.
+pub fn pretty_print_json(line: &str) -> Option<String> {
+    let payload = json_payload(line)?;
+    let value: serde_json::Value = serde_json::from_str(payload).ok()?;
+    let pretty = serde_json::to_string_pretty(&value).ok()?;
+
+    let prefix = line.trim()[..line.trim().len() - payload.len()].trim_end();
+    if prefix.is_empty() {
+        Some(pretty)
+    } else {
+        Some(format!("{}\n{}", prefix, pretty))
+    }
+}
+
+/// Show pretty-printed JSON in a closable window; `content` is cleared when it's closed
+pub fn show_json_window(ctx: &egui::Context, title: &str, content: &mut Option<String>, redactor: &Redactor) {
+    let Some(text) = content.as_ref() else {
+        return;
+    };
+    let text = redactor.redact(text);
+
+    let mut open = true;
+    egui::Window::new(title)
+        .open(&mut open)
+        .default_size([500.0, 400.0])
+        .resizable(true)
+        .show(ctx, |ui| {
+            if ui.button("📋 Copy").clicked() {
+                ui.ctx().copy_text(text.to_string());
+            }
+            ui.separator();
+            egui::ScrollArea::both()
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new(text.as_ref()).monospace());
+                });
+        });
+
+    if !open {
+        *content = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_json_line() {
+        let pretty = pretty_print_json(r#"{"a":1,"b":{"c":[1,2]}}"#).unwrap();
+        assert!(pretty.starts_with("{\n"));
+        assert!(pretty.contains("\"c\": [\n"));
+    }
+
+    #[test]
+    fn test_json_after_log_prefix() {
+        let line = r#"2024-01-15 10:00:00 INFO {"msg":"filled","qty":100}"#;
+        assert!(is_json_line(line));
+
+        let pretty = pretty_print_json(line).unwrap();
+        let mut lines = pretty.lines();
+        assert_eq!(lines.next(), Some("2024-01-15 10:00:00 INFO"));
+        assert_eq!(lines.next(), Some("{"));
+    }
+
+    #[test]
+    fn test_not_json() {
+        assert!(!is_json_line("INFO starting up"));
+        assert!(!is_json_line("{not json}"));
+        assert!(pretty_print_json("[broken").is_none());
+    }
+}
@@ -1,3 +1,7 @@
+pub mod fold;
+pub mod help_overlay;
+pub mod json_view;
 pub mod text_viewer;
+pub mod xml_view;
 
 pub use text_viewer::{TextViewer, TextViewerState, ViewMode};
@@ -1,9 +1,14 @@
 use eframe::egui;
 use log::info;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use crate::config::SavedFilter;
 use crate::filter;
+use crate::widgets::{fold, json_view, xml_view};
 use crate::log_parser::{LogLevelDetector, LogColorScheme};
 use crate::input_handler::{InputHandler, NavigationCommand};
+use crate::highlight::RuleHighlighter;
+use crate::redact::Redactor;
+use crate::truncate;
 
 /// View mode determines scrolling behavior
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -14,6 +19,9 @@ pub enum ViewMode {
     Paused,
 }
 
+/// Fraction of the remaining distance a smooth scroll covers each frame
+const SMOOTH_SCROLL_STEP: f32 = 0.4;
+
 /// State for the text viewer widget
 pub struct TextViewerState {
     /// Current view mode (following/paused)
@@ -22,12 +30,42 @@ pub struct TextViewerState {
     /// Scroll offset when in Paused mode
     pub scroll_offset: f32,
 
+    /// Horizontal scroll offset, moved by h/l and jumped by 0/$
+    pub horizontal_offset: f32,
+
+    /// Pixels per h/l press
+    pub horizontal_step: f32,
+
     /// Filter state
     pub filter: filter::PreviewFilter,
 
     /// Font size for rendering
     pub font_size: f32,
 
+    /// Height of one row including spacing, measured on the last render
+    pub row_pitch: f32,
+
+    /// Lines moved per j/k press
+    pub scroll_lines: usize,
+
+    /// Lines moved per Ctrl+D/Ctrl+U
+    pub page_lines: usize,
+
+    /// Ease j/k and page scrolls toward their target over a few frames instead of jumping
+    pub smooth_scroll: bool,
+
+    /// Where a smooth scroll is heading; `scroll_offset` moves toward it each frame
+    scroll_target: Option<f32>,
+
+    /// Lines longer than this many characters are cut short until clicked (0 = no limit)
+    pub max_line_len: usize,
+
+    /// Mark the token each line's level was detected from
+    pub highlight_level_token: bool,
+
+    /// Over-long lines (0-indexed) the user expanded to full length
+    pub expanded_lines: HashSet<usize>,
+
     /// Goto line mode active
     pub goto_line_active: bool,
 
@@ -51,15 +89,68 @@ pub struct TextViewerState {
 
     /// Input handler for vim-style navigation
     pub input_handler: InputHandler,
+
+    /// Pretty-printed JSON of a line, shown in a popup while Some
+    pub json_view: Option<String>,
+
+    /// Reindented XML of a line, shown in a popup while Some
+    pub xml_view: Option<String>,
+
+    /// Lines pinned to the strip above the content, in the order they were pinned
+    pub pinned_lines: Vec<PinnedLine>,
+
+    /// Lines (0-indexed) whose indented block below is folded away
+    pub folds: BTreeSet<usize>,
+
+    /// What each content line can be pretty-printed as, found once when the line arrives
+    pub line_formats: Vec<Option<LineFormat>>,
+}
+
+/// A line that can be pretty-printed in a popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineFormat {
+    Json,
+    Xml,
+}
+
+impl LineFormat {
+    pub fn detect(line: &str) -> Option<Self> {
+        if json_view::is_json_line(line) {
+            Some(Self::Json)
+        } else if xml_view::is_xml_line(line) {
+            Some(Self::Xml)
+        } else {
+            None
+        }
+    }
+}
+
+/// A line kept in view above the content. The text is kept so the pin still shows, and
+/// can be found again, after the content reloads and indices shift.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinnedLine {
+    /// 0-indexed line in the content when pinned
+    pub line_idx: usize,
+    pub text: String,
 }
 
 impl TextViewerState {
-    pub fn new(font_size: f32) -> Self {
+    pub fn new(font_size: f32, horizontal_step: f32) -> Self {
         Self {
             view_mode: ViewMode::Following,
             scroll_offset: 0.0,
+            horizontal_offset: 0.0,
+            horizontal_step,
             filter: filter::PreviewFilter::new(),
             font_size,
+            row_pitch: font_size + 4.0,
+            scroll_lines: 1,
+            page_lines: 20,
+            smooth_scroll: true,
+            scroll_target: None,
+            max_line_len: 0,
+            highlight_level_token: true,
+            expanded_lines: HashSet::new(),
             goto_line_active: false,
             goto_line_input: String::new(),
             goto_line_target: None,
@@ -68,7 +159,152 @@ impl TextViewerState {
             marks: HashMap::new(),
             last_navigated_line: None,
             input_handler: InputHandler::new(),
+            json_view: None,
+            xml_view: None,
+            pinned_lines: Vec::new(),
+            folds: BTreeSet::new(),
+            line_formats: Vec::new(),
+        }
+    }
+
+    /// Scroll by a number of lines (negative is up) and pause following
+    pub fn scroll_lines_by(&mut self, lines: isize) {
+        let from = self.scroll_target.unwrap_or(self.scroll_offset);
+        let target = (from + lines as f32 * self.row_pitch).max(0.0);
+        if self.smooth_scroll {
+            self.scroll_target = Some(target);
+        } else {
+            self.scroll_offset = target;
         }
+        self.view_mode = ViewMode::Paused;
+    }
+
+    /// Scroll by `page_lines` pages (negative is up)
+    pub fn scroll_pages_by(&mut self, pages: isize) {
+        self.scroll_lines_by(pages * self.page_lines as isize);
+    }
+
+    /// Move `scroll_offset` part of the way toward the smooth-scroll target, clamped to
+    /// `max_offset`. Returns true while there is still distance to cover.
+    fn step_smooth_scroll(&mut self, max_offset: f32) -> bool {
+        let Some(target) = self.scroll_target else {
+            return false;
+        };
+        let target = target.min(max_offset);
+        let remaining = target - self.scroll_offset;
+        if remaining.abs() < 1.0 {
+            self.scroll_offset = target;
+            self.scroll_target = None;
+            false
+        } else {
+            self.scroll_offset += remaining * SMOOTH_SCROLL_STEP;
+            true
+        }
+    }
+
+    /// Pin the line, or unpin it if it's already pinned
+    pub fn toggle_pin(&mut self, line_idx: usize, text: &str) {
+        let before = self.pinned_lines.len();
+        self.pinned_lines.retain(|pin| pin.line_idx != line_idx);
+        if self.pinned_lines.len() == before {
+            self.pinned_lines.push(PinnedLine {
+                line_idx,
+                text: text.to_string(),
+            });
+        }
+    }
+
+    /// Keep marks, folds, pins and expanded lines on the same file lines when the content's
+    /// window over the file moves from `old_first_line` to `first_line`; those whose line
+    /// left the window are dropped
+    pub fn shift_lines(&mut self, old_first_line: usize, first_line: usize) {
+        if old_first_line == first_line {
+            return;
+        }
+        let shift = |idx: usize| (idx + old_first_line).checked_sub(first_line);
+        self.marks.retain(|_, idx| match shift(*idx) {
+            Some(shifted) => {
+                *idx = shifted;
+                true
+            }
+            None => false,
+        });
+        self.pinned_lines.retain_mut(|pin| match shift(pin.line_idx) {
+            Some(shifted) => {
+                pin.line_idx = shifted;
+                true
+            }
+            None => false,
+        });
+        self.folds = self.folds.iter().filter_map(|&idx| shift(idx)).collect();
+        self.expanded_lines = self.expanded_lines.iter().filter_map(|&idx| shift(idx)).collect();
+    }
+
+    /// Find each line's format after the content was replaced, reusing what was found for
+    /// lines that are still there now the window over the file moved from `old_first_line`
+    /// to `first_line`
+    pub fn update_line_formats(
+        &mut self,
+        old_content: &[String],
+        content: &[String],
+        old_first_line: usize,
+        first_line: usize,
+    ) {
+        let old_formats = std::mem::take(&mut self.line_formats);
+        self.line_formats = content
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                let old_idx = (idx + first_line).checked_sub(old_first_line);
+                match old_idx.filter(|&old_idx| old_content.get(old_idx) == Some(line)) {
+                    Some(old_idx) => old_formats.get(old_idx).copied().flatten(),
+                    None => LineFormat::detect(line),
+                }
+            })
+            .collect();
+    }
+
+    /// Format of a content line, from the cache when it's in step with the content
+    fn line_format(&self, content: &[String], line_idx: usize) -> Option<LineFormat> {
+        if self.line_formats.len() == content.len() {
+            self.line_formats.get(line_idx).copied().flatten()
+        } else {
+            content.get(line_idx).and_then(|line| LineFormat::detect(line))
+        }
+    }
+
+    fn is_pinned(&self, line_idx: usize) -> bool {
+        self.pinned_lines.iter().any(|pin| pin.line_idx == line_idx)
+    }
+
+    /// Fold the indented block under the line, or unfold it if it's folded
+    pub fn toggle_fold(&mut self, content: &[String], line_idx: usize) {
+        if !self.folds.remove(&line_idx) && fold::is_foldable(content, line_idx) {
+            self.folds.insert(line_idx);
+        }
+    }
+
+    /// Lines showing with the current folds, None when nothing is folded (every line shows)
+    fn visible_lines(&self, content: &[String]) -> Option<Vec<usize>> {
+        (!self.folds.is_empty()).then(|| fold::visible_lines(content, &self.folds))
+    }
+
+    /// Line shown in the given row, e.g. the one at the top of the view
+    fn line_at_row(&self, content: &[String], row: usize) -> usize {
+        self.visible_lines(content)
+            .and_then(|lines| lines.get(row).copied())
+            .unwrap_or(row)
+    }
+}
+
+impl PinnedLine {
+    /// Where the pinned line is in `content` now: where it was if the text still matches
+    /// there, otherwise the last line with the same text
+    pub fn locate(&self, content: &[String]) -> Option<usize> {
+        if content.get(self.line_idx) == Some(&self.text) {
+            return Some(self.line_idx);
+        }
+        content.iter().rposition(|line| *line == self.text)
     }
 }
 
@@ -78,6 +314,9 @@ pub struct TextViewer<'a> {
     content: &'a [String],
     log_detector: &'a LogLevelDetector,
     color_scheme: &'a LogColorScheme,
+    redactor: &'a Redactor,
+    saved_filters: &'a [SavedFilter],
+    highlight_rules: &'a RuleHighlighter,
 }
 
 impl<'a> TextViewer<'a> {
@@ -86,12 +325,18 @@ impl<'a> TextViewer<'a> {
         content: &'a [String],
         log_detector: &'a LogLevelDetector,
         color_scheme: &'a LogColorScheme,
+        redactor: &'a Redactor,
+        saved_filters: &'a [SavedFilter],
+        highlight_rules: &'a RuleHighlighter,
     ) -> Self {
         Self {
             state,
             content,
             log_detector,
             color_scheme,
+            redactor,
+            saved_filters,
+            highlight_rules,
         }
     }
 
@@ -99,7 +344,7 @@ impl<'a> TextViewer<'a> {
     pub fn show(mut self, ui: &mut egui::Ui) {
         // Handle filter input and update matches if filter changed
         let mut scroll_to_match = false;
-        if filter::preview::render_filter_input(ui, &mut self.state.filter) {
+        if filter::preview::render_filter_input(ui, &mut self.state.filter, self.saved_filters) {
             // Filter changed, update matches
             scroll_to_match = filter::preview::update_filter_matches(
                 &mut self.state.filter,
@@ -115,82 +360,179 @@ impl<'a> TextViewer<'a> {
 
         // Handle goto line input
         self.render_goto_line_input(ui);
+        self.render_pinned_lines(ui);
 
         // Capture goto target and scroll_to_bottom flag for use inside scroll area
         let goto_target = self.state.goto_line_target;
         let scroll_to_bottom = self.state.scroll_to_bottom;
 
+        // Uniform row height so only the visible rows need laying out. Rows are built with
+        // ui.horizontal, which is never shorter than interact_size.y.
+        let font_id = egui::FontId::new(self.state.font_size, egui::FontFamily::Monospace);
+        let row_height = ui.fonts(|f| f.row_height(&font_id))
+            .max(ui.spacing().interact_size.y);
+        let row_pitch = row_height + ui.spacing().item_spacing.y;
+        self.state.row_pitch = row_pitch;
+        let viewport_height = ui.available_height();
+
+        // Folded blocks take no rows, so rows and line indices differ once anything is folded
+        let visible_lines = self.state.visible_lines(self.content);
+        let row_count = visible_lines.as_ref().map_or(self.content.len(), Vec::len);
+        let row_of_line = |line: usize| visible_lines.as_ref().map_or(line, |lines| fold::row_of_line(lines, line));
+
+        let max_offset = (row_count as f32 * row_pitch - viewport_height).max(0.0);
+        if self.state.step_smooth_scroll(max_offset) {
+            ui.ctx().request_repaint();
+        }
+
+        // Rows that aren't laid out have no rect to scroll to, so translate jump
+        // targets into a row index and from there into a scroll offset
+        let jump_row = if scroll_to_match {
+            self.state.filter.current_match_line()
+        } else {
+            None
+        }.or(goto_target).map(row_of_line);
+
         // Content area - use all available space
-        // When we have a goto_line_target or scroll_to_bottom, don't set scroll_offset - let scroll_to_rect handle it
-        let scroll_area = if self.state.view_mode == ViewMode::Following {
-            egui::ScrollArea::both()
-                .stick_to_bottom(true)
-                .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysVisible)
-        } else if goto_target.is_some() || scroll_to_bottom {
-            // Don't set scroll_offset when goto or scroll_to_bottom is active
-            egui::ScrollArea::both()
-                .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysVisible)
+        let scroll_area = egui::ScrollArea::both()
+            .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysVisible);
+        let scroll_area = if let Some(row) = jump_row {
+            info!("Scrolling to line_idx: {}", row);
+            scroll_area.vertical_scroll_offset(Self::centered_row_offset(row, row_pitch, viewport_height))
+        } else if self.state.view_mode == ViewMode::Following {
+            scroll_area.stick_to_bottom(true)
+        } else if scroll_to_bottom {
+            info!("Scrolling to bottom (last line): {}", self.content.len().saturating_sub(1));
+            scroll_area.vertical_scroll_offset(row_count as f32 * row_pitch)
         } else {
-            egui::ScrollArea::both()
-                .scroll_offset(egui::Vec2::new(0.0, self.state.scroll_offset))
-                .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysVisible)
+            scroll_area.vertical_scroll_offset(self.state.scroll_offset)
         };
 
-        let scroll_output = scroll_area
+        let scroll_area = scroll_area
+            .horizontal_scroll_offset(self.state.horizontal_offset)
             .id_salt("text_viewer_scroll")
-            .auto_shrink([false, false])
-            .show(ui, |ui| {
+            .auto_shrink([false, false]);
+
+        let scroll_output = if self.content.is_empty() {
+            scroll_area.show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new("No content to display")
+                        .italics()
+                        .color(egui::Color32::GRAY),
+                );
+            })
+        } else {
+            scroll_area.show_rows(ui, row_height, row_count, |ui, row_range| {
                 ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
 
                 // Apply custom font size
-                let font_id = egui::FontId::new(self.state.font_size, egui::FontFamily::Monospace);
-                ui.style_mut().text_styles.insert(egui::TextStyle::Monospace, font_id);
-
-                if self.content.is_empty() {
-                    ui.label(
-                        egui::RichText::new("No content to display")
-                            .italics()
-                            .color(egui::Color32::GRAY),
-                    );
-                } else {
-                    for (line_idx, line) in self.content.iter().enumerate() {
-                        let is_match = self.state.filter.match_lines.contains(&line_idx);
-                        let is_current = self.state.filter.current_match_line() == Some(line_idx);
-                        let is_last_line = line_idx == self.content.len() - 1;
+                ui.style_mut().text_styles.insert(egui::TextStyle::Monospace, font_id.clone());
+
+                for row in row_range {
+                    let line_idx = visible_lines.as_ref().map_or(row, |lines| lines[row]);
+                    let line = &self.content[line_idx];
+                    let is_match = self.state.filter.match_lines.contains(&line_idx);
+                    let is_current = self.state.filter.current_match_line() == Some(line_idx);
+
+                    ui.horizontal(|ui| {
+                        // Fold toggle for lines with an indented block below; the space is
+                        // kept on every row so the text stays aligned
+                        let (toggle_rect, toggle) = ui.allocate_exact_size(
+                            egui::vec2(font_id.size, row_height),
+                            egui::Sense::click(),
+                        );
+                        let folded = self.state.folds.contains(&line_idx);
+                        if folded || fold::is_foldable(self.content, line_idx) {
+                            ui.painter().text(
+                                toggle_rect.center(),
+                                egui::Align2::CENTER_CENTER,
+                                if folded { "▶" } else { "▼" },
+                                egui::FontId::proportional(font_id.size * 0.7),
+                                egui::Color32::GRAY,
+                            );
+                            if toggle.on_hover_text(if folded { "Unfold (za)" } else { "Fold the indented block below (za)" }).clicked() {
+                                self.state.toggle_fold(self.content, line_idx);
+                            }
+                        }
 
-                        let response = filter::preview::render_filtered_line(
+                        let redacted = self.redactor.redact(line);
+                        let truncated = truncate::truncate_line(&redacted, self.state.max_line_len);
+                        let expanded = self.state.expanded_lines.contains(&line_idx);
+                        let shown = match truncated {
+                            Some((shown, _)) if !expanded => shown,
+                            _ => &redacted,
+                        };
+                        let gutter = filter::preview::render_filtered_line(
                             ui,
-                            line,
+                            shown,
                             line_idx + 1,
                             is_match,
                             is_current,
                             &self.state.filter,
-                            self.log_detector,
-                            self.color_scheme,
+                            &filter::preview::LineStyle {
+                                log_detector: self.log_detector,
+                                color_scheme: self.color_scheme,
+                                highlight_level_token: self.state.highlight_level_token,
+                                highlight_rules: self.highlight_rules,
+                            },
                         );
-
-                        // If we should scroll to this match, make it visible using actual rect
-                        if scroll_to_match && is_current {
-                            ui.scroll_to_rect(response.rect, Some(egui::Align::Center));
+                        let pinned = self.state.is_pinned(line_idx);
+                        if pinned {
+                            ui.painter().rect_stroke(
+                                gutter.rect,
+                                2.0,
+                                egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 165, 0)),
+                            );
+                        }
+                        if gutter
+                            .on_hover_text(if pinned { "Click to unpin" } else { "Click to pin this line above the preview" })
+                            .clicked()
+                        {
+                            self.state.toggle_pin(line_idx, line);
                         }
 
-                        // If we should scroll to goto line target, make it visible using actual rect
-                        if let Some(target_line) = goto_target {
-                            if line_idx == target_line {
-                                info!("Scrolling to line_idx: {}, target_line: {}, rect: {:?}", line_idx, target_line, response.rect);
-                                ui.scroll_to_rect(response.rect, Some(egui::Align::Center));
+                        if let Some((_, hidden)) = truncated {
+                            let marker = if expanded { "▲ collapse".to_string() } else { truncate::hidden_marker(hidden) };
+                            if ui.add(egui::Label::new(egui::RichText::new(marker).italics().color(egui::Color32::GRAY)).sense(egui::Sense::click()))
+                                .clicked()
+                                && !self.state.expanded_lines.remove(&line_idx)
+                            {
+                                self.state.expanded_lines.insert(line_idx);
+                            }
+                            if ui.small_button("📋").on_hover_text("Copy the full line").clicked() {
+                                match arboard::Clipboard::new() {
+                                    Ok(mut clipboard) => {
+                                        if let Err(e) = clipboard.set_text(redacted.to_string()) {
+                                            log::error!("Failed to copy line: {}", e);
+                                        }
+                                    }
+                                    Err(e) => log::error!("Failed to access clipboard: {}", e),
+                                }
                             }
                         }
 
-                        // If we should scroll to bottom (G command), scroll to last line
-                        if scroll_to_bottom && is_last_line {
-                            info!("Scrolling to bottom (last line): {}", line_idx);
-                            // Scroll to show last line at bottom of viewport
-                            ui.scroll_to_rect(response.rect, None);
+                        if folded {
+                            let hidden = fold::fold_end(self.content, line_idx) - line_idx - 1;
+                            ui.label(egui::RichText::new(format!("⋯ {} lines folded", hidden)).italics().color(egui::Color32::GRAY));
                         }
-                    }
+
+                        let format = self.state.line_format(self.content, line_idx);
+                        if format == Some(LineFormat::Json)
+                            && ui.small_button("{ }").on_hover_text("Pretty-print JSON (zj)").clicked()
+                        {
+                            self.state.json_view = json_view::pretty_print_json(line);
+                        } else if format == Some(LineFormat::Xml)
+                            && ui.small_button("</>").on_hover_text("Pretty-print XML (zj)").clicked()
+                        {
+                            self.state.xml_view = xml_view::pretty_print_xml(line);
+                        }
+                    });
                 }
-            });
+            })
+        };
+
+        json_view::show_json_window(ui.ctx(), "JSON", &mut self.state.json_view, self.redactor);
+        json_view::show_json_window(ui.ctx(), "XML", &mut self.state.xml_view, self.redactor);
 
         // Clear goto target and scroll_to_bottom after scroll area completes
         if goto_target.is_some() {
@@ -202,6 +544,9 @@ impl<'a> TextViewer<'a> {
             self.state.scroll_to_bottom = false;
         }
 
+        // Offsets past the content width are clamped by the scroll area; keep the clamped value
+        self.state.horizontal_offset = scroll_output.state.offset.x;
+
         // Update scroll offset
         if self.state.view_mode == ViewMode::Following {
             // In Following mode, we don't track manual scrolls
@@ -220,7 +565,7 @@ impl<'a> TextViewer<'a> {
         ui.separator();
         ui.horizontal(|ui| {
             ui.label(
-                egui::RichText::new("j/k: scroll  gg/G: jump  /: filter  n/N: next/prev match  :: goto line  ma/'a: mark/goto")
+                egui::RichText::new("j/k/h/l: scroll  0/$: line start/end  gg/G: jump  /: filter  n/N: next/prev match  :: goto line  ma/'a: mark/goto  zz: center  zj: JSON/XML  za: fold  click line no.: pin  ?: help")
                     .color(egui::Color32::GRAY)
                     .small(),
             );
@@ -243,6 +588,56 @@ impl<'a> TextViewer<'a> {
         });
     }
 
+    /// Scroll offset that places `row` in the middle of a viewport of the given height
+    fn centered_row_offset(row: usize, row_pitch: f32, viewport_height: f32) -> f32 {
+        (row as f32 * row_pitch - (viewport_height - row_pitch) / 2.0).max(0.0)
+    }
+
+    /// Collapsible strip of pinned lines; clicking one scrolls the content to it
+    fn render_pinned_lines(&mut self, ui: &mut egui::Ui) {
+        if self.state.pinned_lines.is_empty() {
+            return;
+        }
+
+        let mut jump_to = None;
+        let mut unpin = None;
+        egui::CollapsingHeader::new(format!("📌 Pinned ({})", self.state.pinned_lines.len()))
+            .id_salt("text_viewer_pinned")
+            .default_open(true)
+            .show(ui, |ui| {
+                for (pin_idx, pin) in self.state.pinned_lines.iter().enumerate() {
+                    let location = pin.locate(self.content);
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✖").on_hover_text("Unpin").clicked() {
+                            unpin = Some(pin_idx);
+                        }
+                        let number = location.map_or("gone".to_string(), |idx| format!("{}", idx + 1));
+                        let text = self.redactor.redact(&pin.text);
+                        let shown = truncate::truncate_line(&text, 200).map_or(text.as_ref(), |(shown, _)| shown);
+                        let response = ui
+                            .add_enabled(
+                                location.is_some(),
+                                egui::Button::new(egui::RichText::new(format!("{:>4}  {}", number, shown)).monospace())
+                                    .frame(false),
+                            )
+                            .on_disabled_hover_text("No longer in the preview");
+                        if response.clicked() {
+                            jump_to = location;
+                        }
+                    });
+                }
+            });
+
+        if let Some(pin_idx) = unpin {
+            self.state.pinned_lines.remove(pin_idx);
+        }
+        if let Some(target) = jump_to {
+            self.state.goto_line_target = Some(target);
+            self.state.last_navigated_line = Some(target);
+            self.state.view_mode = ViewMode::Paused;
+        }
+    }
+
     fn render_goto_line_input(&mut self, ui: &mut egui::Ui) {
         if self.state.goto_line_active {
             ui.horizontal(|ui| {
@@ -287,7 +682,7 @@ impl<'a> TextViewer<'a> {
     /// Call this from your event handler to process navigation commands
     pub fn handle_input(
         state: &mut TextViewerState,
-        _content: &[String],
+        content: &[String],
         ctx: &egui::Context,
     ) -> bool {
         // Check if any text input is focused (skip vim keys if typing)
@@ -328,15 +723,27 @@ impl<'a> TextViewer<'a> {
                 }
             }
 
-            // Simple j/k scrolling (not in InputHandler, too specific)
-            if !state.goto_line_active && !state.filter.active {
+            // Simple j/k scrolling (not in InputHandler, too specific). Skipped while a
+            // sequence like "z" is pending so the InputHandler sees the second key.
+            if !state.goto_line_active && !state.filter.active && !state.input_handler.has_pending_keys() {
                 if i.key_pressed(egui::Key::J) {
-                    state.scroll_offset += state.font_size + 4.0;
-                    state.view_mode = ViewMode::Paused;
+                    state.scroll_lines_by(state.scroll_lines as isize);
                     handled = true;
                 } else if i.key_pressed(egui::Key::K) {
-                    state.scroll_offset = (state.scroll_offset - (state.font_size + 4.0)).max(0.0);
-                    state.view_mode = ViewMode::Paused;
+                    state.scroll_lines_by(-(state.scroll_lines as isize));
+                    handled = true;
+                } else if i.key_pressed(egui::Key::L) && !i.modifiers.shift {
+                    state.horizontal_offset += state.horizontal_step;
+                    handled = true;
+                } else if i.key_pressed(egui::Key::H) && !i.modifiers.shift {
+                    state.horizontal_offset = (state.horizontal_offset - state.horizontal_step).max(0.0);
+                    handled = true;
+                } else if i.key_pressed(egui::Key::Num0)
+                    && !i.modifiers.any()
+                    && state.input_handler.get_status().is_empty()
+                {
+                    // 0 - back to the start of the lines (a 0 after a count digit is part of the count)
+                    state.horizontal_offset = 0.0;
                     handled = true;
                 }
             }
@@ -379,8 +786,7 @@ impl<'a> TextViewer<'a> {
                         let mark_line = if let Some(line) = state.last_navigated_line {
                             line
                         } else {
-                            let line_height = state.font_size + 4.0;
-                            (state.scroll_offset / line_height) as usize
+                            state.line_at_row(content, (state.scroll_offset / state.row_pitch) as usize)
                         };
                         state.marks.insert(mark_char, mark_line);
                         info!("Set mark '{}' at line {} (1-indexed: {})", mark_char, mark_line, mark_line + 1);
@@ -398,6 +804,51 @@ impl<'a> TextViewer<'a> {
                             info!("Mark '{}' not set", mark_char);
                         }
                     }
+                    NavigationCommand::ToggleJsonView => {
+                        // zj - pretty-print the current line as JSON or XML (last navigated, else current match, else top of view)
+                        if state.json_view.is_some() || state.xml_view.is_some() {
+                            state.json_view = None;
+                            state.xml_view = None;
+                        } else {
+                            let line_idx = state.last_navigated_line
+                                .or(state.filter.current_match_line())
+                                .unwrap_or_else(|| state.line_at_row(content, (state.scroll_offset / state.row_pitch) as usize));
+                            let line = content.get(line_idx).map(String::as_str).unwrap_or_default();
+                            state.json_view = json_view::pretty_print_json(line);
+                            if state.json_view.is_none() {
+                                state.xml_view = xml_view::pretty_print_xml(line);
+                            }
+                            if state.json_view.is_none() && state.xml_view.is_none() {
+                                info!("Line {} is not JSON or XML", line_idx + 1);
+                            }
+                        }
+                        handled = true;
+                    }
+                    NavigationCommand::LastMatchInCurrentFile => {
+                        // $ - scroll to the end of the longest line (clamped by the scroll area)
+                        state.horizontal_offset = f32::MAX;
+                        handled = true;
+                    }
+                    NavigationCommand::CenterView => {
+                        // zz - recenter on the current line (last navigated, else current match)
+                        // without moving it
+                        if let Some(line_idx) = state.last_navigated_line.or(state.filter.current_match_line()) {
+                            state.goto_line_target = Some(line_idx);
+                            state.view_mode = ViewMode::Paused;
+                        } else {
+                            info!("zz: no current line to center on");
+                        }
+                        handled = true;
+                    }
+                    NavigationCommand::ToggleFold => {
+                        // za - fold/unfold the block under the current line (last navigated,
+                        // else current match, else top of view)
+                        let line_idx = state.last_navigated_line
+                            .or(state.filter.current_match_line())
+                            .unwrap_or_else(|| state.line_at_row(content, (state.scroll_offset / state.row_pitch) as usize));
+                        state.toggle_fold(content, line_idx);
+                        handled = true;
+                    }
                     _ => {
                         // Other commands not applicable to text viewer
                     }
@@ -0,0 +1,174 @@
+/// The XML part of a log line: from the first `<` to the last `>`, when that starts like
+/// a tag (`<name`, `<?xml`, `<!--`)
+fn xml_payload(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
+    let start = trimmed.find('<')?;
+    let end = trimmed.rfind('>')?;
+    let payload = trimmed.get(start..=end)?;
+    let first = payload[1..].chars().next()?;
+    (first.is_alphabetic() || first == '?' || first == '!').then_some(payload)
+}
+
+/// A piece of XML: markup from `<` to its closing `>`, or the text between markup
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Open { name: &'a str, markup: &'a str },
+    Close { name: &'a str, markup: &'a str },
+    /// Self-closing elements, declarations, comments and CDATA
+    Leaf(&'a str),
+    Text(&'a str),
+}
+
+fn tag_name(markup: &str) -> &str {
+    let inner = markup.trim_start_matches(['<', '/']);
+    let end = inner.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(inner.len());
+    &inner[..end]
+}
+
+/// Split the payload into tokens; None if markup isn't closed
+fn tokenize(xml: &str) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < xml.len() {
+        let rest = &xml[pos..];
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            tokens.push(Token::Text(&rest[..end]));
+            pos += end;
+            continue;
+        }
+
+        let len = if rest.starts_with("<!--") {
+            rest.find("-->")? + 3
+        } else if rest.starts_with("<![CDATA[") {
+            rest.find("]]>")? + 3
+        } else {
+            // The closing '>' outside quoted attribute values
+            let mut quote = None;
+            let end = rest.char_indices().skip(1).find(|&(_, c)| match quote {
+                Some(q) if c == q => {
+                    quote = None;
+                    false
+                }
+                Some(_) => false,
+                None if c == '"' || c == '\'' => {
+                    quote = Some(c);
+                    false
+                }
+                None => c == '>',
+            })?;
+            end.0 + 1
+        };
+
+        let markup = &rest[..len];
+        tokens.push(if markup.starts_with("</") {
+            Token::Close { name: tag_name(markup), markup }
+        } else if markup.starts_with("<?") || markup.starts_with("<!") || markup.ends_with("/>") {
+            Token::Leaf(markup)
+        } else {
+            Token::Open { name: tag_name(markup), markup }
+        });
+        pos += len;
+    }
+    Some(tokens)
+}
+
+/// Reindent the XML part of a line, one element per line with two spaces per level; an
+/// element holding only text stays on one line. Any log prefix goes on its own line
+/// above. None if the line holds no well-formed XML elements.
+pub fn pretty_print_xml(line: &str) -> Option<String> {
+    let payload = xml_payload(line)?;
+    let tokens = tokenize(payload)?;
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut open: Vec<&str> = Vec::new();
+    let mut elements = 0;
+    let mut idx = 0;
+    while idx < tokens.len() {
+        let indent = "  ".repeat(open.len());
+        match &tokens[idx] {
+            Token::Open { name, markup } => {
+                elements += 1;
+                // <a>text</a> on one line
+                if let (Some(Token::Text(text)), Some(Token::Close { name: close, markup: end })) =
+                    (tokens.get(idx + 1), tokens.get(idx + 2))
+                {
+                    if close == name {
+                        lines.push(format!("{}{}{}{}", indent, markup, text.trim(), end));
+                        idx += 3;
+                        continue;
+                    }
+                }
+                lines.push(format!("{}{}", indent, markup));
+                open.push(name);
+            }
+            Token::Close { name, markup } => {
+                if open.pop() != Some(*name) {
+                    return None;
+                }
+                lines.push(format!("{}{}", "  ".repeat(open.len()), markup));
+            }
+            Token::Leaf(markup) => {
+                elements += usize::from(markup.ends_with("/>"));
+                lines.push(format!("{}{}", indent, markup));
+            }
+            Token::Text(text) => {
+                if !text.trim().is_empty() {
+                    lines.push(format!("{}{}", indent, text.trim()));
+                }
+            }
+        }
+        idx += 1;
+    }
+    if !open.is_empty() || elements == 0 {
+        return None;
+    }
+
+    let pretty = lines.join("\n");
+    let trimmed = line.trim();
+    let prefix = trimmed[..trimmed.find('<')?].trim_end();
+    if prefix.is_empty() {
+        Some(pretty)
+    } else {
+        Some(format!("{}\n{}", prefix, pretty))
+    }
+}
+
+/// True if the line carries well-formed XML elements
+pub fn is_xml_line(line: &str) -> bool {
+    // Cheap check first: most lines have no markup at all
+    (line.contains("</") || line.contains("/>")) && pretty_print_xml(line).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pretty_print_with_prefix() {
+        let line = r#"10:00:01 INFO recv <FIXML v="5.0"><Order ID="1" Side='>'><Instrmt Sym="AAPL"/><Qty>100</Qty></Order></FIXML>"#;
+        assert!(is_xml_line(line));
+        let pretty = pretty_print_xml(line).unwrap();
+        assert_eq!(
+            pretty,
+            [
+                "10:00:01 INFO recv",
+                r#"<FIXML v="5.0">"#,
+                r#"  <Order ID="1" Side='>'>"#,
+                r#"    <Instrmt Sym="AAPL"/>"#,
+                "    <Qty>100</Qty>",
+                "  </Order>",
+                "</FIXML>",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_not_xml() {
+        assert!(!is_xml_line("INFO a < b and c > d"));
+        assert!(pretty_print_xml("<a><b></a></b>").is_none());
+        assert!(pretty_print_xml("<a><b>").is_none());
+        assert!(pretty_print_xml("x <!-- just a comment -->").is_none());
+    }
+}